@@ -0,0 +1,108 @@
+// Ingestion throughput of one upsert per interval against bulk upserts per page.
+//
+// Runs against the Mongo at `MONGOURI` (a local one by default) in a scratch database:
+//     cargo bench --bench bulk_upsert
+// `BENCH_ROWS` sets the number of intervals written by each path.
+
+use std::{env, time::Instant};
+
+use mongodb::{bson::doc, options::ReplaceOptions, Client};
+use rust_api::{
+    models::depth_history_model::DepthHistory,
+    repository::{depth_history_repo::DepthHistoryRepository, history_store::HistoryStore},
+};
+use serde_json::json;
+
+const PAGE_SIZE: usize = 400;
+
+fn depth_histories(rows: usize) -> Vec<DepthHistory> {
+    (0..rows)
+        .map(|i| {
+            let start_time = 1648771200 + i as i64 * 3600;
+
+            serde_json::from_value(json!({
+                "startTime": start_time.to_string(),
+                "endTime": (start_time + 3600).to_string(),
+                "assetDepth": "1000000000",
+                "runeDepth": "50000000000",
+                "assetPrice": "50",
+                "assetPriceUSD": "30000",
+                "liquidityUnits": "1000000000",
+                "membersCount": "1000",
+                "synthUnits": "0",
+                "synthSupply": "0",
+                "units": "1000000000",
+                "luvi": "1",
+            }))
+            .unwrap()
+        })
+        .collect()
+}
+
+fn report(path: &str, rows: usize, started_at: Instant) {
+    let seconds = started_at.elapsed().as_secs_f64();
+
+    println!(
+        "{:<12} {:>6} rows in {:>8.3}s, {:>10.0} rows/s",
+        path,
+        rows,
+        seconds,
+        rows as f64 / seconds
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let uri = env::var("MONGOURI").unwrap_or_else(|_| {
+        String::from("mongodb://localhost:27017/?serverSelectionTimeoutMS=2000")
+    });
+    let rows = env::var("BENCH_ROWS")
+        .ok()
+        .and_then(|rows| rows.parse::<usize>().ok())
+        .unwrap_or(4000);
+
+    let client = Client::with_uri_str(&uri).await.unwrap();
+    let db = client.database("rustmidgardapi_bench");
+
+    if let Err(e) = db.run_command(doc! { "ping": 1 }, None).await {
+        eprintln!(
+            "Mongo isn't reachable at {}, skipping the benchmark: {}",
+            uri, e
+        );
+        return;
+    }
+
+    let depth_histories = depth_histories(rows);
+
+    let col = db.collection::<DepthHistory>("depth_history");
+    col.drop(None).await.unwrap();
+
+    let started_at = Instant::now();
+    let options = ReplaceOptions::builder().upsert(true).build();
+
+    for depth_history in &depth_histories {
+        col.replace_one(
+            doc! { "startTime": depth_history.start_time, "granularity": "hour" },
+            depth_history,
+            options.clone(),
+        )
+        .await
+        .unwrap();
+    }
+
+    report("replace_one", rows, started_at);
+
+    col.drop(None).await.unwrap();
+
+    let repo = DepthHistoryRepository::init(col.clone()).await.unwrap();
+    let started_at = Instant::now();
+
+    for page in depth_histories.chunks(PAGE_SIZE) {
+        let result = repo.upsert(page).await.unwrap();
+        assert!(result.failed.is_empty(), "{:?}", result.failed);
+    }
+
+    report("bulk upsert", rows, started_at);
+
+    db.drop(None).await.unwrap();
+}
@@ -0,0 +1,64 @@
+-- History rows are keyed like the Mongo upserts, by pool, granularity and start time. The pool is
+-- empty for the datasets that aren't per pool. The model is kept whole in `data`, the keys are
+-- copied out to columns to be filtered and bucketed on.
+
+CREATE TABLE depth_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time)
+);
+
+CREATE INDEX depth_history_end_time ON depth_history (granularity, end_time);
+
+CREATE TABLE swaps_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time)
+);
+
+CREATE INDEX swaps_history_end_time ON swaps_history (granularity, end_time);
+
+CREATE TABLE rune_pool_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time)
+);
+
+CREATE INDEX rune_pool_history_end_time ON rune_pool_history (granularity, end_time);
+
+CREATE TABLE earnings_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time),
+    UNIQUE (granularity, start_time)
+);
+
+CREATE INDEX earnings_history_end_time ON earnings_history (granularity, end_time);
+
+-- The `pools` of an earnings interval, in the order Midgard returned them.
+CREATE TABLE earnings_history_pool (
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    pool TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (granularity, start_time, pool),
+    FOREIGN KEY (granularity, start_time)
+        REFERENCES earnings_history (granularity, start_time) ON DELETE CASCADE
+);
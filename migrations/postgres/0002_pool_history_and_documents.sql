@@ -0,0 +1,34 @@
+-- The per pool history datasets, keyed like the others.
+
+CREATE TABLE savers_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time)
+);
+
+CREATE INDEX savers_history_end_time ON savers_history (granularity, end_time);
+
+CREATE TABLE liquidity_changes_history (
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    end_time DOUBLE PRECISION NOT NULL,
+    is_complete BOOLEAN NOT NULL DEFAULT TRUE,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, granularity, start_time)
+);
+
+CREATE INDEX liquidity_changes_history_end_time ON liquidity_changes_history (granularity, end_time);
+
+-- Documents of the other collections (actions, quarantine, coverage gaps, ...), read whole into
+-- memory on start and saved back by their position in the collection as they change.
+CREATE TABLE documents (
+    collection TEXT NOT NULL,
+    position BIGINT NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (collection, position)
+);
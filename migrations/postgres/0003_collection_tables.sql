@@ -0,0 +1,174 @@
+-- The collections kept whole in `documents` until now, each in a table of its own. Like the
+-- history rows, the model is kept whole in `data` and the fields it is looked up and sorted by are
+-- copied out to columns, indexed like `ensure_indexes` indexes the Mongo collections.
+
+-- The block time, type and inbound transactions identify an action.
+CREATE TABLE actions (
+    id BIGSERIAL PRIMARY KEY,
+    date BIGINT NOT NULL,
+    type TEXT NOT NULL,
+    in_txs TEXT NOT NULL,
+    data JSONB NOT NULL,
+    UNIQUE (date, type, in_txs)
+);
+
+CREATE INDEX actions_date ON actions (date DESC);
+CREATE INDEX actions_type_date ON actions (type, date DESC);
+
+-- Transaction ids and addresses of both sides of an action and its pools, one row each.
+CREATE TABLE action_keys (
+    action_id BIGINT NOT NULL REFERENCES actions (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    date BIGINT NOT NULL,
+    PRIMARY KEY (action_id, kind, value)
+);
+
+CREATE INDEX action_keys_value_date ON action_keys (kind, value, date DESC);
+
+CREATE TABLE actions_sync_state (
+    id TEXT PRIMARY KEY,
+    data JSONB NOT NULL
+);
+
+CREATE TABLE network_history (
+    start_time DOUBLE PRECISION PRIMARY KEY,
+    data JSONB NOT NULL
+);
+
+CREATE TABLE member_positions (
+    address TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (address, pool, start_time)
+);
+
+CREATE TABLE pool_stats (
+    pool TEXT NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (pool, start_time)
+);
+
+CREATE TABLE quarantine (
+    id TEXT PRIMARY KEY,
+    dataset TEXT NOT NULL,
+    replayed_at DOUBLE PRECISION,
+    quarantined_at DOUBLE PRECISION NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX quarantine_dataset ON quarantine (dataset, replayed_at, quarantined_at DESC);
+
+CREATE TABLE schema_drift (
+    dataset TEXT NOT NULL,
+    field TEXT NOT NULL,
+    first_seen_at DOUBLE PRECISION NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (dataset, field)
+);
+
+CREATE TABLE validation_metrics (
+    dataset TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (dataset, rule)
+);
+
+-- The pool is empty for the datasets that aren't per pool.
+CREATE TABLE coverage_gaps (
+    dataset TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    start_time DOUBLE PRECISION NOT NULL,
+    status TEXT NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (dataset, pool, start_time)
+);
+
+CREATE TABLE upstream_disagreements (
+    id TEXT PRIMARY KEY,
+    dataset TEXT NOT NULL,
+    detected_at DOUBLE PRECISION NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX upstream_disagreements_dataset
+    ON upstream_disagreements (dataset, detected_at DESC);
+
+CREATE TABLE _migrations (
+    id TEXT PRIMARY KEY,
+    data JSONB NOT NULL
+);
+
+-- The documents saved so far move to their tables.
+INSERT INTO actions (date, type, in_txs, data)
+SELECT (data->>'date')::BIGINT, data->>'type', (data->'in')::TEXT, data
+FROM documents WHERE collection = 'actions'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO action_keys (action_id, kind, value, date)
+SELECT DISTINCT a.id, k.kind, k.value, a.date
+FROM actions a,
+LATERAL (
+    SELECT 'txID' AS kind, tx->>'txID' AS value
+    FROM jsonb_array_elements((a.data->'in') || (a.data->'out')) tx
+    UNION
+    SELECT 'address', tx->>'address'
+    FROM jsonb_array_elements((a.data->'in') || (a.data->'out')) tx
+    UNION
+    SELECT 'pool', pool FROM jsonb_array_elements_text(a.data->'pools') pool
+) k
+WHERE k.value <> '';
+
+INSERT INTO actions_sync_state (id, data)
+SELECT data->>'_id', data FROM documents WHERE collection = 'actions_sync_state'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO network_history (start_time, data)
+SELECT (data->>'startTime')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'network_history'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO member_positions (address, pool, start_time, data)
+SELECT data->>'address', data->>'pool', (data->>'startTime')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'member_positions'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO pool_stats (pool, start_time, data)
+SELECT data->>'pool', (data->>'startTime')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'pool_stats'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO quarantine (id, dataset, replayed_at, quarantined_at, data)
+SELECT data->'_id'->>'$oid', data->>'dataset', (data->>'replayedAt')::DOUBLE PRECISION,
+    (data->>'quarantinedAt')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'quarantine'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO schema_drift (dataset, field, first_seen_at, data)
+SELECT data->>'dataset', data->>'field', (data->>'firstSeenAt')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'schema_drift'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO validation_metrics (dataset, rule, data)
+SELECT data->>'dataset', data->>'rule', data
+FROM documents WHERE collection = 'validation_metrics'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO coverage_gaps (dataset, pool, start_time, status, data)
+SELECT data->>'dataset', coalesce(data->>'pool', ''), (data->>'startTime')::DOUBLE PRECISION,
+    data->>'status', data
+FROM documents WHERE collection = 'coverage_gaps'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO upstream_disagreements (id, dataset, detected_at, data)
+SELECT data->'_id'->>'$oid', data->>'dataset', (data->>'detectedAt')::DOUBLE PRECISION, data
+FROM documents WHERE collection = 'upstream_disagreements'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO _migrations (id, data)
+SELECT data->>'id', data FROM documents WHERE collection = '_migrations'
+ON CONFLICT DO NOTHING;
+
+DROP TABLE documents;
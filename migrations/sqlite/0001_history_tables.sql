@@ -0,0 +1,55 @@
+-- Rows of every network in one file, keyed like the Mongo upserts by network, pool, granularity
+-- and start time. The pool is empty for the datasets that aren't per pool. The model is kept whole
+-- in `data`, earnings with its `pools`, the keys are copied out to columns to be filtered on.
+
+CREATE TABLE depth_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX depth_history_end_time ON depth_history (network, granularity, end_time);
+
+CREATE TABLE swaps_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX swaps_history_end_time ON swaps_history (network, granularity, end_time);
+
+CREATE TABLE rune_pool_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX rune_pool_history_end_time ON rune_pool_history (network, granularity, end_time);
+
+CREATE TABLE earnings_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX earnings_history_end_time ON earnings_history (network, granularity, end_time);
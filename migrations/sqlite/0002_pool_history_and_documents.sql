@@ -0,0 +1,38 @@
+-- The per pool history datasets, keyed like the others.
+
+CREATE TABLE savers_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX savers_history_end_time ON savers_history (network, granularity, end_time);
+
+CREATE TABLE liquidity_changes_history (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    granularity TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    end_time REAL NOT NULL,
+    is_complete INTEGER NOT NULL DEFAULT 1,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, granularity, start_time)
+);
+
+CREATE INDEX liquidity_changes_history_end_time
+    ON liquidity_changes_history (network, granularity, end_time);
+
+-- Documents of the other collections (actions, quarantine, coverage gaps, ...), read whole into
+-- memory on start and saved back by their position in the collection as they change.
+CREATE TABLE documents (
+    network TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, collection, position)
+);
@@ -0,0 +1,191 @@
+-- The collections kept whole in `documents` until now, each in a table of its own. Like the
+-- history rows, the model is kept whole in `data` and the fields it is looked up and sorted by are
+-- copied out to columns, indexed like `ensure_indexes` indexes the Mongo collections.
+
+-- The block time, type and inbound transactions identify an action.
+CREATE TABLE actions (
+    id INTEGER PRIMARY KEY,
+    network TEXT NOT NULL,
+    date INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    in_txs TEXT NOT NULL,
+    data TEXT NOT NULL,
+    UNIQUE (network, date, type, in_txs)
+);
+
+CREATE INDEX actions_date ON actions (network, date DESC);
+CREATE INDEX actions_type_date ON actions (network, type, date DESC);
+
+-- Transaction ids and addresses of both sides of an action and its pools, one row each.
+CREATE TABLE action_keys (
+    action_id INTEGER NOT NULL REFERENCES actions (id) ON DELETE CASCADE,
+    network TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    date INTEGER NOT NULL,
+    PRIMARY KEY (action_id, kind, value)
+);
+
+CREATE INDEX action_keys_value_date ON action_keys (network, kind, value, date DESC);
+
+CREATE TABLE actions_sync_state (
+    network TEXT NOT NULL,
+    id TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, id)
+);
+
+CREATE TABLE network_history (
+    network TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, start_time)
+);
+
+CREATE TABLE member_positions (
+    network TEXT NOT NULL,
+    address TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, address, pool, start_time)
+);
+
+CREATE TABLE pool_stats (
+    network TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    start_time REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, pool, start_time)
+);
+
+CREATE TABLE quarantine (
+    network TEXT NOT NULL,
+    id TEXT NOT NULL,
+    dataset TEXT NOT NULL,
+    replayed_at REAL,
+    quarantined_at REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, id)
+);
+
+CREATE INDEX quarantine_dataset
+    ON quarantine (network, dataset, replayed_at, quarantined_at DESC);
+
+CREATE TABLE schema_drift (
+    network TEXT NOT NULL,
+    dataset TEXT NOT NULL,
+    field TEXT NOT NULL,
+    first_seen_at REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, dataset, field)
+);
+
+CREATE TABLE validation_metrics (
+    network TEXT NOT NULL,
+    dataset TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, dataset, rule)
+);
+
+-- The pool is empty for the datasets that aren't per pool.
+CREATE TABLE coverage_gaps (
+    network TEXT NOT NULL,
+    dataset TEXT NOT NULL,
+    pool TEXT NOT NULL DEFAULT '',
+    start_time REAL NOT NULL,
+    status TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, dataset, pool, start_time)
+);
+
+CREATE TABLE upstream_disagreements (
+    network TEXT NOT NULL,
+    id TEXT NOT NULL,
+    dataset TEXT NOT NULL,
+    detected_at REAL NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, id)
+);
+
+CREATE INDEX upstream_disagreements_dataset
+    ON upstream_disagreements (network, dataset, detected_at DESC);
+
+CREATE TABLE _migrations (
+    network TEXT NOT NULL,
+    id TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (network, id)
+);
+
+-- The documents saved so far move to their tables.
+INSERT OR IGNORE INTO actions (network, date, type, in_txs, data)
+SELECT network, json_extract(data, '$.date'), json_extract(data, '$.type'),
+    json_extract(data, '$.in'), data
+FROM documents WHERE collection = 'actions';
+
+INSERT OR IGNORE INTO action_keys (action_id, network, kind, value, date)
+SELECT a.id, a.network, 'txID', json_extract(tx.value, '$.txID'), a.date
+FROM actions a, json_each(a.data, '$.in') tx
+UNION
+SELECT a.id, a.network, 'txID', json_extract(tx.value, '$.txID'), a.date
+FROM actions a, json_each(a.data, '$.out') tx
+UNION
+SELECT a.id, a.network, 'address', json_extract(tx.value, '$.address'), a.date
+FROM actions a, json_each(a.data, '$.in') tx
+UNION
+SELECT a.id, a.network, 'address', json_extract(tx.value, '$.address'), a.date
+FROM actions a, json_each(a.data, '$.out') tx
+UNION
+SELECT a.id, a.network, 'pool', pool.value, a.date
+FROM actions a, json_each(a.data, '$.pools') pool;
+
+DELETE FROM action_keys WHERE value = '' OR value IS NULL;
+
+INSERT OR IGNORE INTO actions_sync_state (network, id, data)
+SELECT network, json_extract(data, '$._id'), data
+FROM documents WHERE collection = 'actions_sync_state';
+
+INSERT OR IGNORE INTO network_history (network, start_time, data)
+SELECT network, json_extract(data, '$.startTime'), data
+FROM documents WHERE collection = 'network_history';
+
+INSERT OR IGNORE INTO member_positions (network, address, pool, start_time, data)
+SELECT network, json_extract(data, '$.address'), json_extract(data, '$.pool'),
+    json_extract(data, '$.startTime'), data
+FROM documents WHERE collection = 'member_positions';
+
+INSERT OR IGNORE INTO pool_stats (network, pool, start_time, data)
+SELECT network, json_extract(data, '$.pool'), json_extract(data, '$.startTime'), data
+FROM documents WHERE collection = 'pool_stats';
+
+INSERT OR IGNORE INTO quarantine (network, id, dataset, replayed_at, quarantined_at, data)
+SELECT network, json_extract(data, '$._id."$oid"'), json_extract(data, '$.dataset'),
+    json_extract(data, '$.replayedAt'), json_extract(data, '$.quarantinedAt'), data
+FROM documents WHERE collection = 'quarantine';
+
+INSERT OR IGNORE INTO schema_drift (network, dataset, field, first_seen_at, data)
+SELECT network, json_extract(data, '$.dataset'), json_extract(data, '$.field'),
+    json_extract(data, '$.firstSeenAt'), data
+FROM documents WHERE collection = 'schema_drift';
+
+INSERT OR IGNORE INTO validation_metrics (network, dataset, rule, data)
+SELECT network, json_extract(data, '$.dataset'), json_extract(data, '$.rule'), data
+FROM documents WHERE collection = 'validation_metrics';
+
+INSERT OR IGNORE INTO coverage_gaps (network, dataset, pool, start_time, status, data)
+SELECT network, json_extract(data, '$.dataset'), coalesce(json_extract(data, '$.pool'), ''),
+    json_extract(data, '$.startTime'), json_extract(data, '$.status'), data
+FROM documents WHERE collection = 'coverage_gaps';
+
+INSERT OR IGNORE INTO upstream_disagreements (network, id, dataset, detected_at, data)
+SELECT network, json_extract(data, '$._id."$oid"'), json_extract(data, '$.dataset'),
+    json_extract(data, '$.detectedAt'), data
+FROM documents WHERE collection = 'upstream_disagreements';
+
+INSERT OR IGNORE INTO _migrations (network, id, data)
+SELECT network, json_extract(data, '$.id'), data
+FROM documents WHERE collection = '_migrations';
+
+DROP TABLE documents;
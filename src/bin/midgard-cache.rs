@@ -0,0 +1,305 @@
+use std::{io, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
+use rust_api::{
+    migrate, network_db,
+    repository::stores::Stores,
+    restore, run,
+    services::{coverage_service, retention_service, rollup_service},
+    utils::time_interval::TimeInterval,
+};
+
+// Maintenance of the cached data without the HTTP server, through the same services and stores.
+#[derive(Parser)]
+#[command(
+    name = "midgard-cache",
+    about = "Serves and maintains the cached Midgard data"
+)]
+struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Network to work on (e.g., mainnet, stagenet, mayachain), the first of NETWORKS if not given"
+    )]
+    network: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+// Hourly datasets and range a command works on.
+#[derive(Args)]
+struct Selection {
+    #[arg(
+        long,
+        help = "Dataset (e.g., depth_history, savers_history), every hourly dataset if not given"
+    )]
+    dataset: Option<String>,
+
+    #[arg(
+        long,
+        help = "Pool of the per pool datasets, every tracked pool if not given"
+    )]
+    pool: Option<String>,
+
+    #[arg(long, help = "Start of the range in seconds")]
+    from: Option<i64>,
+
+    #[arg(long, help = "End of the range in seconds, defaults to now")]
+    to: Option<i64>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Serves the API and runs the sync jobs of every network")]
+    Serve,
+
+    #[command(about = "Refetches the hours of a range from Midgard and rebuilds their rollups")]
+    Backfill(Selection),
+
+    #[command(about = "Reports the missing and duplicated hours, fails if there are any")]
+    Verify(Selection),
+
+    #[command(about = "Writes the rows of a dataset to an NDJSON file, gzipped if it ends in .gz")]
+    Export {
+        #[arg(long, help = "Dataset (e.g., depth_history, savers_history)")]
+        dataset: String,
+
+        #[arg(
+            long,
+            help = "Pool, required by the per pool datasets (e.g., savers_history)"
+        )]
+        pool: Option<String>,
+
+        #[arg(long, default_value = "hour", value_parser = parse_granularity, help = "Granularity of the rows")]
+        granularity: TimeInterval,
+
+        #[arg(long, help = "Start of the range in seconds")]
+        from: Option<i64>,
+
+        #[arg(long, help = "End of the range in seconds, defaults to now")]
+        to: Option<i64>,
+
+        #[arg(long, short, help = "File to write")]
+        output: PathBuf,
+    },
+
+    #[command(about = "Upserts the rows of exported or archived NDJSON files into a dataset")]
+    Import {
+        #[arg(long, help = "Dataset (e.g., depth_history, savers_history)")]
+        dataset: String,
+
+        #[arg(required = true, help = "Files to import, gzipped if they end in .gz")]
+        files: Vec<String>,
+    },
+
+    #[command(subcommand, about = "Manages the day to year rollups")]
+    Rollup(RollupCommand),
+
+    #[command(about = "Applies the pending migrations of every network")]
+    Migrate {
+        #[arg(long, help = "Only reports the rows the migrations would change")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RollupCommand {
+    #[command(about = "Rebuilds the rollups of a range from the stored hours")]
+    Rebuild(Selection),
+}
+
+fn parse_granularity(granularity: &str) -> Result<TimeInterval, String> {
+    TimeInterval::from_str(granularity)
+        .ok_or_else(|| format!("Unknown granularity {}", granularity))
+}
+
+fn range(from: Option<i64>, to: Option<i64>) -> (f64, f64) {
+    (
+        from.unwrap_or_default() as f64,
+        to.map_or(Utc::now().timestamp() as f64, |to| to as f64),
+    )
+}
+
+fn date(time: f64) -> String {
+    DateTime::from_timestamp(time as i64, 0).map_or(time.to_string(), |date| {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    })
+}
+
+fn label(dataset: &str, pool: &Option<String>) -> String {
+    match pool {
+        Some(pool) => format!("{} {}", dataset, pool),
+        None => dataset.to_string(),
+    }
+}
+
+fn datasets(db: &Stores, selection: &Selection) -> io::Result<Vec<(&'static str, Option<String>)>> {
+    let datasets = coverage_service::selected_datasets(
+        &db.network,
+        selection.dataset.as_deref(),
+        selection.pool.as_deref(),
+    );
+
+    if datasets.is_empty() {
+        return Err(io::Error::other("No hourly dataset matches"));
+    }
+
+    Ok(datasets)
+}
+
+async fn backfill(network: Option<&str>, selection: Selection) -> io::Result<()> {
+    if selection.from.is_none() {
+        return Err(io::Error::other("--from is required"));
+    }
+
+    let (from, to) = range(selection.from, selection.to);
+    let db = network_db(network).await?;
+    let mut failed = 0;
+
+    for (dataset, pool) in datasets(&db, &selection)? {
+        let fetched = coverage_service::backfill(&db, dataset, pool.as_deref(), from, to)
+            .await
+            .map_err(io::Error::other)?;
+
+        if fetched {
+            println!("{}: backfilled", label(dataset, &pool));
+        } else {
+            println!("{}: some fetches failed", label(dataset, &pool));
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "{} datasets weren't fully backfilled",
+            failed
+        )));
+    }
+
+    Ok(())
+}
+
+async fn verify(network: Option<&str>, selection: Selection) -> io::Result<()> {
+    let (from, to) = range(selection.from, selection.to);
+    let db = network_db(network).await?;
+    let mut problems = 0;
+
+    for (dataset, pool) in datasets(&db, &selection)? {
+        let (gaps, duplicates) = coverage_service::verify(&db, dataset, pool.as_deref(), from, to)
+            .await
+            .map_err(io::Error::other)?;
+
+        for (start_time, end_time) in &gaps {
+            println!(
+                "{}: missing {} to {}",
+                label(dataset, &pool),
+                date(*start_time),
+                date(*end_time)
+            );
+        }
+
+        for start_time in &duplicates {
+            println!(
+                "{}: duplicated {}",
+                label(dataset, &pool),
+                date(*start_time)
+            );
+        }
+
+        problems += gaps.len() + duplicates.len();
+    }
+
+    if problems > 0 {
+        return Err(io::Error::other(format!(
+            "Found {} gaps and duplicated hours",
+            problems
+        )));
+    }
+
+    println!("No gaps or duplicated hours");
+
+    Ok(())
+}
+
+async fn rebuild_rollups(network: Option<&str>, selection: Selection) -> io::Result<()> {
+    if selection.from.is_none() {
+        return Err(io::Error::other("--from is required"));
+    }
+
+    let (from, to) = range(selection.from, selection.to);
+    let db = network_db(network).await?;
+
+    datasets(&db, &selection)?;
+
+    let rebuilt = rollup_service::rebuild(
+        &db,
+        selection.dataset.as_deref(),
+        selection.pool.as_deref(),
+        from,
+        to,
+    )
+    .await
+    .map_err(io::Error::other)?;
+
+    for rebuild in rebuilt {
+        println!(
+            "{}: {} buckets",
+            label(&rebuild.dataset, &rebuild.pool),
+            rebuild.buckets
+        );
+    }
+
+    Ok(())
+}
+
+#[actix_web::main]
+async fn main() -> io::Result<()> {
+    let cli = Cli::parse();
+    let network = cli.network.as_deref();
+
+    match cli.command {
+        Command::Serve => run().await,
+        Command::Backfill(selection) => backfill(network, selection).await,
+        Command::Verify(selection) => verify(network, selection).await,
+        Command::Export {
+            dataset,
+            pool,
+            granularity,
+            from,
+            to,
+            output,
+        } => {
+            let (from, to) = range(from, to);
+            let db = network_db(network).await?;
+
+            let exported = retention_service::export_dataset(
+                &db,
+                &dataset,
+                pool.as_deref(),
+                granularity,
+                from,
+                to,
+                &output,
+            )
+            .await
+            .map_err(io::Error::other)?;
+
+            println!(
+                "Exported {} {} rows of {} to {}",
+                exported,
+                granularity.to_str(),
+                dataset,
+                output.display()
+            );
+
+            Ok(())
+        }
+        Command::Import { dataset, files } => restore(network, &dataset, &files).await,
+        Command::Rollup(RollupCommand::Rebuild(selection)) => {
+            rebuild_rollups(network, selection).await
+        }
+        Command::Migrate { dry_run } => migrate(dry_run).await,
+    }
+}
@@ -10,41 +10,168 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use actix_web::{
+    error::ErrorInternalServerError,
     web::{self, Data},
     App, Error, HttpResponse, HttpServer,
 };
-use repository::mongodb_repository::MongoDB;
+use repository::stores::Stores;
 use services::{
-    depth_history_service, earnings_history_service, rune_pool_history_service,
-    swaps_history_service,
+    actions_service, coverage_service, depth_history_service, earnings_history_service,
+    health_service, index_service, liquidity_changes_history_service, member_positions_service,
+    network_history_service, pool_stats_service, quarantine_service, retention_service,
+    rollup_service, rune_pool_history_service, savers_history_service, schema_drift_service,
+    swaps_history_service, upstream_service, validation_service,
+};
+use utils::{
+    api_doc::ApiDoc, migrations::run_migrations, network::networks_from_env,
+    scheduler::run_cron_job,
 };
-use utils::{api_doc::ApiDoc, scheduler::run_cron_job};
 
 pub async fn home_route() -> HttpResponse {
     HttpResponse::Ok().body("Hello! Welcome to our API")
 }
 
-pub async fn init_db() -> Result<Data<MongoDB>, Error> {
-    let db = MongoDB::init().await.unwrap();
-    Ok(Data::new(db))
+// One database per network, sharing the backend.
+async fn init_networks() -> Result<Vec<Data<Stores>>, Error> {
+    let backend = Stores::connect().await.map_err(ErrorInternalServerError)?;
+    let networks = networks_from_env().map_err(ErrorInternalServerError)?;
+
+    let mut dbs = vec![];
+
+    for network in networks {
+        let db = Stores::init(&backend, network)
+            .await
+            .map_err(ErrorInternalServerError)?;
+        dbs.push(Data::new(db));
+    }
+
+    Ok(dbs)
+}
+
+// The databases of every network with their pending migrations applied.
+pub async fn init_db() -> Result<Vec<Data<Stores>>, Error> {
+    let dbs = init_networks().await?;
+
+    for db in &dbs {
+        run_migrations(db, false)
+            .await
+            .map_err(|e| ErrorInternalServerError(e.to_string()))?;
+    }
+
+    Ok(dbs)
+}
+
+// Applies the pending migrations of every network without serving, with `dry_run` only reports
+// them.
+pub async fn migrate(dry_run: bool) -> std::io::Result<()> {
+    let dbs = init_networks()
+        .await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    for db in &dbs {
+        let pending = run_migrations(db, dry_run)
+            .await
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+        if pending.is_empty() {
+            println!("[{}] No pending migrations", db.network.name);
+        }
+    }
+
+    Ok(())
 }
 
-pub async fn init_server(db_data: Data<MongoDB>) -> std::io::Result<()> {
-    actix_web::rt::spawn(run_cron_job(db_data.clone()));
+// The database of a network, the first one unless `network` is given, for the maintenance commands.
+pub async fn network_db(network: Option<&str>) -> std::io::Result<Data<Stores>> {
+    let dbs = init_networks()
+        .await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    match network {
+        Some(name) => dbs.into_iter().find(|db| db.network.name == name),
+        None => dbs.into_iter().next(),
+    }
+    .ok_or_else(|| std::io::Error::other(format!("Unknown network {}", network.unwrap_or(""))))
+}
+
+// Reimports archived rows of a dataset into the store of a network, the first one unless `network`
+// is given.
+pub async fn restore(
+    network: Option<&str>,
+    dataset: &str,
+    archives: &[String],
+) -> std::io::Result<()> {
+    let db = network_db(network).await?;
+
+    for archive in archives {
+        let restored = retention_service::restore_archive(&db, dataset, archive.as_ref())
+            .await
+            .map_err(|e| std::io::Error::other(format!("{}: {}", archive, e)))?;
+
+        println!(
+            "[{}] Restored {} {} rows from {}",
+            db.network.name, restored, dataset, archive
+        );
+    }
+
+    Ok(())
+}
+
+// Routes of a network, resolved against the `Stores` of the enclosing scope.
+pub fn network_routes(config: &mut web::ServiceConfig) {
+    config
+        .service(web::scope("/depth-history").configure(depth_history_service::init))
+        .service(
+            web::scope("/liquidity-changes-history")
+                .configure(liquidity_changes_history_service::init),
+        )
+        .service(web::scope("/earnings-history").configure(earnings_history_service::init))
+        .service(web::scope("/swaps-history").configure(swaps_history_service::init))
+        .service(web::scope("/rune-pool-history").configure(rune_pool_history_service::init))
+        .service(web::scope("/actions").configure(actions_service::init))
+        .service(web::scope("/network-history").configure(network_history_service::init))
+        .service(web::scope("/members").configure(member_positions_service::init))
+        .service(web::scope("/pool-stats").configure(pool_stats_service::init))
+        .service(web::scope("/savers-history").configure(savers_history_service::init))
+        .service(web::scope("/health").configure(health_service::init))
+        .service(
+            web::scope("/admin")
+                .configure(quarantine_service::init)
+                .configure(schema_drift_service::init)
+                .configure(validation_service::init)
+                .configure(coverage_service::init)
+                .configure(upstream_service::init)
+                .configure(index_service::init)
+                .configure(rollup_service::init),
+        );
+}
+
+pub async fn init_server(db_data: Vec<Data<Stores>>) -> std::io::Result<()> {
+    for db in &db_data {
+        actix_web::rt::spawn(run_cron_job(db.clone()));
+    }
 
     let openapi = ApiDoc::openapi();
 
     HttpServer::new(move || {
-        App::new()
-            .app_data(db_data.clone())
+        // The first network is also served without a prefix, for the clients of the single network
+        // routes.
+        let mut app = App::new()
+            .app_data(db_data[0].clone())
             .route("/", web::get().to(home_route))
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
-            )
-            .service(web::scope("/depth-history").configure(depth_history_service::init))
-            .service(web::scope("/earnings-history").configure(earnings_history_service::init))
-            .service(web::scope("/swaps-history").configure(swaps_history_service::init))
-            .service(web::scope("/rune-pool-history").configure(rune_pool_history_service::init))
+            );
+
+        for db in &db_data {
+            app = app.service(
+                web::scope(&format!("/{}", db.network.name))
+                    .app_data(db.clone())
+                    .configure(network_routes),
+            );
+        }
+
+        app.configure(network_routes)
     })
     .bind(("0.0.0.0", 3000))?
     .run()
@@ -52,15 +179,15 @@ pub async fn init_server(db_data: Data<MongoDB>) -> std::io::Result<()> {
 }
 
 pub async fn run() -> std::io::Result<()> {
-    let db_data: Result<Data<MongoDB>, Error> = init_db().await;
+    let db_data: Result<Vec<Data<Stores>>, Error> = init_db().await;
 
     let db_data = match db_data {
         Ok(data) => {
             println!("Successfully connected to database.");
             data
         }
-        Err(_) => {
-            println!("Failed to connect to the database.");
+        Err(e) => {
+            println!("Failed to connect to the database: {}", e);
             return Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "Database connection failed",
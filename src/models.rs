@@ -1,4 +1,19 @@
+pub mod actions_model;
+pub mod coverage_model;
 pub mod depth_history_model;
 pub mod earnings_history_model;
+pub mod health_model;
+pub mod index_model;
+pub mod liquidity_changes_history_model;
+pub mod member_positions_model;
+pub mod migration_model;
+pub mod network_history_model;
+pub mod pool_stats_model;
+pub mod quarantine_model;
+pub mod rollup_model;
 pub mod rune_pool_history_model;
+pub mod savers_history_model;
+pub mod schema_drift_model;
 pub mod swaps_history_model;
+pub mod upstream_disagreement_model;
+pub mod validation_model;
@@ -0,0 +1,91 @@
+use crate::utils::deserialize_util::{deserialize_string_to_i64, deserialize_string_to_number};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCoin {
+    pub asset: String,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionTransaction {
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+    #[serde(default)]
+    pub coins: Vec<ActionCoin>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    pub pools: Vec<String>,
+    #[serde(rename = "type")]
+    pub action_type: String,
+    pub status: String,
+    #[serde(rename = "in")]
+    pub in_txs: Vec<ActionTransaction>,
+    #[serde(rename = "out")]
+    pub out_txs: Vec<ActionTransaction>,
+    // Nanoseconds since epoch, as returned by Midgard.
+    #[serde(deserialize_with = "deserialize_string_to_i64")]
+    pub date: i64,
+    #[serde(deserialize_with = "deserialize_string_to_i64")]
+    pub height: i64,
+    // Type specific details (swap, addLiquidity, withdraw...) are kept as returned by Midgard.
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+}
+
+impl Action {
+    pub fn is_action_type(action_type: &str) -> bool {
+        [
+            "swap",
+            "addLiquidity",
+            "withdraw",
+            "donate",
+            "refund",
+            "switch",
+        ]
+        .contains(&action_type)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionsMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionsResponse {
+    #[serde(default)]
+    #[schema(inline)]
+    pub meta: ActionsMeta,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub count: f64,
+    pub actions: Vec<Action>,
+}
+
+// Resumable position of an actions ingestion run. `nextPageToken` is only set while a run
+// is in progress, so an interrupted run continues from the last stored page.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionsSyncState {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub from_timestamp: i64,
+    pub next_page_token: Option<String>,
+    pub newest_date: i64,
+}
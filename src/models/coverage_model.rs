@@ -0,0 +1,51 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum GapStatus {
+    // Waiting for a refetch.
+    Found,
+    // Every missing interval is stored now.
+    Repaired,
+    // Still missing after the last refetch attempt, Midgard likely has nothing for it.
+    Unrepairable,
+}
+
+impl GapStatus {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            GapStatus::Found => "found",
+            GapStatus::Repaired => "repaired",
+            GapStatus::Unrepairable => "unrepairable",
+        }
+    }
+}
+
+// A run of missing hourly intervals between the first and last stored record of a dataset.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageGap {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<ObjectId>,
+    pub dataset: String,
+    pub pool: Option<String>,
+    // Start of the first missing interval and end of the last one.
+    pub start_time: f64,
+    pub end_time: f64,
+    pub missing_intervals: i64,
+    pub status: GapStatus,
+    pub attempts: i64,
+    pub detected_at: f64,
+    pub updated_at: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageResponse {
+    pub found: Vec<CoverageGap>,
+    pub repaired: Vec<CoverageGap>,
+    pub unrepairable: Vec<CoverageGap>,
+}
@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use crate::utils::deserialize_util::deserialize_string_to_number;
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::{
+    default_granularity, default_true, deserialize_string_to_number,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -20,6 +23,10 @@ pub struct EarningsHistoryPool {
     pub rewards: f64,
     #[serde(deserialize_with = "deserialize_string_to_number")]
     pub earnings: f64,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -45,6 +52,20 @@ pub struct EarningsHistory {
     #[serde(rename = "runePriceUSD")]
     pub rune_price_usd: f64,
     pub pools: Vec<EarningsHistoryPool>,
+    // False while Midgard is still accumulating the interval, it is overwritten on a later sync.
+    // Records stored before the flag existed are treated as complete.
+    #[serde(default = "default_true")]
+    pub is_complete: bool,
+    // Midgard interval the row was fetched at (e.g. hour, day, month).
+    #[serde(default = "default_granularity")]
+    pub granularity: String,
+    // Set on the buckets rolled up from finer rows, unset on the intervals fetched from Midgard.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_up: bool,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
 }
 
 impl EarningsHistory {
@@ -67,6 +88,30 @@ impl EarningsHistory {
     }
 }
 
+impl HasExtraFields for EarningsHistory {
+    fn extra_field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.extra.keys().cloned().collect();
+
+        for pool in &self.pools {
+            for name in pool.extra.keys() {
+                let name = format!("pools.{}", name);
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+        for pool in self.pools.iter_mut() {
+            pool.extra.clear();
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct EarningsHistoryMeta {
@@ -89,6 +134,12 @@ pub struct EarningsHistoryMeta {
     #[serde(deserialize_with = "deserialize_string_to_number")]
     #[serde(rename = "runePriceUSD")]
     pub rune_price_usd: f64,
+    // Share of the hourly intervals of the period that are stored, below 1 when it has gaps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    // Symbol the `rune*` amounts are in (e.g. RUNE, CACAO), the native asset of the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_asset: Option<String>,
 }
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// State of the circuit breaker in front of Midgard.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitBreakerStatus {
+    // `closed`, `open` while ingestion is paused, or `half_open` while a trial request is let through.
+    pub state: String,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<f64>,
+    // When the next trial request is let through, while open.
+    pub retry_at: Option<f64>,
+}
+
+// A configured Midgard endpoint, as seen by the last probe.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamStatus {
+    pub url: String,
+    // Lower is preferred.
+    pub priority: u32,
+    // Behind the most advanced endpoint, it is only used once the others fail.
+    pub lagging: bool,
+    pub last_aggregated_height: Option<i64>,
+    pub last_end_time: Option<f64>,
+    pub checked_at: Option<f64>,
+    pub breaker: CircuitBreakerStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthResponse {
+    pub network: String,
+    pub native_asset: String,
+    // `ok`, or `degraded` while a Midgard endpoint is failing or lagging.
+    pub status: String,
+    // Endpoints in the order they are tried.
+    pub midgard: Vec<UpstreamStatus>,
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+// An index the repositories query with that the collection doesn't have as expected.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexProblem {
+    pub collection: String,
+    // Fields and directions, e.g. `{"granularity": 1, "startTime": 1}`.
+    #[schema(value_type = Object)]
+    pub keys: Value,
+    pub unique: bool,
+    // `missing`, or `mismatched` when an index on the same keys has other options.
+    pub problem: String,
+}
+
+// Usage of an index as counted by the server, since it started or the index was built.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexUsage {
+    pub collection: String,
+    pub name: String,
+    #[schema(value_type = Object)]
+    pub keys: Value,
+    pub accesses: i64,
+    pub since: Option<f64>,
+    // Whether the repositories query with it. The others only cost writes.
+    pub expected: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexReportResponse {
+    pub problems: Vec<IndexProblem>,
+    pub usage: Vec<IndexUsage>,
+}
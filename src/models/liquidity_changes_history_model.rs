@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::{
+    default_granularity, default_true, deserialize_string_to_number,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityChangesHistory {
+    // Midgard doesn't return the pool inside the intervals, it is set before inserting.
+    #[serde(default)]
+    pub pool: String,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_asset_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_rune_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_liquidity_count: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addAssetLiquidityVolumeUSD")]
+    pub add_asset_liquidity_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addRuneLiquidityVolumeUSD")]
+    pub add_rune_liquidity_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addLiquidityVolumeUSD")]
+    pub add_liquidity_volume_usd: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_asset_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_rune_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_count: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawAssetVolumeUSD")]
+    pub withdraw_asset_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawRuneVolumeUSD")]
+    pub withdraw_rune_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawVolumeUSD")]
+    pub withdraw_volume_usd: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub impermanent_loss_protection_paid: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub net: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "runePriceUSD")]
+    pub rune_price_usd: f64,
+    // False while Midgard is still accumulating the interval, it is overwritten on a later sync.
+    // Records stored before the flag existed are treated as complete.
+    #[serde(default = "default_true")]
+    pub is_complete: bool,
+    // Midgard interval the row was fetched at (e.g. hour, day, month).
+    #[serde(default = "default_granularity")]
+    pub granularity: String,
+    // Set on the buckets rolled up from finer rows, unset on the intervals fetched from Midgard.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_up: bool,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl LiquidityChangesHistory {
+    pub fn has_field(field: &str) -> bool {
+        let camel_to_snake_fields: HashSet<&str> = vec![
+            "startTime",
+            "endTime",
+            "addAssetLiquidityVolume",
+            "addRuneLiquidityVolume",
+            "addLiquidityVolume",
+            "addLiquidityCount",
+            "addAssetLiquidityVolumeUSD",
+            "addRuneLiquidityVolumeUSD",
+            "addLiquidityVolumeUSD",
+            "withdrawAssetVolume",
+            "withdrawRuneVolume",
+            "withdrawVolume",
+            "withdrawCount",
+            "withdrawAssetVolumeUSD",
+            "withdrawRuneVolumeUSD",
+            "withdrawVolumeUSD",
+            "impermanentLossProtectionPaid",
+            "net",
+            "runePriceUSD",
+        ]
+        .into_iter()
+        .collect();
+
+        camel_to_snake_fields.contains(field)
+    }
+}
+
+impl HasExtraFields for LiquidityChangesHistory {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityChangesHistoryMeta {
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_asset_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_rune_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_liquidity_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_liquidity_count: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addAssetLiquidityVolumeUSD")]
+    pub add_asset_liquidity_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addRuneLiquidityVolumeUSD")]
+    pub add_rune_liquidity_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "addLiquidityVolumeUSD")]
+    pub add_liquidity_volume_usd: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_asset_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_rune_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_count: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawAssetVolumeUSD")]
+    pub withdraw_asset_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawRuneVolumeUSD")]
+    pub withdraw_rune_volume_usd: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "withdrawVolumeUSD")]
+    pub withdraw_volume_usd: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub impermanent_loss_protection_paid: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub net: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "runePriceUSD")]
+    pub rune_price_usd: f64,
+    // Share of the hourly intervals of the period that are stored, below 1 when it has gaps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    // Symbol the `rune*` amounts are in (e.g. RUNE, CACAO), the native asset of the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_asset: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityChangesHistoryResponse {
+    #[schema(inline)]
+    pub meta: LiquidityChangesHistoryMeta,
+    pub intervals: Vec<LiquidityChangesHistory>,
+}
@@ -0,0 +1,136 @@
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::deserialize_string_to_number;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Position of a member in one pool as returned by Midgard `/v2/member/{address}`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberPool {
+    pub pool: String,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub liquidity_units: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_added: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_added: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_withdrawn: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_withdrawn: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_pending: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_pending: f64,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberResponse {
+    pub pools: Vec<MemberPool>,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl HasExtraFields for MemberResponse {
+    fn extra_field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.extra.keys().cloned().collect();
+
+        for pool in &self.pools {
+            for name in pool.extra.keys() {
+                let name = format!("pools.{}", name);
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+        for pool in self.pools.iter_mut() {
+            pool.extra.clear();
+        }
+    }
+}
+
+// Snapshot of a member position, stored in the hourly interval it was taken in. The value of the
+// position is only filled in when it is returned, from the depth of the pool at the same time.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberPosition {
+    pub address: String,
+    pub pool: String,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub liquidity_units: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_added: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_added: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_withdrawn: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_withdrawn: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_pending: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_pending: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rune_value: Option<f64>,
+    // Fields of the member pool Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl MemberPosition {
+    pub fn from_member_pool(address: &str, member_pool: MemberPool, timestamp: f64) -> Self {
+        let start_time = timestamp - timestamp % 3600.0;
+
+        MemberPosition {
+            address: address.to_string(),
+            pool: member_pool.pool,
+            start_time,
+            end_time: start_time + 3600.0,
+            liquidity_units: member_pool.liquidity_units,
+            rune_added: member_pool.rune_added,
+            asset_added: member_pool.asset_added,
+            rune_withdrawn: member_pool.rune_withdrawn,
+            asset_withdrawn: member_pool.asset_withdrawn,
+            rune_pending: member_pool.rune_pending,
+            asset_pending: member_pool.asset_pending,
+            asset_value: None,
+            rune_value: None,
+            extra: member_pool.extra,
+        }
+    }
+}
+
+impl HasExtraFields for MemberPosition {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberPositionsResponse {
+    pub address: String,
+    pub intervals: Vec<MemberPosition>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// A migration applied to a network's database.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub id: String,
+    pub description: String,
+    pub applied_at: f64,
+    // Rows the migration changed.
+    pub rows: i64,
+}
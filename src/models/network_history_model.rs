@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::deserialize_string_to_number;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkBondMetrics {
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_active_bond: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_standby_bond: f64,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+// Response of Midgard `/v2/network`. The fields snapshotted into `NetworkHistory` are its own, the
+// others are kept with it as they are.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkResponse {
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "bondingAPY")]
+    pub bonding_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "liquidityAPY")]
+    pub liquidity_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub active_node_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub standby_node_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub pool_share_factor: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_pooled_rune: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_reserve: f64,
+    pub bond_metrics: NetworkBondMetrics,
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl HasExtraFields for NetworkResponse {
+    fn extra_field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.extra.keys().cloned().collect();
+
+        for name in self.bond_metrics.extra.keys() {
+            names.push(format!("bondMetrics.{}", name));
+        }
+
+        names
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+        self.bond_metrics.extra.clear();
+    }
+}
+
+// Snapshot of `/v2/network`, stored in the hourly interval it was taken in.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHistory {
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "bondingAPY")]
+    pub bonding_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "liquidityAPY")]
+    pub liquidity_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_active_bond: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_standby_bond: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub active_node_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub standby_node_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub pool_share_factor: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_pooled_rune: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_reserve: f64,
+    // Fields of the response that aren't snapshotted, the unknown bond metrics under
+    // `bondMetrics`.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl NetworkHistory {
+    pub fn from_network_response(network: NetworkResponse, timestamp: f64) -> Self {
+        let start_time = timestamp - timestamp % 3600.0;
+        let mut extra = network.extra;
+
+        if !network.bond_metrics.extra.is_empty() {
+            extra.insert(
+                String::from("bondMetrics"),
+                Value::Object(network.bond_metrics.extra.into_iter().collect()),
+            );
+        }
+
+        NetworkHistory {
+            start_time,
+            end_time: start_time + 3600.0,
+            bonding_apy: network.bonding_apy,
+            liquidity_apy: network.liquidity_apy,
+            total_active_bond: network.bond_metrics.total_active_bond,
+            total_standby_bond: network.bond_metrics.total_standby_bond,
+            active_node_count: network.active_node_count,
+            standby_node_count: network.standby_node_count,
+            pool_share_factor: network.pool_share_factor,
+            total_pooled_rune: network.total_pooled_rune,
+            total_reserve: network.total_reserve,
+            extra,
+        }
+    }
+
+    pub fn has_field(field: &str) -> bool {
+        let camel_to_snake_fields: HashSet<&str> = vec![
+            "startTime",
+            "endTime",
+            "bondingAPY",
+            "liquidityAPY",
+            "totalActiveBond",
+            "totalStandbyBond",
+            "activeNodeCount",
+            "standbyNodeCount",
+            "poolShareFactor",
+            "totalPooledRune",
+            "totalReserve",
+        ]
+        .into_iter()
+        .collect();
+
+        camel_to_snake_fields.contains(field)
+    }
+}
+
+impl HasExtraFields for NetworkHistory {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHistoryMeta {
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "startBondingAPY")]
+    pub start_bonding_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "endBondingAPY")]
+    pub end_bonding_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_total_active_bond: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_total_active_bond: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_active_node_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_active_node_count: f64,
+    // Symbol the bonds are in (e.g. RUNE, CACAO), the native asset of the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_asset: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHistoryResponse {
+    #[schema(inline)]
+    pub meta: NetworkHistoryMeta,
+    pub intervals: Vec<NetworkHistory>,
+}
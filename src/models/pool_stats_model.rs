@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::deserialize_string_to_number;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Snapshot of Midgard `/v2/pool/{pool}/stats`. Midgard only returns the stats of the period, the
+// pool, period and interval times are set before inserting.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+    #[serde(default)]
+    #[serde(alias = "asset")]
+    pub pool: String,
+    #[serde(default)]
+    pub period: String,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_depth: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub rune_depth: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub asset_price: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "assetPriceUSD")]
+    pub asset_price_usd: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub units: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub swap_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub to_asset_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub to_rune_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub swap_volume: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub total_fees: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub average_slip: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub unique_swapper_count: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub unique_member_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub add_liquidity_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub withdraw_count: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub annual_percentage_rate: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "poolAPY")]
+    pub pool_apy: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub earnings: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    pub earnings_annual_as_percent_of_depth: f64,
+    #[serde(default, deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "saversAPR")]
+    pub savers_apr: f64,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
+}
+
+impl PoolStats {
+    pub fn has_field(field: &str) -> bool {
+        let camel_to_snake_fields: HashSet<&str> = vec![
+            "startTime",
+            "endTime",
+            "assetDepth",
+            "runeDepth",
+            "assetPrice",
+            "assetPriceUSD",
+            "units",
+            "swapCount",
+            "toAssetCount",
+            "toRuneCount",
+            "swapVolume",
+            "totalFees",
+            "averageSlip",
+            "uniqueSwapperCount",
+            "uniqueMemberCount",
+            "addLiquidityCount",
+            "withdrawCount",
+            "annualPercentageRate",
+            "poolAPY",
+            "earnings",
+            "earningsAnnualAsPercentOfDepth",
+            "saversAPR",
+        ]
+        .into_iter()
+        .collect();
+
+        camel_to_snake_fields.contains(field)
+    }
+}
+
+impl HasExtraFields for PoolStats {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatsMeta {
+    pub pool: String,
+    pub period: String,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_time: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "startPoolAPY")]
+    pub start_pool_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    #[serde(rename = "endPoolAPY")]
+    pub end_pool_apy: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub start_annual_percentage_rate: f64,
+    #[serde(deserialize_with = "deserialize_string_to_number")]
+    pub end_annual_percentage_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatsResponse {
+    #[schema(inline)]
+    pub meta: PoolStatsMeta,
+    pub intervals: Vec<PoolStats>,
+}
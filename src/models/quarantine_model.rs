@@ -0,0 +1,47 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum QuarantineKind {
+    // The whole upstream page, when the body or its meta can't be parsed.
+    Page,
+    // A single interval of an otherwise valid page.
+    Interval,
+    // An interval that parsed but broke a validation rule, replaying it skips the rules.
+    Invalid,
+}
+
+// Raw upstream JSON that failed to parse or validate, kept so it can be replayed after a model
+// update instead of being lost.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<ObjectId>,
+    pub dataset: String,
+    pub kind: QuarantineKind,
+    pub pool: Option<String>,
+    pub source_url: String,
+    pub error: String,
+    pub raw: String,
+    pub quarantined_at: f64,
+    pub replayed_at: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineResponse {
+    pub count: u64,
+    pub records: Vec<QuarantineRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantineReplayResponse {
+    pub replayed: u64,
+    pub inserted: u64,
+    pub failed: Vec<String>,
+}
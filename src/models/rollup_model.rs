@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Rollup buckets rebuilt for a dataset, over every materialized granularity.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupRebuild {
+    pub dataset: String,
+    pub pool: Option<String>,
+    pub buckets: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupRebuildResponse {
+    pub from: f64,
+    pub to: f64,
+    pub rebuilt: Vec<RollupRebuild>,
+}
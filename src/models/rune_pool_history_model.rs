@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use crate::utils::deserialize_util::deserialize_string_to_number;
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::{
+    default_granularity, default_true, deserialize_string_to_number,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -15,6 +18,20 @@ pub struct RunePoolHistory {
     pub count: f64,
     #[serde(deserialize_with = "deserialize_string_to_number")]
     pub units: f64,
+    // False while Midgard is still accumulating the interval, it is overwritten on a later sync.
+    // Records stored before the flag existed are treated as complete.
+    #[serde(default = "default_true")]
+    pub is_complete: bool,
+    // Midgard interval the row was fetched at (e.g. hour, day, month).
+    #[serde(default = "default_granularity")]
+    pub granularity: String,
+    // Set on the buckets rolled up from finer rows, unset on the intervals fetched from Midgard.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_up: bool,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
 }
 
 impl RunePoolHistory {
@@ -27,8 +44,18 @@ impl RunePoolHistory {
     }
 }
 
+impl HasExtraFields for RunePoolHistory {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct RunePoolHistoryMeta {
     #[serde(deserialize_with = "deserialize_string_to_number")]
     pub start_time: f64,
@@ -42,10 +69,16 @@ pub struct RunePoolHistoryMeta {
     pub end_units: f64,
     #[serde(deserialize_with = "deserialize_string_to_number")]
     pub end_count: f64,
+    // Share of the hourly intervals of the period that are stored, below 1 when it has gaps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    // Symbol the `rune*` amounts are in (e.g. RUNE, CACAO), the native asset of the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_asset: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct RunePoolHistoryResponse {
     #[schema(inline)]
     pub meta: RunePoolHistoryMeta,
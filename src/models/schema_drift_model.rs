@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+// Upstream keys a model doesn't know about, flattened into the model so new Midgard fields are
+// persisted instead of failing the parse.
+pub type ExtraFields = BTreeMap<String, Value>;
+
+pub trait HasExtraFields {
+    // Names of the unknown keys, nested ones are prefixed with their parent (e.g. `pools.newKey`).
+    fn extra_field_names(&self) -> Vec<String>;
+
+    fn clear_extra(&mut self);
+}
+
+// First and latest time an unknown field was seen in a dataset.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDriftField {
+    pub dataset: String,
+    pub field: String,
+    pub first_seen_at: f64,
+    pub last_seen_at: f64,
+    pub first_source_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDriftResponse {
+    pub count: usize,
+    pub fields: Vec<SchemaDriftField>,
+}
@@ -1,4 +1,7 @@
-use crate::utils::deserialize_util::deserialize_string_to_number;
+use crate::models::schema_drift_model::{ExtraFields, HasExtraFields};
+use crate::utils::deserialize_util::{
+    default_granularity, default_true, deserialize_string_to_number,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use utoipa::ToSchema;
@@ -90,6 +93,20 @@ pub struct SwapsHistory {
     #[serde(deserialize_with = "deserialize_string_to_number")]
     #[serde(rename = "runePriceUSD")]
     pub rune_price_usd: f64,
+    // False while Midgard is still accumulating the interval, it is overwritten on a later sync.
+    // Records stored before the flag existed are treated as complete.
+    #[serde(default = "default_true")]
+    pub is_complete: bool,
+    // Midgard interval the row was fetched at (e.g. hour, day, month).
+    #[serde(default = "default_granularity")]
+    pub granularity: String,
+    // Set on the buckets rolled up from finer rows, unset on the intervals fetched from Midgard.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_up: bool,
+    // Fields Midgard added after this model was written.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub extra: ExtraFields,
 }
 
 impl SwapsHistory {
@@ -141,8 +158,18 @@ impl SwapsHistory {
     }
 }
 
+impl HasExtraFields for SwapsHistory {
+    fn extra_field_names(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
+    fn clear_extra(&mut self) {
+        self.extra.clear();
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct SwapsHistoryMeta {
     #[serde(deserialize_with = "deserialize_string_to_number")]
     pub start_time: f64,
@@ -228,10 +255,16 @@ pub struct SwapsHistoryMeta {
     #[serde(deserialize_with = "deserialize_string_to_number")]
     #[serde(rename = "runePriceUSD")]
     pub rune_price_usd: f64,
+    // Share of the hourly intervals of the period that are stored, below 1 when it has gaps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    // Symbol the `rune*` amounts are in (e.g. RUNE, CACAO), the native asset of the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_asset: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct SwapsHistoryResponse {
     #[schema(inline)]
     pub meta: SwapsHistoryMeta,
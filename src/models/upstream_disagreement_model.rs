@@ -0,0 +1,27 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// A field of an interval two Midgard endpoints returned different values for.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamDisagreement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<ObjectId>,
+    pub dataset: String,
+    pub start_time: f64,
+    pub field: String,
+    pub primary_url: String,
+    pub primary_value: String,
+    pub secondary_url: String,
+    pub secondary_value: String,
+    pub detected_at: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamDisagreementsResponse {
+    pub count: usize,
+    pub disagreements: Vec<UpstreamDisagreement>,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// Number of intervals that broke a rule of a dataset, split by what was done with them.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationMetric {
+    pub dataset: String,
+    pub rule: String,
+    pub warned: i64,
+    pub rejected: i64,
+    pub last_violation_at: f64,
+    pub last_violation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationMetricsResponse {
+    // `warn` or `reject`, from `VALIDATION_MODE`.
+    pub mode: String,
+    pub count: usize,
+    pub metrics: Vec<ValidationMetric>,
+}
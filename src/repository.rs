@@ -1,5 +1,25 @@
+pub mod actions_repo;
+pub mod bulk_repo;
+pub mod coverage_repo;
 pub mod depth_history_repo;
 pub mod earnings_history_repo;
-pub mod mongodb_repository;
+pub mod history_store;
+pub mod index_repo;
+pub mod liquidity_changes_history_repo;
+pub mod member_positions_repo;
+pub mod memory_repo;
+pub mod migration_repo;
+pub mod network_history_repo;
+pub mod pool_stats_repo;
+#[cfg(feature = "postgres")]
+pub mod postgres_repo;
+pub mod quarantine_repo;
 pub mod rune_pool_history_repo;
+pub mod savers_history_repo;
+pub mod schema_drift_repo;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_repo;
+pub mod stores;
 pub mod swaps_history_repo;
+pub mod upstream_disagreement_repo;
+pub mod validation_metrics_repo;
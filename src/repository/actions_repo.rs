@@ -0,0 +1,183 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_bson, Document},
+    options::{FindOptions, ReplaceOptions},
+    Collection,
+};
+
+use crate::{
+    models::actions_model::{Action, ActionsSyncState},
+    repository::history_store::StoreError,
+};
+
+#[async_trait]
+pub trait ActionsStore: Send + Sync {
+    // Actions don't carry an id, the block time, type and inbound transactions identify them.
+    // Upserting keeps re-ingesting an already stored page idempotent.
+    async fn upsert_action(&self, action: &Action) -> Result<(), StoreError>;
+
+    // Newest first, with the number of actions matching.
+    async fn fetch_actions(
+        &self,
+        filter: &ActionsFilter,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<Action>), StoreError>;
+
+    // Actions with the transaction on their inbound or outbound side.
+    async fn fetch_actions_by_tx_id(&self, tx_id: &str) -> Result<Vec<Action>, StoreError>;
+
+    async fn get_sync_state(&self, id: &str) -> Result<Option<ActionsSyncState>, StoreError>;
+
+    async fn save_sync_state(&self, state: &ActionsSyncState) -> Result<(), StoreError>;
+}
+
+pub struct ActionsRepository {
+    col: Collection<Action>,
+    sync_col: Collection<ActionsSyncState>,
+}
+
+pub struct ActionsFilter {
+    pub address: Option<String>,
+    pub pool: Option<String>,
+    pub action_type: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl ActionsFilter {
+    fn to_document(&self) -> Document {
+        let mut filter = doc! {};
+
+        if let Some(address) = &self.address {
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { "in.address": address },
+                    doc! { "out.address": address },
+                ],
+            );
+        }
+        if let Some(pool) = &self.pool {
+            filter.insert("pools", pool);
+        }
+        if let Some(action_type) = &self.action_type {
+            filter.insert("type", action_type);
+        }
+
+        // `from` and `to` are unix seconds while actions are stored with nanosecond dates.
+        let mut date = doc! {};
+        if let Some(from) = self.from {
+            date.insert("$gte", from * 1_000_000_000);
+        }
+        if let Some(to) = self.to {
+            date.insert("$lte", to * 1_000_000_000);
+        }
+        if !date.is_empty() {
+            filter.insert("date", date);
+        }
+
+        filter
+    }
+
+    // `from` and `to` in the nanoseconds actions are stored with.
+    pub fn date_bounds(&self) -> (Option<i64>, Option<i64>) {
+        (
+            self.from.map(|from| from * 1_000_000_000),
+            self.to.map(|to| to * 1_000_000_000),
+        )
+    }
+}
+
+// Transaction ids and addresses of both sides of an action and its pools, the SQL stores look
+// actions up by them. Empty ones are left out.
+pub fn action_keys(action: &Action) -> Vec<(&'static str, &str)> {
+    let txs = action.in_txs.iter().chain(&action.out_txs);
+
+    let mut keys: Vec<(&'static str, &str)> = txs
+        .clone()
+        .map(|tx| ("txID", tx.tx_id.as_str()))
+        .chain(txs.map(|tx| ("address", tx.address.as_str())))
+        .chain(action.pools.iter().map(|pool| ("pool", pool.as_str())))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    keys
+}
+
+impl ActionsRepository {
+    pub async fn init(
+        col: Collection<Action>,
+        sync_col: Collection<ActionsSyncState>,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(ActionsRepository { col, sync_col })
+    }
+}
+
+#[async_trait]
+impl ActionsStore for ActionsRepository {
+    async fn upsert_action(&self, action: &Action) -> Result<(), StoreError> {
+        let filter = doc! {
+            "date": action.date,
+            "type": &action.action_type,
+            "in": to_bson(&action.in_txs)?,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.col.replace_one(filter, action, options).await?;
+
+        Ok(())
+    }
+
+    async fn fetch_actions(
+        &self,
+        filter: &ActionsFilter,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<Action>), StoreError> {
+        let filter = filter.to_document();
+
+        let total = self.col.count_documents(filter.clone(), None).await?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "date": -1 })
+            .skip(((page - 1).max(0) * count) as u64)
+            .limit(count)
+            .build();
+
+        let actions = self.col.find(filter, options).await?.try_collect().await?;
+
+        Ok((total, actions))
+    }
+
+    async fn fetch_actions_by_tx_id(&self, tx_id: &str) -> Result<Vec<Action>, StoreError> {
+        let filter = doc! {
+            "$or": [
+                { "in.txID": tx_id },
+                { "out.txID": tx_id },
+            ]
+        };
+        let options = FindOptions::builder().sort(doc! { "date": -1 }).build();
+
+        Ok(self.col.find(filter, options).await?.try_collect().await?)
+    }
+
+    async fn get_sync_state(&self, id: &str) -> Result<Option<ActionsSyncState>, StoreError> {
+        Ok(self.sync_col.find_one(doc! { "_id": id }, None).await?)
+    }
+
+    async fn save_sync_state(&self, state: &ActionsSyncState) -> Result<(), StoreError> {
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.sync_col
+            .replace_one(doc! { "_id": &state.id }, state, options)
+            .await?;
+
+        Ok(())
+    }
+}
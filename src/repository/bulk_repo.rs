@@ -0,0 +1,134 @@
+use mongodb::{
+    bson::{doc, to_document, Bson, Document},
+    Collection,
+};
+use serde::Serialize;
+
+use super::history_store::StoreError;
+
+// Statements per `update` command, well under the server limits on batch and command size.
+const BATCH_SIZE: usize = 500;
+
+// A row the server refused, with the filter it was upserted on to tell which one it is.
+#[derive(Debug)]
+pub struct BulkWriteFailure {
+    pub index: usize,
+    pub filter: Document,
+    pub error: String,
+}
+
+impl std::fmt::Display for BulkWriteFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} ({}): {}", self.index, self.filter, self.error)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BulkUpsertResult {
+    pub matched: u64,
+    pub upserted: u64,
+    pub failed: Vec<BulkWriteFailure>,
+    // The rows were written but not acknowledged as the write concern asks, e.g. not replicated to
+    // enough members. They may be rolled back.
+    pub write_concern_error: Option<String>,
+}
+
+impl BulkUpsertResult {
+    // Whether every row was written and acknowledged.
+    pub fn is_written(&self) -> bool {
+        self.failed.is_empty() && self.write_concern_error.is_none()
+    }
+}
+
+// Replaces or inserts every row with unordered `update` commands, one round trip per batch instead
+// of one per row. A row that fails doesn't stop the others, it is reported in `failed`.
+pub async fn bulk_upsert<T: Serialize>(
+    col: &Collection<T>,
+    rows: &[(Document, &T)],
+) -> Result<BulkUpsertResult, mongodb::error::Error> {
+    let namespace = col.namespace();
+    let db = col.client().database(&namespace.db);
+    let mut result = BulkUpsertResult::default();
+
+    for (batch_index, batch) in rows.chunks(BATCH_SIZE).enumerate() {
+        let mut updates = Vec::with_capacity(batch.len());
+
+        for (filter, row) in batch {
+            updates.push(doc! {
+                "q": filter,
+                "u": to_document(row)?,
+                "upsert": true,
+                "multi": false,
+            });
+        }
+
+        let reply = db
+            .run_command(
+                doc! { "update": &namespace.coll, "updates": updates, "ordered": false },
+                None,
+            )
+            .await?;
+
+        let written = reply.get_i32("n").unwrap_or_default() as u64;
+        let upserted = reply.get_array("upserted").map_or(0, Vec::len) as u64;
+
+        result.matched += written - upserted;
+        result.upserted += upserted;
+
+        if let Ok(write_concern_error) = reply.get_document("writeConcernError") {
+            result.write_concern_error = Some(
+                write_concern_error
+                    .get_str("errmsg")
+                    .unwrap_or("unknown write concern error")
+                    .to_string(),
+            );
+        }
+
+        let write_errors = match reply.get_array("writeErrors") {
+            Ok(write_errors) => write_errors,
+            Err(_) => continue,
+        };
+
+        for write_error in write_errors.iter().filter_map(Bson::as_document) {
+            let index = write_error.get_i32("index").unwrap_or_default() as usize;
+
+            result.failed.push(BulkWriteFailure {
+                index: batch_index * BATCH_SIZE + index,
+                filter: batch
+                    .get(index)
+                    .map(|(filter, _)| filter.clone())
+                    .unwrap_or_default(),
+                error: write_error
+                    .get_str("errmsg")
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+// Logs every row of a bulk upsert that wasn't written, returns whether all of them were.
+pub fn check_bulk_upsert(dataset: &str, result: Result<BulkUpsertResult, StoreError>) -> bool {
+    match result {
+        Ok(result) => {
+            for failure in &result.failed {
+                eprintln!("Failed to upsert {} {}", dataset, failure);
+            }
+
+            if let Some(write_concern_error) = &result.write_concern_error {
+                eprintln!(
+                    "Failed to acknowledge {} page: {}",
+                    dataset, write_concern_error
+                );
+            }
+
+            result.is_written()
+        }
+        Err(e) => {
+            eprintln!("Failed to upsert {} page: {:?}", dataset, e);
+            false
+        }
+    }
+}
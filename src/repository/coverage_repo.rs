@@ -0,0 +1,355 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{bson, doc, Bson, Document},
+    options::{FindOneOptions, FindOptions},
+    Collection,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    models::coverage_model::{CoverageGap, GapStatus},
+    repository::history_store::{
+        choose_granularity, rollup_page, HistoryQuery, HistoryRecord, StoreError,
+    },
+    utils::time_interval::TimeInterval,
+};
+
+pub fn bson_to_f64(value: &Bson) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_i64().map(|t| t as f64))
+        .or_else(|| value.as_i32().map(f64::from))
+}
+
+// Matches the rows of one granularity. Rows stored before the granularity was recorded were all
+// fetched by the hourly scheduler, so they count as hours.
+pub fn granularity_filter(granularity: &str) -> Bson {
+    if granularity == TimeInterval::Hour.to_str() {
+        bson!({ "$in": [granularity, Bson::Null] })
+    } else {
+        Bson::String(granularity.to_string())
+    }
+}
+
+// Granularity a query at `interval` is served from, chosen by `choose_granularity` from the bounds
+// of the rows of every granularity matching `filter`, read in one aggregation.
+async fn pick_granularity<T>(
+    col: &Collection<T>,
+    filter: Document,
+    query: &HistoryQuery,
+) -> Result<TimeInterval, mongodb::error::Error> {
+    let pipeline = vec![
+        doc! { "$match": filter },
+        doc! {
+            "$group": {
+                "_id": { "$ifNull": ["$granularity", TimeInterval::Hour.to_str()] },
+                "firstStartTime": { "$min": "$startTime" },
+                "lastEndTime": { "$max": "$endTime" },
+            }
+        },
+    ];
+
+    let bounds: Vec<(TimeInterval, f64, f64)> = col
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .iter()
+        .filter_map(|bounds| {
+            Some((
+                TimeInterval::from_str(bounds.get_str("_id").ok()?)?,
+                bson_to_f64(bounds.get("firstStartTime")?)?,
+                bson_to_f64(bounds.get("lastEndTime")?)?,
+            ))
+        })
+        .collect();
+
+    Ok(choose_granularity(
+        query.interval,
+        query.from,
+        query.to,
+        |granularity| {
+            bounds
+                .iter()
+                .find(|(other, _, _)| *other == granularity)
+                .map(|(_, first_start_time, last_end_time)| (*first_start_time, *last_end_time))
+        },
+    ))
+}
+
+// Rows of a history collection matching `filter` in the range of `query`, from the granularity
+// `pick_granularity` chooses, rolled up to the query interval the same way as on every backend.
+pub async fn fetch_history<T: HistoryRecord + Unpin>(
+    col: &Collection<T>,
+    mut filter: Document,
+    query: &HistoryQuery,
+) -> Result<Vec<T>, StoreError> {
+    filter.insert("startTime", doc! { "$gte": query.from });
+    filter.insert("endTime", doc! { "$lte": query.to });
+
+    let granularity = pick_granularity(col, filter.clone(), query).await?;
+    filter.insert("granularity", granularity_filter(granularity.to_str()));
+
+    let rows = fetch_intervals(col, filter)
+        .await?
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<Value>, _>>()?;
+
+    rollup_page(rows, granularity, query)
+}
+
+// Start times of the intervals of a history collection matching `filter`, oldest first. Only
+// `startTime` is read so it stays cheap on the whole collection.
+pub async fn fetch_start_times<T>(
+    col: &Collection<T>,
+    filter: Document,
+) -> Result<Vec<f64>, mongodb::error::Error> {
+    let options = FindOptions::builder()
+        .sort(doc! { "startTime": 1 })
+        .projection(doc! { "_id": 0, "startTime": 1 })
+        .build();
+
+    let docs: Vec<Document> = col
+        .clone_with_type::<Document>()
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(docs
+        .iter()
+        .filter_map(|doc| doc.get("startTime").and_then(bson_to_f64))
+        .collect())
+}
+
+// Intervals of a history collection matching `filter`, oldest first, as they were stored.
+pub async fn fetch_intervals<T: DeserializeOwned + Unpin + Send + Sync>(
+    col: &Collection<T>,
+    filter: Document,
+) -> Result<Vec<T>, mongodb::error::Error> {
+    let options = FindOptions::builder()
+        .sort(doc! { "startTime": 1 })
+        .projection(doc! { "_id": 0 })
+        .build();
+
+    col.find(filter, options).await?.try_collect().await
+}
+
+// Deletes the intervals of a history collection matching `filter`.
+pub async fn delete_intervals<T>(
+    col: &Collection<T>,
+    filter: Document,
+) -> Result<u64, mongodb::error::Error> {
+    Ok(col.delete_many(filter, None).await?.deleted_count)
+}
+
+// Latest hourly interval matching `filter` that ends at or before `end_time`. Day and month rows
+// backfilled by hand end ahead of the hourly sync, only hours are considered.
+pub async fn fetch_latest<T: DeserializeOwned + Unpin + Send + Sync>(
+    col: &Collection<T>,
+    mut filter: Document,
+    end_time: f64,
+) -> Result<Option<T>, mongodb::error::Error> {
+    filter.insert("endTime", doc! { "$lte": end_time });
+    filter.insert(
+        "granularity",
+        granularity_filter(TimeInterval::Hour.to_str()),
+    );
+
+    let options = FindOneOptions::builder()
+        .sort(doc! { "endTime": -1 })
+        .projection(doc! { "_id": 0 })
+        .build();
+
+    col.find_one(filter, options).await
+}
+
+// Oldest hourly interval matching `filter` that was still in progress when it was fetched.
+pub async fn fetch_first_incomplete<T: DeserializeOwned + Unpin + Send + Sync>(
+    col: &Collection<T>,
+    mut filter: Document,
+) -> Result<Option<T>, mongodb::error::Error> {
+    filter.insert("isComplete", false);
+    filter.insert(
+        "granularity",
+        granularity_filter(TimeInterval::Hour.to_str()),
+    );
+
+    let options = FindOneOptions::builder()
+        .sort(doc! { "startTime": 1 })
+        .projection(doc! { "_id": 0 })
+        .build();
+
+    col.find_one(filter, options).await
+}
+
+// Rows stored before the granularity was recorded.
+pub async fn count_missing_granularity<T>(
+    col: &Collection<T>,
+) -> Result<u64, mongodb::error::Error> {
+    col.count_documents(doc! { "granularity": Bson::Null }, None)
+        .await
+}
+
+// Marks at most `limit` rows without a granularity as hours, one batch of a migration.
+pub async fn mark_missing_granularity<T>(
+    col: &Collection<T>,
+    limit: i64,
+) -> Result<u64, mongodb::error::Error> {
+    let col = col.clone_with_type::<Document>();
+
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 1 })
+        .limit(limit)
+        .build();
+
+    let ids: Vec<Bson> = col
+        .find(doc! { "granularity": Bson::Null }, options)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .into_iter()
+        .filter_map(|doc| doc.get("_id").cloned())
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let result = col
+        .update_many(
+            doc! { "_id": { "$in": ids } },
+            doc! { "$set": { "granularity": TimeInterval::Hour.to_str() } },
+            None,
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+// Rows stored before the pool was recorded.
+pub async fn count_missing_pool<T>(col: &Collection<T>) -> Result<u64, mongodb::error::Error> {
+    col.count_documents(doc! { "pool": Bson::Null }, None).await
+}
+
+// Sets `pool` on at most `limit` rows without one, one batch of a migration.
+pub async fn mark_missing_pool<T>(
+    col: &Collection<T>,
+    pool: &str,
+    limit: i64,
+) -> Result<u64, mongodb::error::Error> {
+    let col = col.clone_with_type::<Document>();
+
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 1 })
+        .limit(limit)
+        .build();
+
+    let ids: Vec<Bson> = col
+        .find(doc! { "pool": Bson::Null }, options)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .into_iter()
+        .filter_map(|doc| doc.get("_id").cloned())
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let result = col
+        .update_many(
+            doc! { "_id": { "$in": ids } },
+            doc! { "$set": { "pool": pool } },
+            None,
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+#[async_trait]
+pub trait CoverageStore: Send + Sync {
+    async fn insert_gap(&self, gap: &CoverageGap) -> Result<(), StoreError>;
+
+    // Gaps by dataset, pool and start time.
+    async fn fetch_gaps(
+        &self,
+        dataset: Option<String>,
+        status: Option<GapStatus>,
+    ) -> Result<Vec<CoverageGap>, StoreError>;
+
+    // Saves the end, status and attempts of the gap with the same dataset, pool and start time.
+    async fn update_gap(&self, gap: &CoverageGap) -> Result<(), StoreError>;
+}
+
+pub struct CoverageRepository {
+    col: Collection<CoverageGap>,
+}
+
+impl CoverageRepository {
+    pub async fn init(col: Collection<CoverageGap>) -> Result<Self, Box<dyn Error>> {
+        Ok(CoverageRepository { col })
+    }
+}
+
+#[async_trait]
+impl CoverageStore for CoverageRepository {
+    async fn insert_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        self.col.insert_one(gap, None).await?;
+
+        Ok(())
+    }
+
+    async fn fetch_gaps(
+        &self,
+        dataset: Option<String>,
+        status: Option<GapStatus>,
+    ) -> Result<Vec<CoverageGap>, StoreError> {
+        let mut filter = doc! {};
+
+        if let Some(dataset) = dataset {
+            filter.insert("dataset", dataset);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.to_str());
+        }
+
+        let options = FindOptions::builder()
+            .sort(doc! { "dataset": 1, "pool": 1, "startTime": 1 })
+            .build();
+
+        Ok(self.col.find(filter, options).await?.try_collect().await?)
+    }
+
+    async fn update_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        self.col
+            .update_one(
+                doc! {
+                    "dataset": &gap.dataset,
+                    "pool": gap.pool.clone(),
+                    "startTime": gap.start_time,
+                },
+                doc! {
+                    "$set": {
+                        "endTime": gap.end_time,
+                        "missingIntervals": gap.missing_intervals,
+                        "status": gap.status.to_str(),
+                        "attempts": gap.attempts,
+                        "updatedAt": gap.updated_at,
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
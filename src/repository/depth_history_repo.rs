@@ -1,12 +1,23 @@
-use futures::stream::TryStreamExt;
+use async_trait::async_trait;
 use mongodb::{
     bson::{doc, Document},
-    results::InsertOneResult,
     Collection,
 };
 use std::error::Error;
 
-use crate::{models::depth_history_model::DepthHistory, utils::time_interval::TimeInterval};
+use crate::{
+    models::depth_history_model::DepthHistory,
+    repository::{
+        bulk_repo::{bulk_upsert, BulkUpsertResult},
+        coverage_repo::{
+            count_missing_granularity, count_missing_pool, delete_intervals,
+            fetch_first_incomplete, fetch_history, fetch_intervals, fetch_latest,
+            fetch_start_times, granularity_filter, mark_missing_granularity, mark_missing_pool,
+        },
+        history_store::{HistoryQuery, HistoryRecord, HistoryStore, StoreError},
+    },
+    utils::time_interval::TimeInterval,
+};
 
 pub struct DepthHistoryRepository {
     pub col: Collection<DepthHistory>,
@@ -16,109 +27,138 @@ impl DepthHistoryRepository {
     pub async fn init(col: Collection<DepthHistory>) -> Result<Self, Box<dyn Error>> {
         Ok(DepthHistoryRepository { col })
     }
+}
+
+impl HistoryRecord for DepthHistory {
+    fn is_sortable(field: &str) -> bool {
+        DepthHistory::has_field(field)
+    }
+}
+
+#[async_trait]
+impl HistoryStore<DepthHistory> for DepthHistoryRepository {
+    // Replaces the interval if it was already stored, so incomplete intervals get overwritten.
+    async fn upsert(
+        &self,
+        depth_histories: &[DepthHistory],
+    ) -> Result<BulkUpsertResult, StoreError> {
+        let rows: Vec<(Document, &DepthHistory)> = depth_histories
+            .iter()
+            .map(|depth_history| {
+                let filter = doc! {
+                    "pool": &depth_history.pool,
+                    "startTime": depth_history.start_time,
+                    "granularity": granularity_filter(&depth_history.granularity),
+                };
+
+                (filter, depth_history)
+            })
+            .collect();
+
+        Ok(bulk_upsert(&self.col, &rows).await?)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<DepthHistory>, StoreError> {
+        fetch_history(
+            &self.col,
+            doc! { "pool": query.pool.clone().unwrap_or_default() },
+            query,
+        )
+        .await
+    }
 
-    pub async fn insert_depth_history(
+    async fn fetch_start_times(
         &self,
-        depth_history: &DepthHistory,
-    ) -> Result<InsertOneResult, Box<dyn Error>> {
-        let insert_details = self
-            .col
-            .insert_one(depth_history, None)
-            .await
-            .expect("Failed to insert depth history.");
-
-        Ok(insert_details)
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        Ok(fetch_start_times(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
     }
 
-    pub async fn fetch_depth_history_data(
+    async fn fetch_intervals(
         &self,
         from: f64,
         to: f64,
-        count: f64,
-        interval: TimeInterval,
-        page: i64,
-        sort_by: String,
-    ) -> Result<Vec<DepthHistory>, mongodb::error::Error> {
-        let filter = doc! {
-            "startTime": { "$gte": from },
-            "endTime":{"$lte":to},
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<DepthHistory>, StoreError> {
+        Ok(fetch_intervals(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
+
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(delete_intervals(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
+
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<DepthHistory>, StoreError> {
+        let filter = match pool {
+            Some(pool) => doc! { "pool": pool },
+            None => doc! {},
         };
 
-        let mut sort_by = sort_by;
+        Ok(fetch_latest(&self.col, filter, end_time).await?)
+    }
 
-        if !DepthHistory::has_field(&sort_by) {
-            sort_by = String::from("startTime");
-        }
+    async fn fetch_first_incomplete(
+        &self,
+        pool: Option<&str>,
+    ) -> Result<Option<DepthHistory>, StoreError> {
+        let filter = match pool {
+            Some(pool) => doc! { "pool": pool },
+            None => doc! {},
+        };
 
-        let sort_stage = doc! { &sort_by: -1 };
+        Ok(fetch_first_incomplete(&self.col, filter).await?)
+    }
 
-        let skip = (page - 1).max(0) * (count as i64);
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_granularity(&self.col).await?)
+    }
 
-        let interval_seconds = interval.as_seconds();
+    async fn mark_missing_granularity(&self, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_granularity(&self.col, limit).await?)
+    }
 
-        let pipeline = vec![
-            doc! { "$match": filter },
-            doc! { "$sort": { "startTime": 1 } },
-            doc! {
-                "$group": {
-                    "_id": {
-                        "$toDate": {
-                            "$subtract": [
-                                "$startTime",
-                                { "$mod": ["$startTime", interval_seconds] }
-                            ]
-                        }
-                    },
-                    "assetDepth": { "$last": "$assetDepth" },
-                    "runeDepth": { "$last": "$runeDepth" },
-                    "assetPrice": { "$last": "$assetPrice" },
-                    "assetPriceUSD": { "$last": "$assetPriceUSD" },
-                    "liquidityUnits": { "$last": "$liquidityUnits" },
-                    "membersCount": { "$last": "$membersCount" },
-                    "synthUnits": { "$last": "$synthUnits" },
-                    "synthSupply": { "$last": "$synthSupply" },
-                    "units": { "$last": "$units" },
-                    "luvi": { "$last": "$luvi" },
-                    "startTime": { "$first": "$startTime" },
-                    "endTime": { "$last": "$endTime" }
-                }
-            },
-            doc! { "$project": {
-                "_id": 0,
-                "startTime": 1,
-                "endTime": 1,
-                "assetDepth": 1,
-                "runeDepth": 1,
-                "assetPrice": 1,
-                "assetPriceUSD": 1,
-                "liquidityUnits": 1,
-                "membersCount": 1,
-                "synthUnits": 1,
-                "synthSupply": 1,
-                "units": 1,
-                "luvi": 1
-            }},
-            doc! {"$sort" : {"startTime" : 1}},
-            doc! {"$skip" : skip},
-            doc! { "$limit": count as i64 },
-            doc! {"$sort" : sort_stage},
-        ];
-
-        let cursor = self.col.aggregate(pipeline, None).await?;
-
-        let results: Vec<DepthHistory> = cursor
-            .try_collect::<Vec<Document>>()
-            .await?
-            .into_iter()
-            .map(|doc| {
-                // Convert each Document to DepthHistory
-                mongodb::bson::from_document(doc).map_err(|e| {
-                    // Convert bson::de::Error to mongodb::error::Error
-                    mongodb::error::Error::from(e)
-                })
-            })
-            .collect::<Result<Vec<DepthHistory>, _>>()?;
+    async fn count_missing_pool(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_pool(&self.col).await?)
+    }
 
-        Ok(results)
+    async fn mark_missing_pool(&self, pool: &str, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_pool(&self.col, pool, limit).await?)
     }
 }
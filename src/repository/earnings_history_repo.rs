@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use futures::TryStreamExt;
+use async_trait::async_trait;
 use mongodb::{
     bson::{doc, Document},
     results::InsertOneResult,
@@ -9,6 +9,15 @@ use mongodb::{
 
 use crate::{
     models::earnings_history_model::{EarningsHistory, EarningsHistoryPool},
+    repository::{
+        bulk_repo::{bulk_upsert, BulkUpsertResult},
+        coverage_repo::{
+            count_missing_granularity, delete_intervals, fetch_first_incomplete, fetch_history,
+            fetch_intervals, fetch_latest, fetch_start_times, granularity_filter,
+            mark_missing_granularity,
+        },
+        history_store::{HistoryQuery, HistoryRecord, HistoryStore, StoreError},
+    },
     utils::time_interval::TimeInterval,
 };
 
@@ -25,18 +34,6 @@ impl EarningsHistoryRepository {
         Ok(EarningsHistoryRepository { col, pools_col })
     }
 
-    pub async fn insert_earnings_history(
-        &self,
-        earnings_history: &EarningsHistory,
-    ) -> Result<InsertOneResult, Box<dyn Error>> {
-        let insert_details = self
-            .col
-            .insert_one(earnings_history, None)
-            .await
-            .expect("Failed to insert earnings data into database");
-        Ok(insert_details)
-    }
-
     pub async fn insert_earnings_history_pool(
         &self,
         pool: &EarningsHistoryPool,
@@ -48,78 +45,133 @@ impl EarningsHistoryRepository {
             .map_err(|e| e)?;
         Ok(insert_details)
     }
+}
 
-    pub async fn fetch_earnings_history_data(
-        &self,
-        from: f64,
-        to: f64,
-        count: f64,
-        interval: TimeInterval,
-        page: i64,
-        sort_by: String,
-        _: String,
-    ) -> Result<Vec<EarningsHistory>, mongodb::error::Error> {
-        let filter = doc! {
-            "startTime": { "$gte": from },
-            "endTime":{"$lte":to},
-        };
+impl HistoryRecord for EarningsHistory {
+    // Fees and rewards are flows over the interval, the price is state.
+    const SUMMED_FIELDS: &'static [&'static str] = &[
+        "liquidityFees",
+        "blockRewards",
+        "earnings",
+        "bondingEarnings",
+        "liquidityEarnings",
+    ];
+
+    const TIME_WEIGHTED_FIELDS: &'static [&'static str] = &["avgNodeCount"];
+
+    const SUMMED_POOL_FIELDS: &'static [&'static str] = &[
+        "assetLiquidityFees",
+        "runeLiquidityFees",
+        "totalLiquidityFeesRune",
+        "saverEarning",
+        "rewards",
+        "earnings",
+    ];
+
+    fn is_sortable(field: &str) -> bool {
+        EarningsHistory::has_field(field)
+    }
+}
 
-        let mut sort_by = sort_by;
+#[async_trait]
+impl HistoryStore<EarningsHistory> for EarningsHistoryRepository {
+    async fn upsert(
+        &self,
+        earnings_histories: &[EarningsHistory],
+    ) -> Result<BulkUpsertResult, StoreError> {
+        let rows: Vec<(Document, &EarningsHistory)> = earnings_histories
+            .iter()
+            .map(|earnings_history| {
+                let filter = doc! {
+                    "startTime": earnings_history.start_time,
+                    "granularity": granularity_filter(&earnings_history.granularity),
+                };
+
+                (filter, earnings_history)
+            })
+            .collect();
 
-        if !EarningsHistory::has_field(&sort_by) {
-            sort_by = String::from("startTime");
-        }
+        Ok(bulk_upsert(&self.col, &rows).await?)
+    }
 
-        let _ = doc! { &sort_by: -1 };
+    async fn fetch_history(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<EarningsHistory>, StoreError> {
+        fetch_history(&self.col, doc! {}, query).await
+    }
 
-        let skip = (page - 1).max(0) * (count as i64);
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        Ok(fetch_start_times(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let interval_seconds = interval.as_seconds();
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<EarningsHistory>, StoreError> {
+        Ok(fetch_intervals(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let pipeline = vec![
-            doc! { "$match": filter },
-            doc! {"$sort" : {"startTime" : 1}},
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(delete_intervals(
+            &self.col,
             doc! {
-                "$group": {
-                    "_id": {
-                        "$toDate": {
-                            "$subtract": [
-                                "$startTime",
-                                { "$mod": ["$startTime", interval_seconds] }
-                            ]
-                        }
-                    },
-                    "startTime": { "$first": "$startTime" },
-                    "endTime": { "$last": "$endTime" },
-                    "liquidityFees": { "$last": "$liquidityFees" },
-                    "blockRewards": { "$last": "$blockRewards" },
-                    "earnings": { "$last": "$earnings" },
-                    "bondingEarnings": { "$last": "$bondingEarnings" },
-                    "liquidityEarnings": { "$last": "$liquidityEarnings" },
-                    "avgNodeCount": { "$last": "$avgNodeCount" },
-                    "runePriceUSD": { "$last": "$runePriceUSD" },
-                    "pools": { "$last": "$pools" }
-                }
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
             },
-            doc! {"$skip": skip},
-            doc! { "$limit": count as i64 },
-        ];
-
-        let cursor = self.col.aggregate(pipeline, None).await?;
-
-        let results: Vec<EarningsHistory> = cursor
-            .try_collect::<Vec<Document>>()
-            .await?
-            .into_iter()
-            .map(|doc| {
-                // Convert each Document to DepthHistory
-                mongodb::bson::from_document(doc).map_err(|e| {
-                    // Convert bson::de::Error to mongodb::error::Error
-                    mongodb::error::Error::from(e)
-                })
-            })
-            .collect::<Result<Vec<EarningsHistory>, _>>()?;
+        )
+        .await?)
+    }
+
+    async fn fetch_latest(
+        &self,
+        _pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<EarningsHistory>, StoreError> {
+        Ok(fetch_latest(&self.col, doc! {}, end_time).await?)
+    }
+
+    async fn fetch_first_incomplete(
+        &self,
+        _pool: Option<&str>,
+    ) -> Result<Option<EarningsHistory>, StoreError> {
+        Ok(fetch_first_incomplete(&self.col, doc! {}).await?)
+    }
+
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_granularity(&self.col).await?)
+    }
 
-        Ok(results)
+    async fn mark_missing_granularity(&self, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_granularity(&self.col, limit).await?)
     }
 }
@@ -0,0 +1,336 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    repository::{
+        bulk_repo::BulkUpsertResult,
+        memory_repo::{number, page, parse, set, sort_by_field},
+    },
+    utils::time_interval::TimeInterval,
+};
+
+// Errors of any storage backend, the callers only log them.
+pub type StoreError = Box<dyn std::error::Error + Send + Sync>;
+
+// A range of history rows rolled up to `interval`, `count` buckets per page. `pool` is ignored by
+// the datasets that aren't per pool.
+pub struct HistoryQuery {
+    pub from: f64,
+    pub to: f64,
+    pub count: f64,
+    pub interval: TimeInterval,
+    pub page: i64,
+    pub sort_by: String,
+    pub pool: Option<String>,
+}
+
+// A Midgard history interval. Rolled up buckets keep the last value of every field, as most of
+// them are the state at the end of the interval, except the flows listed in `SUMMED_FIELDS`.
+pub trait HistoryRecord: Serialize + DeserializeOwned + Send + Sync {
+    const SUMMED_FIELDS: &'static [&'static str] = &[];
+
+    // Averages over the interval and the field of the row they are weighted by (e.g. the average
+    // slip by the swap count).
+    const WEIGHTED_FIELDS: &'static [(&'static str, &'static str)] = &[];
+
+    // Averages weighted by the length of each row.
+    const TIME_WEIGHTED_FIELDS: &'static [&'static str] = &[];
+
+    // Flows of each entry of `pools`, summed per pool.
+    const SUMMED_POOL_FIELDS: &'static [&'static str] = &[];
+
+    // Whether the results may be sorted by this field.
+    fn is_sortable(field: &str) -> bool;
+}
+
+// Storage of one history dataset. Rows are keyed by start time, granularity and pool, so upserting
+// an interval again replaces it.
+#[async_trait]
+pub trait HistoryStore<T: HistoryRecord>: Send + Sync {
+    async fn upsert(&self, rows: &[T]) -> Result<BulkUpsertResult, StoreError>;
+
+    // Rows in the range rolled up to the query interval, from the granularity `choose_granularity`
+    // picks.
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<T>, StoreError>;
+
+    // Start times of the stored intervals of one granularity, oldest first, to measure coverage.
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError>;
+
+    // Stored intervals of one granularity starting in the range, oldest first, as they were
+    // stored.
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<T>, StoreError>;
+
+    // Deletes the intervals of one granularity starting in the range, returns how many were.
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError>;
+
+    // Latest hourly interval ending at or before `end_time`.
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<T>, StoreError>;
+
+    // Oldest hourly interval Midgard was still accumulating when it was fetched.
+    async fn fetch_first_incomplete(&self, pool: Option<&str>) -> Result<Option<T>, StoreError>;
+
+    // Rows stored before the granularity was recorded, they were all fetched hourly. Stores that
+    // always record it have none.
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(0)
+    }
+
+    // Marks at most `limit` of them as hours, returns how many were marked.
+    async fn mark_missing_granularity(&self, _limit: i64) -> Result<u64, StoreError> {
+        Ok(0)
+    }
+
+    // Rows of a per pool dataset stored before its pool was recorded. Only the depth history was
+    // ingested without one, the stores of the other datasets and those that always record it have
+    // none.
+    async fn count_missing_pool(&self) -> Result<u64, StoreError> {
+        Ok(0)
+    }
+
+    // Sets `pool` on at most `limit` of them, returns how many were set.
+    async fn mark_missing_pool(&self, _pool: &str, _limit: i64) -> Result<u64, StoreError> {
+        Ok(0)
+    }
+}
+
+// Whether the rows of a granularity, from `first_start_time` to `last_end_time`, cover a range. The
+// first row may start up to an hour inside it, as `from` needn't be on an hour. A rollup starting
+// later would drop the hours before its first bucket, the finer rows are read instead. The last
+// row may end up to one interval inside it.
+pub fn covers_range(
+    granularity: TimeInterval,
+    first_start_time: f64,
+    last_end_time: f64,
+    from: f64,
+    to: f64,
+) -> bool {
+    let (_, max_span) = granularity.span_bounds();
+
+    first_start_time <= from + TimeInterval::Hour.as_seconds() as f64
+        && last_end_time >= to - max_span as f64
+}
+
+// Granularity a query at `interval` is served from, given the first start and last end time of the
+// rows of each granularity in the range. Midgard's own rows of that interval are used when they
+// cover the range, then the finest stored granularity that covers it. If none does, the one
+// reaching furthest back is used. Every backend picks it here.
+pub fn choose_granularity(
+    interval: TimeInterval,
+    from: f64,
+    to: f64,
+    bounds: impl Fn(TimeInterval) -> Option<(f64, f64)>,
+) -> TimeInterval {
+    let to = to.min(Utc::now().timestamp() as f64);
+    let mut widest: Option<(TimeInterval, f64)> = None;
+
+    for granularity in std::iter::once(interval).chain(interval.finer()) {
+        let (first_start_time, last_end_time) = match bounds(granularity) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+
+        if covers_range(granularity, first_start_time, last_end_time, from, to) {
+            return granularity;
+        }
+
+        let is_wider = match widest {
+            Some((_, start_time)) => first_start_time < start_time,
+            None => true,
+        };
+
+        if is_wider {
+            widest = Some((granularity, first_start_time));
+        }
+    }
+
+    widest.map_or(TimeInterval::Hour, |(granularity, _)| granularity)
+}
+
+// Sums the flows of the `pools` entries per pool, the other fields are the last entry's.
+fn roll_up_pools<T: HistoryRecord>(rows: &[Value]) -> Value {
+    let mut pools: Vec<Value> = vec![];
+
+    for entry in rows
+        .iter()
+        .filter_map(|row| row.get("pools").and_then(Value::as_array))
+        .flatten()
+    {
+        let pool = entry.get("pool");
+
+        match pools.iter_mut().find(|rolled| rolled.get("pool") == pool) {
+            Some(rolled) => {
+                let mut entry = entry.clone();
+
+                for field in T::SUMMED_POOL_FIELDS {
+                    let sum = number(rolled, field) + number(&entry, field);
+                    set(&mut entry, field, Value::from(sum));
+                }
+
+                *rolled = entry;
+            }
+            None => pools.push(entry.clone()),
+        }
+    }
+
+    Value::Array(pools)
+}
+
+// The `granularity` bucket from `start` to `end` of the rows in it, oldest first. Flows are summed,
+// averages weighted and the state is the last row's. A bucket is complete once it has ended and
+// all of its rows are. It is marked `rolledUp`, apart from the rows Midgard returned at that
+// interval.
+fn roll_up_values<T: HistoryRecord>(
+    rows: &[Value],
+    granularity: TimeInterval,
+    start: f64,
+    end: f64,
+) -> Option<Value> {
+    let mut bucket = rows.last()?.clone();
+
+    for field in T::SUMMED_FIELDS {
+        let sum: f64 = rows.iter().map(|row| number(row, field)).sum();
+        set(&mut bucket, field, Value::from(sum));
+    }
+
+    let weighted_average = |field: &str, weight: &dyn Fn(&Value) -> f64| {
+        let total: f64 = rows.iter().map(weight).sum();
+
+        if total > 0.0 {
+            let sum: f64 = rows
+                .iter()
+                .map(|row| number(row, field) * weight(row))
+                .sum();
+            Some(sum / total)
+        } else {
+            None
+        }
+    };
+
+    for (field, weight) in T::WEIGHTED_FIELDS {
+        if let Some(average) = weighted_average(field, &|row| number(row, weight)) {
+            set(&mut bucket, field, Value::from(average));
+        }
+    }
+
+    for field in T::TIME_WEIGHTED_FIELDS {
+        let length = |row: &Value| number(row, "endTime") - number(row, "startTime");
+
+        if let Some(average) = weighted_average(field, &length) {
+            set(&mut bucket, field, Value::from(average));
+        }
+    }
+
+    if !T::SUMMED_POOL_FIELDS.is_empty() {
+        set(&mut bucket, "pools", roll_up_pools::<T>(rows));
+    }
+
+    let is_complete = number(&bucket, "endTime") >= end
+        && rows
+            .iter()
+            .all(|row| row.get("isComplete").and_then(Value::as_bool) != Some(false));
+
+    set(&mut bucket, "startTime", Value::from(start));
+    set(
+        &mut bucket,
+        "granularity",
+        Value::from(granularity.to_str()),
+    );
+    set(&mut bucket, "isComplete", Value::from(is_complete));
+    set(&mut bucket, "rolledUp", Value::from(true));
+
+    Some(bucket)
+}
+
+pub fn roll_up<T: HistoryRecord>(
+    rows: &[T],
+    granularity: TimeInterval,
+    start: f64,
+    end: f64,
+) -> Result<Option<T>, StoreError> {
+    let rows = rows
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<Value>, _>>()?;
+
+    roll_up_values::<T>(&rows, granularity, start, end)
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(StoreError::from)
+}
+
+// Rows of `granularity` sorted by start time, as a page of the buckets of `query`. Every backend
+// groups them here, in the calendar buckets the materialized rollups have, so a query returns the
+// same buckets whichever granularity it is served from. Rows of the requested interval are already
+// its buckets, each one is kept on its own. Buckets keep the granularity they were served from, the
+// coverage is measured on it.
+pub fn rollup_page<T: HistoryRecord>(
+    rows: Vec<Value>,
+    granularity: TimeInterval,
+    query: &HistoryQuery,
+) -> Result<Vec<T>, StoreError> {
+    let interval = query.interval;
+
+    let rows = if granularity == interval {
+        rows
+    } else {
+        let mut buckets: Vec<(f64, Vec<Value>)> = vec![];
+
+        for row in rows {
+            let start = interval.bucket_start(number(&row, "startTime"));
+
+            match buckets.last_mut() {
+                Some((last_start, bucket_rows)) if *last_start == start => bucket_rows.push(row),
+                _ => buckets.push((start, vec![row])),
+            }
+        }
+
+        buckets
+            .into_iter()
+            .filter_map(|(start, bucket_rows)| {
+                let mut bucket =
+                    roll_up_values::<T>(&bucket_rows, interval, start, interval.next_start(start))?;
+                set(
+                    &mut bucket,
+                    "granularity",
+                    Value::from(granularity.to_str()),
+                );
+                Some(bucket)
+            })
+            .collect()
+    };
+
+    let mut rows = page(rows, query.count, query.page);
+
+    let sort_by = if T::is_sortable(&query.sort_by) {
+        query.sort_by.as_str()
+    } else {
+        "startTime"
+    };
+    sort_by_field(&mut rows, sort_by, true);
+
+    parse(rows)
+}
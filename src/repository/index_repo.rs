@@ -0,0 +1,311 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    error::ErrorKind,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+
+use crate::{
+    models::index_model::{IndexProblem, IndexUsage},
+    repository::{coverage_repo::bson_to_f64, history_store::StoreError},
+};
+
+// Code of the error listing the indexes of a collection that wasn't created yet.
+const NAMESPACE_NOT_FOUND: i32 = 26;
+
+// An index the repositories query with. Time-series collections can't be upserted on
+// `startTime`, so the history collections get compound indexes instead.
+pub struct ExpectedIndex {
+    pub collection: &'static str,
+    pub keys: Document,
+    pub unique: bool,
+}
+
+impl ExpectedIndex {
+    fn new(collection: &'static str, keys: Document) -> Self {
+        ExpectedIndex {
+            collection,
+            keys,
+            unique: false,
+        }
+    }
+
+    fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    fn problem(&self, problem: &str) -> IndexProblem {
+        IndexProblem {
+            collection: self.collection.to_string(),
+            keys: serde_json::to_value(&self.keys).unwrap_or_default(),
+            unique: self.unique,
+            problem: problem.to_string(),
+        }
+    }
+}
+
+// Every index of a network's database. History rows are looked up by granularity and start time,
+// the latest by end time and the incomplete ones by `isComplete`.
+pub fn expected_indexes() -> Vec<ExpectedIndex> {
+    let mut indexes = vec![];
+
+    for collection in ["swaps_history", "rune_pool_history", "earnings_history_new"] {
+        indexes.push(ExpectedIndex::new(
+            collection,
+            doc! { "granularity": 1, "startTime": 1 },
+        ));
+        indexes.push(ExpectedIndex::new(
+            collection,
+            doc! { "granularity": 1, "endTime": -1 },
+        ));
+        indexes.push(ExpectedIndex::new(
+            collection,
+            doc! { "isComplete": 1, "granularity": 1, "startTime": 1 },
+        ));
+    }
+
+    for collection in [
+        "depth_history",
+        "savers_history",
+        "liquidity_changes_history",
+    ] {
+        indexes.push(ExpectedIndex::new(
+            collection,
+            doc! { "pool": 1, "granularity": 1, "startTime": 1 },
+        ));
+        indexes.push(ExpectedIndex::new(
+            collection,
+            doc! { "pool": 1, "granularity": 1, "endTime": -1 },
+        ));
+    }
+
+    // The scheduler resumes from the first incomplete depth interval of the default pool.
+    indexes.push(ExpectedIndex::new(
+        "depth_history",
+        doc! { "pool": 1, "isComplete": 1, "granularity": 1, "startTime": 1 },
+    ));
+
+    indexes.extend([
+        ExpectedIndex::new("network_history", doc! { "startTime": 1 }),
+        ExpectedIndex::new("actions", doc! { "in.txID": 1 }),
+        ExpectedIndex::new("actions", doc! { "out.txID": 1 }),
+        ExpectedIndex::new("actions", doc! { "in.address": 1, "date": -1 }),
+        ExpectedIndex::new("actions", doc! { "out.address": 1, "date": -1 }),
+        ExpectedIndex::new("actions", doc! { "pools": 1, "date": -1 }),
+        ExpectedIndex::new("actions", doc! { "type": 1, "date": -1 }),
+        ExpectedIndex::new("actions", doc! { "date": -1 }),
+        ExpectedIndex::new(
+            "member_positions",
+            doc! { "address": 1, "pool": 1, "startTime": 1 },
+        ),
+        ExpectedIndex::new("pool_stats", doc! { "pool": 1, "startTime": 1 }),
+        ExpectedIndex::new(
+            "quarantine",
+            doc! { "dataset": 1, "replayedAt": 1, "quarantinedAt": -1 },
+        ),
+        ExpectedIndex::new("schema_drift", doc! { "dataset": 1, "field": 1 }).unique(),
+        ExpectedIndex::new("validation_metrics", doc! { "dataset": 1, "rule": 1 }).unique(),
+        ExpectedIndex::new(
+            "coverage_gaps",
+            doc! { "dataset": 1, "pool": 1, "startTime": 1 },
+        )
+        .unique(),
+        ExpectedIndex::new("_migrations", doc! { "id": 1 }).unique(),
+        ExpectedIndex::new(
+            "upstream_disagreements",
+            doc! { "dataset": 1, "detectedAt": -1 },
+        ),
+    ]);
+
+    indexes
+}
+
+// Same fields in the same order and direction. Indexes built from the shell store `1.0` where the
+// repositories use `1`.
+fn same_keys(a: &Document, b: &Document) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|((a_key, a_value), (b_key, b_value))| {
+                a_key == b_key
+                    && (a_value == b_value
+                        || bson_to_f64(a_value).is_some()
+                            && bson_to_f64(a_value) == bson_to_f64(b_value))
+            })
+}
+
+#[async_trait]
+pub trait IndexStore: Send + Sync {
+    // Creates the expected indexes a collection is missing. Returns the ones that are still wrong,
+    // an index on the same keys with other options has to be dropped by hand.
+    async fn ensure_indexes(&self) -> Result<Vec<IndexProblem>, StoreError>;
+
+    async fn check_indexes(&self) -> Result<Vec<IndexProblem>, StoreError>;
+
+    // Every index of every collection with the number of operations that used it.
+    async fn fetch_index_usage(&self) -> Result<Vec<IndexUsage>, StoreError>;
+}
+
+pub struct IndexRepository {
+    db: Database,
+}
+
+impl IndexRepository {
+    pub fn new(db: Database) -> Self {
+        IndexRepository { db }
+    }
+
+    async fn list_indexes(&self, collection: &str) -> Result<Vec<IndexModel>, StoreError> {
+        let col = self.db.collection::<Document>(collection);
+
+        match col.list_indexes(None).await {
+            Ok(cursor) => Ok(cursor.try_collect().await?),
+            Err(e) => match *e.kind {
+                ErrorKind::Command(ref command) if command.code == NAMESPACE_NOT_FOUND => {
+                    Ok(vec![])
+                }
+                _ => Err(e.into()),
+            },
+        }
+    }
+
+    // The expected indexes that are missing or mismatched, with the mismatched flag.
+    async fn compare(&self) -> Result<Vec<(ExpectedIndex, bool)>, StoreError> {
+        let mut wrong = vec![];
+
+        for expected in expected_indexes() {
+            let existing = self.list_indexes(expected.collection).await?;
+
+            let found = existing
+                .iter()
+                .find(|index| same_keys(&index.keys, &expected.keys));
+
+            match found {
+                None => wrong.push((expected, false)),
+                Some(index) => {
+                    let unique = index
+                        .options
+                        .as_ref()
+                        .and_then(|options| options.unique)
+                        .unwrap_or(false);
+
+                    if unique != expected.unique {
+                        wrong.push((expected, true));
+                    }
+                }
+            }
+        }
+
+        Ok(wrong)
+    }
+}
+
+#[async_trait]
+impl IndexStore for IndexRepository {
+    async fn ensure_indexes(&self) -> Result<Vec<IndexProblem>, StoreError> {
+        let mut problems = vec![];
+
+        for (expected, mismatched) in self.compare().await? {
+            if mismatched {
+                problems.push(expected.problem("mismatched"));
+                continue;
+            }
+
+            let index = IndexModel::builder()
+                .keys(expected.keys.clone())
+                .options(
+                    expected
+                        .unique
+                        .then(|| IndexOptions::builder().unique(true).build()),
+                )
+                .build();
+
+            match self
+                .db
+                .collection::<Document>(expected.collection)
+                .create_index(index, None)
+                .await
+            {
+                Ok(created) => println!(
+                    "Created index {} on {}.{}",
+                    created.index_name,
+                    self.db.name(),
+                    expected.collection
+                ),
+                Err(e) => {
+                    eprintln!(
+                        "Failed to create index {} on {}.{}: {}",
+                        expected.keys,
+                        self.db.name(),
+                        expected.collection,
+                        e
+                    );
+                    problems.push(expected.problem("missing"));
+                }
+            }
+        }
+
+        Ok(problems)
+    }
+
+    async fn check_indexes(&self) -> Result<Vec<IndexProblem>, StoreError> {
+        Ok(self
+            .compare()
+            .await?
+            .into_iter()
+            .map(|(expected, mismatched)| {
+                expected.problem(if mismatched { "mismatched" } else { "missing" })
+            })
+            .collect())
+    }
+
+    async fn fetch_index_usage(&self) -> Result<Vec<IndexUsage>, StoreError> {
+        let expected = expected_indexes();
+        let mut usage = vec![];
+
+        for collection in self.db.list_collection_names(None).await? {
+            let col = self.db.collection::<Document>(&collection);
+
+            // Views have no indexes to report.
+            let stats: Vec<Document> = match col.aggregate([doc! { "$indexStats": {} }], None).await
+            {
+                Ok(cursor) => cursor.try_collect().await?,
+                Err(_) => continue,
+            };
+
+            for stat in stats {
+                let keys = stat.get_document("key").cloned().unwrap_or_default();
+                let accesses = stat.get_document("accesses").ok();
+
+                usage.push(IndexUsage {
+                    expected: same_keys(&keys, &doc! { "_id": 1 })
+                        || expected.iter().any(|index| {
+                            index.collection == collection && same_keys(&index.keys, &keys)
+                        }),
+                    collection: collection.clone(),
+                    name: stat.get_str("name").unwrap_or_default().to_string(),
+                    keys: serde_json::to_value(&keys).unwrap_or_default(),
+                    accesses: accesses
+                        .and_then(|accesses| accesses.get("ops"))
+                        .and_then(bson_to_f64)
+                        .unwrap_or_default() as i64,
+                    since: accesses
+                        .and_then(|accesses| accesses.get("since"))
+                        .and_then(Bson::as_datetime)
+                        .map(|since| since.timestamp_millis() as f64 / 1000.0),
+                });
+            }
+        }
+
+        usage.sort_by(|a, b| {
+            a.collection
+                .cmp(&b.collection)
+                .then_with(|| b.accesses.cmp(&a.accesses))
+        });
+
+        Ok(usage)
+    }
+}
@@ -0,0 +1,135 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::ReplaceOptions,
+    Collection,
+};
+
+use crate::{
+    models::member_positions_model::MemberPosition,
+    repository::history_store::{HistoryQuery, StoreError},
+};
+
+#[async_trait]
+pub trait MemberPositionsStore: Send + Sync {
+    // Only one snapshot is kept per member, pool and hour.
+    async fn upsert_member_position(
+        &self,
+        member_position: &MemberPosition,
+    ) -> Result<(), StoreError>;
+
+    // Positions of a member in the range, in every pool unless the query has one. Units, added
+    // and withdrawn amounts are running totals, so each bucket keeps the last snapshot of every
+    // pool.
+    async fn fetch_member_positions_data(
+        &self,
+        address: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<MemberPosition>, StoreError>;
+}
+
+pub struct MemberPositionsRepository {
+    col: Collection<MemberPosition>,
+}
+
+impl MemberPositionsRepository {
+    pub async fn init(col: Collection<MemberPosition>) -> Result<Self, Box<dyn Error>> {
+        Ok(MemberPositionsRepository { col })
+    }
+}
+
+#[async_trait]
+impl MemberPositionsStore for MemberPositionsRepository {
+    async fn upsert_member_position(
+        &self,
+        member_position: &MemberPosition,
+    ) -> Result<(), StoreError> {
+        let filter = doc! {
+            "address": &member_position.address,
+            "pool": &member_position.pool,
+            "startTime": member_position.start_time,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.col
+            .replace_one(filter, member_position, options)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_member_positions_data(
+        &self,
+        address: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<MemberPosition>, StoreError> {
+        let (from, to, count, interval, page) = (
+            query.from,
+            query.to,
+            query.count,
+            query.interval,
+            query.page,
+        );
+        let pool = query.pool.clone();
+
+        let mut filter = doc! {
+            "address": address,
+            "startTime": { "$gte": from },
+            "endTime":{"$lte":to},
+        };
+
+        if let Some(pool) = pool {
+            filter.insert("pool", pool);
+        }
+
+        let skip = (page - 1).max(0) * (count as i64);
+
+        let interval_seconds = interval.as_seconds();
+
+        // Units, added and withdrawn amounts are running totals, so each bucket keeps the last
+        // snapshot of every pool the member is in.
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$sort": { "startTime": 1 } },
+            doc! {
+                "$group": {
+                    "_id": {
+                        "pool": "$pool",
+                        "bucket": {
+                            "$subtract": [
+                                "$startTime",
+                                { "$mod": ["$startTime", interval_seconds] }
+                            ]
+                        }
+                    },
+                    // The last snapshot is kept whole, its unknown fields with it.
+                    "last": { "$last": "$$ROOT" },
+                    "startTime": { "$first": "$startTime" }
+                }
+            },
+            doc! {
+                "$replaceRoot": {
+                    "newRoot": { "$mergeObjects": ["$last", { "startTime": "$startTime" }] }
+                }
+            },
+            doc! { "$project": { "_id": 0 } },
+            doc! { "$sort": { "startTime": 1, "pool": 1 } },
+            doc! { "$skip": skip },
+            doc! { "$limit": count as i64 },
+        ];
+
+        let cursor = self.col.aggregate(pipeline, None).await?;
+
+        let results: Vec<MemberPosition> = cursor
+            .try_collect::<Vec<Document>>()
+            .await?
+            .into_iter()
+            .map(|doc| mongodb::bson::from_document(doc).map_err(mongodb::error::Error::from))
+            .collect::<Result<Vec<MemberPosition>, _>>()?;
+
+        Ok(results)
+    }
+}
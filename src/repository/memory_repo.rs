@@ -0,0 +1,856 @@
+use std::{cmp::Ordering, marker::PhantomData, sync::RwLock};
+
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    models::{
+        actions_model::{Action, ActionsSyncState},
+        coverage_model::{CoverageGap, GapStatus},
+        index_model::{IndexProblem, IndexUsage},
+        member_positions_model::MemberPosition,
+        migration_model::AppliedMigration,
+        network_history_model::NetworkHistory,
+        pool_stats_model::PoolStats,
+        quarantine_model::QuarantineRecord,
+        schema_drift_model::SchemaDriftField,
+        upstream_disagreement_model::UpstreamDisagreement,
+        validation_model::ValidationMetric,
+    },
+    repository::{
+        actions_repo::{ActionsFilter, ActionsStore},
+        bulk_repo::BulkUpsertResult,
+        coverage_repo::CoverageStore,
+        history_store::{
+            choose_granularity, rollup_page, HistoryQuery, HistoryRecord, HistoryStore, StoreError,
+        },
+        index_repo::IndexStore,
+        member_positions_repo::MemberPositionsStore,
+        migration_repo::MigrationStore,
+        network_history_repo::NetworkHistoryStore,
+        pool_stats_repo::PoolStatsStore,
+        quarantine_repo::QuarantineStore,
+        schema_drift_repo::SchemaDriftStore,
+        upstream_disagreement_repo::UpstreamDisagreementStore,
+        validation_metrics_repo::ValidationMetricsStore,
+    },
+    utils::{midgard_upstreams::json_number, time_interval::TimeInterval},
+};
+
+// Rows of one collection kept as the documents Mongo would store, so the rollups can read them by
+// field name like the pipelines do. Nothing outlives the process, it is meant for running the
+// service and its tests without a database.
+pub struct MemoryRepository<T> {
+    rows: RwLock<Vec<Value>>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for MemoryRepository<T> {
+    fn default() -> Self {
+        MemoryRepository {
+            rows: RwLock::new(vec![]),
+            model: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> MemoryRepository<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find(&self, predicate: impl Fn(&Value) -> bool) -> Vec<Value> {
+        self.rows
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|row| predicate(row))
+            .cloned()
+            .collect()
+    }
+
+    // Replaces the first row with the same key, or appends it. Returns whether it was replaced.
+    fn replace_or_insert(&self, row: Value, same_key: impl Fn(&Value, &Value) -> bool) -> bool {
+        let mut rows = self.rows.write().unwrap();
+
+        match rows.iter_mut().find(|stored| same_key(stored, &row)) {
+            Some(stored) => {
+                *stored = row;
+                true
+            }
+            None => {
+                rows.push(row);
+                false
+            }
+        }
+    }
+
+    // Applies `update` to the rows matching, returns how many there were.
+    fn update(&self, predicate: impl Fn(&Value) -> bool, update: impl Fn(&mut Value)) -> usize {
+        let mut rows = self.rows.write().unwrap();
+        let mut updated = 0;
+
+        for row in rows.iter_mut().filter(|row| predicate(row)) {
+            update(row);
+            updated += 1;
+        }
+
+        updated
+    }
+
+    // Stores a new document with an `_id`, like an insert into Mongo.
+    fn insert_with_id(&self, row: &T) -> Result<(), StoreError> {
+        let mut row = serde_json::to_value(row)?;
+
+        if let Value::Object(fields) = &mut row {
+            fields.insert(String::from("_id"), serde_json::to_value(ObjectId::new())?);
+        }
+
+        self.rows.write().unwrap().push(row);
+        Ok(())
+    }
+}
+
+pub fn parse<T: DeserializeOwned>(rows: Vec<Value>) -> Result<Vec<T>, StoreError> {
+    rows.into_iter()
+        .map(|row| serde_json::from_value(row).map_err(StoreError::from))
+        .collect()
+}
+
+pub fn number(row: &Value, field: &str) -> f64 {
+    row.get(field).and_then(json_number).unwrap_or_default()
+}
+
+fn text<'a>(row: &'a Value, field: &str) -> Option<&'a str> {
+    row.get(field).and_then(Value::as_str)
+}
+
+fn is_id(row: &Value, id: ObjectId) -> bool {
+    serde_json::to_value(id).is_ok_and(|id| row.get("_id") == Some(&id))
+}
+
+pub fn set(row: &mut Value, field: &str, value: Value) {
+    if let Value::Object(fields) = row {
+        fields.insert(field.to_string(), value);
+    }
+}
+
+// Numbers compare as numbers, anything else as its JSON text.
+fn compare_field(a: &Value, b: &Value, field: &str) -> Ordering {
+    match (
+        a.get(field).and_then(json_number),
+        b.get(field).and_then(json_number),
+    ) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a
+            .get(field)
+            .map(Value::to_string)
+            .cmp(&b.get(field).map(Value::to_string)),
+    }
+}
+
+pub fn sort_by_field(rows: &mut [Value], field: &str, descending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = compare_field(a, b, field);
+
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+pub fn page(rows: Vec<Value>, count: f64, page: i64) -> Vec<Value> {
+    let skip = ((page - 1).max(0) * (count as i64)) as usize;
+
+    rows.into_iter().skip(skip).take(count as usize).collect()
+}
+
+// Rows without a granularity were all fetched by the hourly scheduler, so they count as hours.
+fn granularity(row: &Value) -> &str {
+    text(row, "granularity").unwrap_or(TimeInterval::Hour.to_str())
+}
+
+// Rows of datasets that aren't per pool have no `pool`, the query pool doesn't apply to them.
+fn in_pool(row: &Value, pool: Option<&str>) -> bool {
+    match (pool, row.get("pool")) {
+        (Some(pool), Some(row_pool)) => row_pool.as_str() == Some(pool),
+        _ => true,
+    }
+}
+
+// Groups snapshots sorted by start time into buckets of `seconds`, the same way the snapshot
+// pipelines do: every field keeps the value of the last row, the start time is the first row's and
+// a bucket is only complete once all of its rows are.
+fn rollup(rows: Vec<Value>, seconds: i64) -> Vec<Value> {
+    let mut buckets: Vec<(f64, Vec<Value>)> = vec![];
+
+    for row in rows {
+        let start_time = number(&row, "startTime");
+        let bucket = start_time - start_time.rem_euclid(seconds as f64);
+
+        match buckets.last_mut() {
+            Some((last_bucket, bucket_rows)) if *last_bucket == bucket => bucket_rows.push(row),
+            _ => buckets.push((bucket, vec![row])),
+        }
+    }
+
+    buckets
+        .into_iter()
+        .filter_map(|(_, bucket_rows)| {
+            let mut merged = bucket_rows.last()?.clone();
+
+            set(
+                &mut merged,
+                "startTime",
+                bucket_rows[0].get("startTime").cloned().unwrap_or_default(),
+            );
+
+            if merged.get("isComplete").is_some() {
+                let is_complete = bucket_rows
+                    .iter()
+                    .all(|row| row.get("isComplete").and_then(Value::as_bool) != Some(false));
+                set(&mut merged, "isComplete", Value::Bool(is_complete));
+            }
+
+            Some(merged)
+        })
+        .collect()
+}
+
+fn in_range(row: &Value, from: f64, to: f64) -> bool {
+    number(row, "startTime") >= from && number(row, "endTime") <= to
+}
+
+// First start and last end time of the rows of a granularity.
+fn granularity_bounds(rows: &[Value], granularity_wanted: TimeInterval) -> Option<(f64, f64)> {
+    let rows: Vec<&Value> = rows
+        .iter()
+        .filter(|row| granularity(row) == granularity_wanted.to_str())
+        .collect();
+
+    if rows.is_empty() {
+        return None;
+    }
+
+    Some((
+        rows.iter()
+            .map(|row| number(row, "startTime"))
+            .fold(f64::MAX, f64::min),
+        rows.iter()
+            .map(|row| number(row, "endTime"))
+            .fold(f64::MIN, f64::max),
+    ))
+}
+
+#[async_trait]
+impl<T: HistoryRecord> HistoryStore<T> for MemoryRepository<T> {
+    async fn upsert(&self, rows: &[T]) -> Result<BulkUpsertResult, StoreError> {
+        let mut result = BulkUpsertResult::default();
+
+        for row in rows {
+            let replaced = self.replace_or_insert(serde_json::to_value(row)?, |stored, row| {
+                number(stored, "startTime") == number(row, "startTime")
+                    && granularity(stored) == granularity(row)
+                    && stored.get("pool") == row.get("pool")
+            });
+
+            if replaced {
+                result.matched += 1;
+            } else {
+                result.upserted += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<T>, StoreError> {
+        let pool = query.pool.as_deref();
+
+        let rows = self.find(|row| in_pool(row, pool) && in_range(row, query.from, query.to));
+
+        let granularity_picked =
+            choose_granularity(query.interval, query.from, query.to, |granularity| {
+                granularity_bounds(&rows, granularity)
+            });
+
+        let mut rows: Vec<Value> = rows
+            .into_iter()
+            .filter(|row| granularity(row) == granularity_picked.to_str())
+            .collect();
+        sort_by_field(&mut rows, "startTime", false);
+
+        rollup_page(rows, granularity_picked, query)
+    }
+
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity_wanted: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        let mut start_times: Vec<f64> = self
+            .find(|row| {
+                in_pool(row, pool)
+                    && granularity(row) == granularity_wanted.to_str()
+                    && number(row, "startTime") >= from
+                    && number(row, "startTime") < to
+            })
+            .iter()
+            .map(|row| number(row, "startTime"))
+            .collect();
+
+        start_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Ok(start_times)
+    }
+
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity_wanted: TimeInterval,
+    ) -> Result<Vec<T>, StoreError> {
+        let mut rows = self.find(|row| {
+            in_pool(row, pool)
+                && granularity(row) == granularity_wanted.to_str()
+                && number(row, "startTime") >= from
+                && number(row, "startTime") < to
+        });
+        sort_by_field(&mut rows, "startTime", false);
+
+        parse(rows)
+    }
+
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity_wanted: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        let mut rows = self.rows.write().unwrap();
+        let count = rows.len();
+
+        rows.retain(|row| {
+            !(in_pool(row, pool)
+                && granularity(row) == granularity_wanted.to_str()
+                && number(row, "startTime") >= from
+                && number(row, "startTime") < to)
+        });
+
+        Ok((count - rows.len()) as u64)
+    }
+
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<T>, StoreError> {
+        let mut rows = self.find(|row| {
+            in_pool(row, pool)
+                && granularity(row) == TimeInterval::Hour.to_str()
+                && number(row, "endTime") <= end_time
+        });
+        sort_by_field(&mut rows, "endTime", true);
+
+        Ok(parse(rows.into_iter().take(1).collect())?.pop())
+    }
+
+    async fn fetch_first_incomplete(&self, pool: Option<&str>) -> Result<Option<T>, StoreError> {
+        let mut rows = self.find(|row| {
+            in_pool(row, pool)
+                && granularity(row) == TimeInterval::Hour.to_str()
+                && row.get("isComplete").and_then(Value::as_bool) == Some(false)
+        });
+        sort_by_field(&mut rows, "startTime", false);
+
+        Ok(parse(rows.into_iter().take(1).collect())?.pop())
+    }
+}
+
+// Snapshots in a range grouped like the snapshot pipelines, each bucket keeps its last one.
+pub fn snapshot_rollup(rows: Vec<Value>, query: &HistoryQuery, sort_by: &str) -> Vec<Value> {
+    let mut rows = rows;
+    sort_by_field(&mut rows, "startTime", false);
+
+    let mut rows = page(
+        rollup(rows, query.interval.as_seconds()),
+        query.count,
+        query.page,
+    );
+    sort_by_field(&mut rows, sort_by, true);
+
+    rows
+}
+
+#[async_trait]
+impl NetworkHistoryStore for MemoryRepository<NetworkHistory> {
+    async fn upsert_network_history(
+        &self,
+        network_history: &NetworkHistory,
+    ) -> Result<(), StoreError> {
+        self.replace_or_insert(serde_json::to_value(network_history)?, |stored, row| {
+            number(stored, "startTime") == number(row, "startTime")
+        });
+
+        Ok(())
+    }
+
+    async fn fetch_network_history_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<NetworkHistory>, StoreError> {
+        let sort_by = if NetworkHistory::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+
+        let rows = self.find(|row| in_range(row, query.from, query.to));
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl PoolStatsStore for MemoryRepository<PoolStats> {
+    async fn upsert_pool_stats(&self, pool_stats: &PoolStats) -> Result<(), StoreError> {
+        self.replace_or_insert(serde_json::to_value(pool_stats)?, |stored, row| {
+            number(stored, "startTime") == number(row, "startTime")
+                && stored.get("pool") == row.get("pool")
+        });
+
+        Ok(())
+    }
+
+    async fn fetch_pool_stats_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PoolStats>, StoreError> {
+        let sort_by = if PoolStats::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+        let pool = query.pool.clone().unwrap_or_default();
+
+        let rows = self.find(|row| {
+            text(row, "pool") == Some(pool.as_str()) && in_range(row, query.from, query.to)
+        });
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl MemberPositionsStore for MemoryRepository<MemberPosition> {
+    async fn upsert_member_position(
+        &self,
+        member_position: &MemberPosition,
+    ) -> Result<(), StoreError> {
+        self.replace_or_insert(serde_json::to_value(member_position)?, |stored, row| {
+            number(stored, "startTime") == number(row, "startTime")
+                && stored.get("address") == row.get("address")
+                && stored.get("pool") == row.get("pool")
+        });
+
+        Ok(())
+    }
+
+    async fn fetch_member_positions_data(
+        &self,
+        address: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<MemberPosition>, StoreError> {
+        let pool = query.pool.as_deref();
+
+        let rows = self.find(|row| {
+            text(row, "address") == Some(address)
+                && pool.is_none_or(|pool| text(row, "pool") == Some(pool))
+                && in_range(row, query.from, query.to)
+        });
+
+        parse(member_position_buckets(rows, query))
+    }
+}
+
+// Positions of a member in a range as a page of buckets. Each pool is rolled up on its own.
+pub fn member_position_buckets(rows: Vec<Value>, query: &HistoryQuery) -> Vec<Value> {
+    let mut pools: Vec<&str> = rows.iter().filter_map(|row| text(row, "pool")).collect();
+    pools.sort();
+    pools.dedup();
+
+    let mut buckets = vec![];
+
+    for pool in pools {
+        let mut pool_rows: Vec<Value> = rows
+            .iter()
+            .filter(|row| text(row, "pool") == Some(pool))
+            .cloned()
+            .collect();
+        sort_by_field(&mut pool_rows, "startTime", false);
+
+        buckets.extend(rollup(pool_rows, query.interval.as_seconds()));
+    }
+
+    buckets
+        .sort_by(|a, b| compare_field(a, b, "startTime").then_with(|| compare_field(a, b, "pool")));
+
+    page(buckets, query.count, query.page)
+}
+
+// Actions and the cursors of their ingestion runs, the two collections of `ActionsRepository`.
+#[derive(Default)]
+pub struct MemoryActionsRepository {
+    actions: MemoryRepository<Action>,
+    sync_states: MemoryRepository<ActionsSyncState>,
+}
+
+fn has_address(row: &Value, side: &str, field: &str, value: &str) -> bool {
+    row.get(side)
+        .and_then(Value::as_array)
+        .is_some_and(|txs| txs.iter().any(|tx| text(tx, field) == Some(value)))
+}
+
+fn matches_actions_filter(row: &Value, filter: &ActionsFilter) -> bool {
+    let date = row.get("date").and_then(Value::as_i64).unwrap_or_default();
+
+    filter.address.as_deref().is_none_or(|address| {
+        has_address(row, "in", "address", address) || has_address(row, "out", "address", address)
+    }) && filter.pool.as_deref().is_none_or(|pool| {
+        row.get("pools")
+            .and_then(Value::as_array)
+            .is_some_and(|pools| pools.iter().any(|stored| stored.as_str() == Some(pool)))
+    }) && filter
+        .action_type
+        .as_deref()
+        .is_none_or(|action_type| text(row, "type") == Some(action_type))
+        // `from` and `to` are unix seconds while actions are stored with nanosecond dates.
+        && filter.from.is_none_or(|from| date >= from * 1_000_000_000)
+        && filter.to.is_none_or(|to| date <= to * 1_000_000_000)
+}
+
+#[async_trait]
+impl ActionsStore for MemoryActionsRepository {
+    async fn upsert_action(&self, action: &Action) -> Result<(), StoreError> {
+        self.actions
+            .replace_or_insert(serde_json::to_value(action)?, |stored, row| {
+                stored.get("date") == row.get("date")
+                    && stored.get("type") == row.get("type")
+                    && stored.get("in") == row.get("in")
+            });
+
+        Ok(())
+    }
+
+    async fn fetch_actions(
+        &self,
+        filter: &ActionsFilter,
+        count: i64,
+        page_number: i64,
+    ) -> Result<(u64, Vec<Action>), StoreError> {
+        let mut rows = self.actions.find(|row| matches_actions_filter(row, filter));
+        sort_by_field(&mut rows, "date", true);
+
+        let total = rows.len() as u64;
+
+        Ok((total, parse(page(rows, count as f64, page_number))?))
+    }
+
+    async fn fetch_actions_by_tx_id(&self, tx_id: &str) -> Result<Vec<Action>, StoreError> {
+        let mut rows = self.actions.find(|row| {
+            has_address(row, "in", "txID", tx_id) || has_address(row, "out", "txID", tx_id)
+        });
+        sort_by_field(&mut rows, "date", true);
+
+        parse(rows)
+    }
+
+    async fn get_sync_state(&self, id: &str) -> Result<Option<ActionsSyncState>, StoreError> {
+        Ok(parse(self.sync_states.find(|row| text(row, "_id") == Some(id)))?.pop())
+    }
+
+    async fn save_sync_state(&self, state: &ActionsSyncState) -> Result<(), StoreError> {
+        self.sync_states
+            .replace_or_insert(serde_json::to_value(state)?, |stored, row| {
+                stored.get("_id") == row.get("_id")
+            });
+
+        Ok(())
+    }
+}
+
+fn is_in_dataset(row: &Value, dataset: &Option<String>) -> bool {
+    dataset
+        .as_deref()
+        .is_none_or(|dataset| text(row, "dataset") == Some(dataset))
+}
+
+fn is_pending_in(row: &Value, dataset: &Option<String>, include_replayed: bool) -> bool {
+    is_in_dataset(row, dataset)
+        && (include_replayed || row.get("replayedAt").is_none_or(Value::is_null))
+}
+
+#[async_trait]
+impl QuarantineStore for MemoryRepository<QuarantineRecord> {
+    async fn insert_quarantine_record(&self, record: &QuarantineRecord) -> Result<(), StoreError> {
+        self.insert_with_id(record)
+    }
+
+    async fn fetch_quarantine_records(
+        &self,
+        dataset: Option<String>,
+        include_replayed: bool,
+        count: i64,
+        page_number: i64,
+    ) -> Result<(u64, Vec<QuarantineRecord>), StoreError> {
+        let mut rows = self.find(|row| is_pending_in(row, &dataset, include_replayed));
+        sort_by_field(&mut rows, "quarantinedAt", true);
+
+        let total = rows.len() as u64;
+
+        Ok((total, parse(page(rows, count as f64, page_number))?))
+    }
+
+    async fn fetch_pending_quarantine_records(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<QuarantineRecord>, StoreError> {
+        let mut rows = self.find(|row| is_pending_in(row, &dataset, false));
+        sort_by_field(&mut rows, "quarantinedAt", false);
+
+        parse(rows)
+    }
+
+    async fn fetch_quarantine_record(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<QuarantineRecord>, StoreError> {
+        Ok(parse(self.find(|row| is_id(row, id)))?.pop())
+    }
+
+    async fn mark_replayed(&self, id: ObjectId, replayed_at: f64) -> Result<(), StoreError> {
+        self.update(
+            |row| is_id(row, id),
+            |row| set(row, "replayedAt", Value::from(replayed_at)),
+        );
+
+        Ok(())
+    }
+
+    async fn update_error(&self, id: ObjectId, error: &str) -> Result<(), StoreError> {
+        self.update(
+            |row| is_id(row, id),
+            |row| set(row, "error", Value::from(error)),
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SchemaDriftStore for MemoryRepository<SchemaDriftField> {
+    async fn record_field(
+        &self,
+        dataset: &str,
+        field: &str,
+        source_url: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let is_field = |row: &Value| {
+            text(row, "dataset") == Some(dataset) && text(row, "field") == Some(field)
+        };
+
+        let updated = self.update(is_field, |row| set(row, "lastSeenAt", Value::from(seen_at)));
+
+        if updated == 0 {
+            self.rows
+                .write()
+                .unwrap()
+                .push(serde_json::to_value(SchemaDriftField {
+                    dataset: dataset.to_string(),
+                    field: field.to_string(),
+                    first_seen_at: seen_at,
+                    last_seen_at: seen_at,
+                    first_source_url: source_url.to_string(),
+                })?);
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_schema_drift(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<SchemaDriftField>, StoreError> {
+        let mut rows = self.find(|row| is_in_dataset(row, &dataset));
+        sort_by_field(&mut rows, "firstSeenAt", true);
+
+        parse(rows)
+    }
+}
+
+#[async_trait]
+impl ValidationMetricsStore for MemoryRepository<ValidationMetric> {
+    async fn record_violation(
+        &self,
+        dataset: &str,
+        rule: &str,
+        rejected: bool,
+        message: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let counter = if rejected { "rejected" } else { "warned" };
+        let is_rule =
+            |row: &Value| text(row, "dataset") == Some(dataset) && text(row, "rule") == Some(rule);
+
+        let updated = self.update(is_rule, |row| {
+            let count = row.get(counter).and_then(Value::as_i64).unwrap_or_default();
+            set(row, counter, Value::from(count + 1));
+            set(row, "lastViolationAt", Value::from(seen_at));
+            set(row, "lastViolation", Value::from(message));
+        });
+
+        if updated == 0 {
+            self.rows
+                .write()
+                .unwrap()
+                .push(serde_json::to_value(ValidationMetric {
+                    dataset: dataset.to_string(),
+                    rule: rule.to_string(),
+                    warned: i64::from(!rejected),
+                    rejected: i64::from(rejected),
+                    last_violation_at: seen_at,
+                    last_violation: message.to_string(),
+                })?);
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_validation_metrics(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<ValidationMetric>, StoreError> {
+        let mut rows = self.find(|row| is_in_dataset(row, &dataset));
+        rows.sort_by(|a, b| {
+            compare_field(a, b, "dataset").then_with(|| compare_field(a, b, "rule"))
+        });
+
+        parse(rows)
+    }
+}
+
+#[async_trait]
+impl CoverageStore for MemoryRepository<CoverageGap> {
+    async fn insert_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        self.insert_with_id(gap)
+    }
+
+    async fn fetch_gaps(
+        &self,
+        dataset: Option<String>,
+        status: Option<GapStatus>,
+    ) -> Result<Vec<CoverageGap>, StoreError> {
+        let mut rows = self.find(|row| {
+            is_in_dataset(row, &dataset)
+                && status
+                    .as_ref()
+                    .is_none_or(|status| text(row, "status") == Some(status.to_str()))
+        });
+        rows.sort_by(|a, b| {
+            compare_field(a, b, "dataset")
+                .then_with(|| compare_field(a, b, "pool"))
+                .then_with(|| compare_field(a, b, "startTime"))
+        });
+
+        parse(rows)
+    }
+
+    async fn update_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        let updated = serde_json::to_value(gap)?;
+
+        self.update(
+            |row| {
+                text(row, "dataset") == Some(gap.dataset.as_str())
+                    && text(row, "pool") == gap.pool.as_deref()
+                    && number(row, "startTime") == gap.start_time
+            },
+            |row| {
+                for field in [
+                    "endTime",
+                    "missingIntervals",
+                    "status",
+                    "attempts",
+                    "updatedAt",
+                ] {
+                    set(row, field, updated[field].clone());
+                }
+            },
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpstreamDisagreementStore for MemoryRepository<UpstreamDisagreement> {
+    async fn insert_disagreements(
+        &self,
+        disagreements: &[UpstreamDisagreement],
+    ) -> Result<(), StoreError> {
+        for disagreement in disagreements {
+            self.insert_with_id(disagreement)?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_disagreements(
+        &self,
+        dataset: Option<String>,
+        count: i64,
+        page_number: i64,
+    ) -> Result<Vec<UpstreamDisagreement>, StoreError> {
+        let mut rows = self.find(|row| is_in_dataset(row, &dataset));
+        sort_by_field(&mut rows, "detectedAt", true);
+
+        parse(page(rows, count as f64, page_number))
+    }
+}
+
+// Nothing to index, every query scans the rows.
+#[async_trait]
+impl IndexStore for MemoryRepository<IndexUsage> {
+    async fn ensure_indexes(&self) -> Result<Vec<IndexProblem>, StoreError> {
+        Ok(vec![])
+    }
+
+    async fn check_indexes(&self) -> Result<Vec<IndexProblem>, StoreError> {
+        Ok(vec![])
+    }
+
+    async fn fetch_index_usage(&self) -> Result<Vec<IndexUsage>, StoreError> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl MigrationStore for MemoryRepository<AppliedMigration> {
+    async fn fetch_applied(&self) -> Result<Vec<AppliedMigration>, StoreError> {
+        let mut rows = self.find(|_| true);
+        rows.sort_by(|a, b| text(a, "id").cmp(&text(b, "id")));
+
+        parse(rows)
+    }
+
+    async fn record_applied(&self, migration: &AppliedMigration) -> Result<(), StoreError> {
+        self.insert_with_id(migration)
+    }
+}
@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Collection};
+
+use crate::{models::migration_model::AppliedMigration, repository::history_store::StoreError};
+
+#[async_trait]
+pub trait MigrationStore: Send + Sync {
+    // Applied migrations, in the order of their ids.
+    async fn fetch_applied(&self) -> Result<Vec<AppliedMigration>, StoreError>;
+
+    async fn record_applied(&self, migration: &AppliedMigration) -> Result<(), StoreError>;
+}
+
+pub struct MigrationRepository {
+    col: Collection<AppliedMigration>,
+}
+
+impl MigrationRepository {
+    pub fn new(col: Collection<AppliedMigration>) -> Self {
+        MigrationRepository { col }
+    }
+}
+
+#[async_trait]
+impl MigrationStore for MigrationRepository {
+    async fn fetch_applied(&self) -> Result<Vec<AppliedMigration>, StoreError> {
+        let options = FindOptions::builder()
+            .sort(doc! { "id": 1 })
+            .projection(doc! { "_id": 0 })
+            .build();
+
+        Ok(self.col.find(None, options).await?.try_collect().await?)
+    }
+
+    async fn record_applied(&self, migration: &AppliedMigration) -> Result<(), StoreError> {
+        self.col.insert_one(migration, None).await?;
+
+        Ok(())
+    }
+}
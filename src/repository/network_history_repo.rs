@@ -0,0 +1,131 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::ReplaceOptions,
+    Collection,
+};
+
+use crate::{
+    models::network_history_model::NetworkHistory,
+    repository::history_store::{HistoryQuery, StoreError},
+};
+
+#[async_trait]
+pub trait NetworkHistoryStore: Send + Sync {
+    // Only one snapshot is kept per hour, a later tick in the same hour replaces it.
+    async fn upsert_network_history(
+        &self,
+        network_history: &NetworkHistory,
+    ) -> Result<(), StoreError>;
+
+    // Snapshots in the range, each bucket keeps its last one.
+    async fn fetch_network_history_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<NetworkHistory>, StoreError>;
+}
+
+pub struct NetworkHistoryRepository {
+    col: Collection<NetworkHistory>,
+}
+
+impl NetworkHistoryRepository {
+    pub async fn init(col: Collection<NetworkHistory>) -> Result<Self, Box<dyn Error>> {
+        Ok(NetworkHistoryRepository { col })
+    }
+}
+
+#[async_trait]
+impl NetworkHistoryStore for NetworkHistoryRepository {
+    async fn upsert_network_history(
+        &self,
+        network_history: &NetworkHistory,
+    ) -> Result<(), StoreError> {
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.col
+            .replace_one(
+                doc! { "startTime": network_history.start_time },
+                network_history,
+                options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_network_history_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<NetworkHistory>, StoreError> {
+        let (from, to, count, interval, page) = (
+            query.from,
+            query.to,
+            query.count,
+            query.interval,
+            query.page,
+        );
+        let sort_by = query.sort_by.clone();
+
+        let filter = doc! {
+            "startTime": { "$gte": from },
+            "endTime":{"$lte":to},
+        };
+
+        let mut sort_by = sort_by;
+
+        if !NetworkHistory::has_field(&sort_by) {
+            sort_by = String::from("startTime");
+        }
+
+        let sort_stage = doc! { &sort_by: -1 };
+
+        let skip = (page - 1).max(0) * (count as i64);
+
+        let interval_seconds = interval.as_seconds();
+
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$sort": { "startTime": 1 } },
+            doc! {
+                "$group": {
+                    "_id": {
+                        "$toDate": {
+                            "$subtract": [
+                                "$startTime",
+                                { "$mod": ["$startTime", interval_seconds] }
+                            ]
+                        }
+                    },
+                    // The last snapshot is kept whole, its unknown fields with it.
+                    "last": { "$last": "$$ROOT" },
+                    "startTime": { "$first": "$startTime" }
+                }
+            },
+            doc! {
+                "$replaceRoot": {
+                    "newRoot": { "$mergeObjects": ["$last", { "startTime": "$startTime" }] }
+                }
+            },
+            doc! { "$project": { "_id": 0 } },
+            doc! { "$sort": { "startTime": 1 } },
+            doc! { "$skip": skip },
+            doc! { "$limit": count as i64 },
+            doc! { "$sort": sort_stage },
+        ];
+
+        let cursor = self.col.aggregate(pipeline, None).await?;
+
+        let results: Vec<NetworkHistory> = cursor
+            .try_collect::<Vec<Document>>()
+            .await?
+            .into_iter()
+            .map(|doc| mongodb::bson::from_document(doc).map_err(mongodb::error::Error::from))
+            .collect::<Result<Vec<NetworkHistory>, _>>()?;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::ReplaceOptions,
+    Collection,
+};
+
+use crate::{
+    models::pool_stats_model::PoolStats,
+    repository::history_store::{HistoryQuery, StoreError},
+};
+
+#[async_trait]
+pub trait PoolStatsStore: Send + Sync {
+    // Only one snapshot is kept per pool and hour, a later tick in the same hour replaces it.
+    async fn upsert_pool_stats(&self, pool_stats: &PoolStats) -> Result<(), StoreError>;
+
+    // Snapshots of the query pool in the range, each bucket keeps its last one.
+    async fn fetch_pool_stats_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PoolStats>, StoreError>;
+}
+
+pub struct PoolStatsRepository {
+    col: Collection<PoolStats>,
+}
+
+impl PoolStatsRepository {
+    pub async fn init(col: Collection<PoolStats>) -> Result<Self, Box<dyn Error>> {
+        Ok(PoolStatsRepository { col })
+    }
+}
+
+#[async_trait]
+impl PoolStatsStore for PoolStatsRepository {
+    async fn upsert_pool_stats(&self, pool_stats: &PoolStats) -> Result<(), StoreError> {
+        let filter = doc! {
+            "pool": &pool_stats.pool,
+            "startTime": pool_stats.start_time,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+
+        self.col.replace_one(filter, pool_stats, options).await?;
+
+        Ok(())
+    }
+
+    async fn fetch_pool_stats_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PoolStats>, StoreError> {
+        let (from, to, count, interval, page) = (
+            query.from,
+            query.to,
+            query.count,
+            query.interval,
+            query.page,
+        );
+        let sort_by = query.sort_by.clone();
+        let pool = query.pool.clone().unwrap_or_default();
+
+        let filter = doc! {
+            "pool": &pool,
+            "startTime": { "$gte": from },
+            "endTime":{"$lte":to},
+        };
+
+        let mut sort_by = sort_by;
+
+        if !PoolStats::has_field(&sort_by) {
+            sort_by = String::from("startTime");
+        }
+
+        let sort_stage = doc! { &sort_by: -1 };
+
+        let skip = (page - 1).max(0) * (count as i64);
+
+        let interval_seconds = interval.as_seconds();
+
+        // Every snapshot already covers Midgard's whole stats period, so a bucket keeps its last one.
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$sort": { "startTime": 1 } },
+            doc! {
+                "$group": {
+                    "_id": {
+                        "$toDate": {
+                            "$subtract": [
+                                "$startTime",
+                                { "$mod": ["$startTime", interval_seconds] }
+                            ]
+                        }
+                    },
+                    // The last snapshot is kept whole, its unknown fields with it.
+                    "last": { "$last": "$$ROOT" },
+                    "startTime": { "$first": "$startTime" }
+                }
+            },
+            doc! {
+                "$replaceRoot": {
+                    "newRoot": { "$mergeObjects": ["$last", { "startTime": "$startTime" }] }
+                }
+            },
+            doc! { "$project": { "_id": 0 } },
+            doc! { "$sort": { "startTime": 1 } },
+            doc! { "$skip": skip },
+            doc! { "$limit": count as i64 },
+            doc! { "$sort": sort_stage },
+        ];
+
+        let cursor = self.col.aggregate(pipeline, None).await?;
+
+        let results: Vec<PoolStats> = cursor
+            .try_collect::<Vec<Document>>()
+            .await?
+            .into_iter()
+            .map(|doc| mongodb::bson::from_document(doc).map_err(mongodb::error::Error::from))
+            .collect::<Result<Vec<PoolStats>, _>>()?;
+
+        Ok(results)
+    }
+}
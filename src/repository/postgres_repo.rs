@@ -0,0 +1,1108 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use mongodb::bson::doc;
+use mongodb::bson::oid::ObjectId;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio_postgres::{types::ToSql, Client, Config, NoTls, Transaction};
+
+use crate::{
+    models::{
+        actions_model::{Action, ActionsSyncState},
+        coverage_model::{CoverageGap, GapStatus},
+        member_positions_model::MemberPosition,
+        migration_model::AppliedMigration,
+        network_history_model::NetworkHistory,
+        pool_stats_model::PoolStats,
+        quarantine_model::QuarantineRecord,
+        schema_drift_model::SchemaDriftField,
+        upstream_disagreement_model::UpstreamDisagreement,
+        validation_model::ValidationMetric,
+    },
+    repository::{
+        actions_repo::{action_keys, ActionsFilter, ActionsStore},
+        bulk_repo::{BulkUpsertResult, BulkWriteFailure},
+        coverage_repo::CoverageStore,
+        history_store::{
+            choose_granularity, rollup_page, HistoryQuery, HistoryRecord, HistoryStore, StoreError,
+        },
+        member_positions_repo::MemberPositionsStore,
+        memory_repo::{member_position_buckets, number, parse, set, snapshot_rollup},
+        migration_repo::MigrationStore,
+        network_history_repo::NetworkHistoryStore,
+        pool_stats_repo::PoolStatsStore,
+        quarantine_repo::QuarantineStore,
+        schema_drift_repo::SchemaDriftStore,
+        upstream_disagreement_repo::UpstreamDisagreementStore,
+        validation_metrics_repo::ValidationMetricsStore,
+    },
+    utils::time_interval::TimeInterval,
+};
+
+// Schema changes in the order they are applied, each one once per schema.
+const MIGRATIONS: [(&str, &str); 3] = [
+    (
+        "0001_history_tables",
+        include_str!("../../migrations/postgres/0001_history_tables.sql"),
+    ),
+    (
+        "0002_pool_history_and_documents",
+        include_str!("../../migrations/postgres/0002_pool_history_and_documents.sql"),
+    ),
+    (
+        "0003_collection_tables",
+        include_str!("../../migrations/postgres/0003_collection_tables.sql"),
+    ),
+];
+
+// Connection to the schema of one network, named after its Mongo database.
+pub struct PostgresDatabase {
+    client: Client,
+    // Connection of the upserts, a page is written in one transaction on it.
+    writer: Mutex<Client>,
+}
+
+// `schema` as a quoted identifier, so any network name is a valid one.
+fn quote_identifier(schema: &str) -> String {
+    format!("\"{}\"", schema.replace('"', "\"\""))
+}
+
+impl PostgresDatabase {
+    // Connects with `schema` first in the search path, then `public` for the extensions installed
+    // there, creating it and applying the pending migrations.
+    pub async fn connect(config: &Config, schema: &str) -> Result<Arc<Self>, StoreError> {
+        let mut config = config.clone();
+
+        // The server splits the options on spaces, the ones of the schema are escaped.
+        let search_path = format!("{},public", quote_identifier(schema))
+            .replace('\\', "\\\\")
+            .replace(' ', "\\ ");
+        config.options(format!("-c search_path={}", search_path));
+
+        let mut client = Self::open(&config).await?;
+
+        client
+            .batch_execute(&format!(
+                "CREATE SCHEMA IF NOT EXISTS {};
+                CREATE TABLE IF NOT EXISTS schema_migrations (
+                    id TEXT PRIMARY KEY,
+                    applied_at DOUBLE PRECISION NOT NULL
+                );",
+                quote_identifier(schema)
+            ))
+            .await?;
+
+        for (id, sql) in MIGRATIONS {
+            let transaction = client.transaction().await?;
+
+            let applied = transaction
+                .query_opt("SELECT id FROM schema_migrations WHERE id = $1", &[&id])
+                .await?
+                .is_some();
+
+            if applied {
+                continue;
+            }
+
+            transaction.batch_execute(sql).await?;
+            transaction
+                .execute(
+                    "INSERT INTO schema_migrations (id, applied_at) VALUES ($1, $2)",
+                    &[&id, &(Utc::now().timestamp() as f64)],
+                )
+                .await?;
+            transaction.commit().await?;
+
+            println!("Applied postgres migration {} to {}", id, schema);
+        }
+
+        Ok(Arc::new(PostgresDatabase {
+            client,
+            writer: Mutex::new(Self::open(&config).await?),
+        }))
+    }
+
+    async fn open(config: &Config) -> Result<Client, StoreError> {
+        let (client, connection) = config.connect(NoTls).await?;
+
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("Postgres connection closed: {}", e);
+            }
+        });
+
+        Ok(client)
+    }
+}
+
+// A history dataset in its table of `0001_history_tables` or `0002_pool_history_and_documents`.
+pub struct PostgresHistoryRepository<T> {
+    db: Arc<PostgresDatabase>,
+    table: &'static str,
+    // Child table the `pools` of each row are kept in, one row per pool.
+    pools_table: Option<&'static str>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T> PostgresHistoryRepository<T> {
+    pub fn new(
+        db: Arc<PostgresDatabase>,
+        table: &'static str,
+        pools_table: Option<&'static str>,
+    ) -> Self {
+        PostgresHistoryRepository {
+            db,
+            table,
+            pools_table,
+            model: PhantomData,
+        }
+    }
+
+    // The stored model of the row `h`, with its pools put back.
+    fn row_sql(&self) -> String {
+        match self.pools_table {
+            Some(pools_table) => format!(
+                "h.data || jsonb_build_object('pools', coalesce((
+                    SELECT jsonb_agg(p.data ORDER BY p.position) FROM {} p
+                    WHERE p.granularity = h.granularity AND p.start_time = h.start_time
+                ), '[]'::jsonb))",
+                pools_table
+            ),
+            None => String::from("h.data"),
+        }
+    }
+
+    async fn upsert_row(
+        &self,
+        transaction: &Transaction<'_>,
+        mut data: Value,
+    ) -> Result<bool, StoreError> {
+        let pools = match (self.pools_table, &mut data) {
+            (Some(_), Value::Object(fields)) => fields.remove("pools"),
+            _ => None,
+        };
+
+        let pool = data.get("pool").and_then(Value::as_str).unwrap_or_default();
+        let granularity = data
+            .get("granularity")
+            .and_then(Value::as_str)
+            .unwrap_or(TimeInterval::Hour.to_str());
+        let start_time = number(&data, "startTime");
+        let is_complete = data
+            .get("isComplete")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+
+        let inserted: bool = transaction
+            .query_one(
+                &format!(
+                    "INSERT INTO {} (pool, granularity, start_time, end_time, is_complete, data)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (pool, granularity, start_time) DO UPDATE SET
+                        end_time = EXCLUDED.end_time,
+                        is_complete = EXCLUDED.is_complete,
+                        data = EXCLUDED.data
+                    RETURNING xmax = 0",
+                    self.table
+                ),
+                &[
+                    &pool,
+                    &granularity,
+                    &start_time,
+                    &number(&data, "endTime"),
+                    &is_complete,
+                    &data,
+                ],
+            )
+            .await?
+            .get(0);
+
+        if let (Some(pools_table), Some(Value::Array(pools))) = (self.pools_table, pools) {
+            transaction
+                .execute(
+                    &format!(
+                        "DELETE FROM {} WHERE granularity = $1 AND start_time = $2",
+                        pools_table
+                    ),
+                    &[&granularity, &start_time],
+                )
+                .await?;
+
+            for (position, pool) in pools.iter().enumerate() {
+                transaction
+                    .execute(
+                        &format!(
+                            "INSERT INTO {} (granularity, start_time, pool, position, data)
+                            VALUES ($1, $2, $3, $4, $5)
+                            ON CONFLICT (granularity, start_time, pool) DO UPDATE SET
+                                position = EXCLUDED.position,
+                                data = EXCLUDED.data",
+                            pools_table
+                        ),
+                        &[
+                            &granularity,
+                            &start_time,
+                            &pool.get("pool").and_then(Value::as_str).unwrap_or_default(),
+                            &(position as i32),
+                            pool,
+                        ],
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(inserted)
+    }
+
+    // First hourly row of the pool matching `condition`, its parameters start at `$3`.
+    async fn fetch_one(
+        &self,
+        pool: Option<&str>,
+        condition: &str,
+        order: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<T>, StoreError>
+    where
+        T: HistoryRecord,
+    {
+        let hour = TimeInterval::Hour.to_str();
+        let mut all_params: Vec<&(dyn ToSql + Sync)> = vec![&pool, &hour];
+        all_params.extend_from_slice(params);
+
+        let row = self
+            .db
+            .client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM {} h
+                    WHERE ($1::TEXT IS NULL OR h.pool = $1) AND h.granularity = $2 AND {}
+                    ORDER BY {} LIMIT 1",
+                    self.row_sql(),
+                    self.table,
+                    condition,
+                    order
+                ),
+                &all_params,
+            )
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(serde_json::from_value(row.get(0))?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: HistoryRecord> HistoryStore<T> for PostgresHistoryRepository<T> {
+    async fn upsert(&self, rows: &[T]) -> Result<BulkUpsertResult, StoreError> {
+        let mut result = BulkUpsertResult::default();
+
+        let mut writer = self.db.writer.lock().await;
+        let mut transaction = writer.transaction().await?;
+
+        for (index, row) in rows.iter().enumerate() {
+            let data = serde_json::to_value(row)?;
+            let filter = doc! {
+                "startTime": number(&data, "startTime"),
+                "granularity": data.get("granularity").and_then(Value::as_str),
+            };
+
+            // A failed statement aborts the whole transaction, each row is rolled back to its own
+            // savepoint instead so the rest of the page is still written.
+            let savepoint = transaction.savepoint("row").await?;
+
+            match self.upsert_row(&savepoint, data).await {
+                Ok(inserted) => {
+                    savepoint.commit().await?;
+
+                    if inserted {
+                        result.upserted += 1;
+                    } else {
+                        result.matched += 1;
+                    }
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+
+                    result.failed.push(BulkWriteFailure {
+                        index,
+                        filter,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(result)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<T>, StoreError> {
+        let bounds = self
+            .db
+            .client
+            .query(
+                &format!(
+                    "SELECT h.granularity, min(h.start_time), max(h.end_time) FROM {} h
+                    WHERE ($1::TEXT IS NULL OR h.pool = $1)
+                        AND h.start_time >= $2 AND h.end_time <= $3
+                    GROUP BY h.granularity",
+                    self.table
+                ),
+                &[&query.pool, &query.from, &query.to],
+            )
+            .await?;
+
+        let granularity = choose_granularity(query.interval, query.from, query.to, |granularity| {
+            bounds
+                .iter()
+                .find(|row| row.get::<_, &str>(0) == granularity.to_str())
+                .map(|row| (row.get(1), row.get(2)))
+        });
+
+        let rows = self
+            .db
+            .client
+            .query(
+                &format!(
+                    "SELECT {} FROM {} h
+                    WHERE ($1::TEXT IS NULL OR h.pool = $1) AND h.granularity = $2
+                        AND h.start_time >= $3 AND h.end_time <= $4
+                    ORDER BY h.start_time",
+                    self.row_sql(),
+                    self.table
+                ),
+                &[&query.pool, &granularity.to_str(), &query.from, &query.to],
+            )
+            .await?;
+
+        rollup_page(
+            rows.iter().map(|row| row.get(0)).collect(),
+            granularity,
+            query,
+        )
+    }
+
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        let rows = self
+            .db
+            .client
+            .query(
+                &format!(
+                    "SELECT h.start_time FROM {} h
+                    WHERE ($1::TEXT IS NULL OR h.pool = $1) AND h.granularity = $2
+                        AND h.start_time >= $3 AND h.start_time < $4
+                    ORDER BY h.start_time",
+                    self.table
+                ),
+                &[&pool, &granularity.to_str(), &from, &to],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<T>, StoreError> {
+        let rows = self
+            .db
+            .client
+            .query(
+                &format!(
+                    "SELECT {} FROM {} h
+                    WHERE ($1::TEXT IS NULL OR h.pool = $1) AND h.granularity = $2
+                        AND h.start_time >= $3 AND h.start_time < $4
+                    ORDER BY h.start_time",
+                    self.row_sql(),
+                    self.table
+                ),
+                &[&pool, &granularity.to_str(), &from, &to],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| serde_json::from_value(row.get(0)).map_err(StoreError::from))
+            .collect()
+    }
+
+    // The pools of the deleted rows go with them, through the foreign key.
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(self
+            .db
+            .client
+            .execute(
+                &format!(
+                    "DELETE FROM {}
+                    WHERE ($1::TEXT IS NULL OR pool = $1) AND granularity = $2
+                        AND start_time >= $3 AND start_time < $4",
+                    self.table
+                ),
+                &[&pool, &granularity.to_str(), &from, &to],
+            )
+            .await?)
+    }
+
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<T>, StoreError> {
+        self.fetch_one(pool, "h.end_time <= $3", "h.end_time DESC", &[&end_time])
+            .await
+    }
+
+    async fn fetch_first_incomplete(&self, pool: Option<&str>) -> Result<Option<T>, StoreError> {
+        self.fetch_one(pool, "NOT h.is_complete", "h.start_time", &[])
+            .await
+    }
+
+    async fn count_missing_pool(&self) -> Result<u64, StoreError> {
+        let count: i64 = self
+            .db
+            .client
+            .query_one(
+                &format!("SELECT count(*) FROM {} WHERE pool = ''", self.table),
+                &[],
+            )
+            .await?
+            .get(0);
+
+        Ok(count as u64)
+    }
+
+    async fn mark_missing_pool(&self, pool: &str, limit: i64) -> Result<u64, StoreError> {
+        Ok(self
+            .db
+            .client
+            .execute(
+                &format!(
+                    "UPDATE {0} SET pool = $1, data = jsonb_set(data, '{{pool}}', to_jsonb($1::TEXT))
+                    WHERE ctid IN (SELECT ctid FROM {0} WHERE pool = '' LIMIT $2)",
+                    self.table
+                ),
+                &[&pool, &limit],
+            )
+            .await?)
+    }
+}
+
+// A collection that isn't a history dataset, in its table of `0003_collection_tables`. The model
+// is kept whole in `data`, the fields it is looked up and sorted by are copied out to columns.
+pub struct PostgresRepository<T> {
+    db: Arc<PostgresDatabase>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> PostgresRepository<T> {
+    pub fn new(db: Arc<PostgresDatabase>) -> Self {
+        PostgresRepository {
+            db,
+            model: PhantomData,
+        }
+    }
+
+    // The `data` of the rows `sql` selects.
+    async fn select_values(
+        &self,
+        sql: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Value>, StoreError> {
+        let rows = self.db.client.query(sql, params).await?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn select(
+        &self,
+        sql: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, StoreError> {
+        parse(self.select_values(sql, params).await?)
+    }
+
+    async fn count(&self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64, StoreError> {
+        let count: i64 = self.db.client.query_one(sql, params).await?.get(0);
+
+        Ok(count as u64)
+    }
+
+    async fn execute(&self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<(), StoreError> {
+        self.db.client.execute(sql, params).await?;
+
+        Ok(())
+    }
+}
+
+// Rows skipped before a page.
+fn offset(count: i64, page: i64) -> i64 {
+    (page - 1).max(0) * count
+}
+
+// `row` with a new `_id`, the key of the collections Mongo gives the ids of.
+fn with_id<T: Serialize>(row: &T) -> Result<(String, Value), StoreError> {
+    let id = ObjectId::new();
+    let mut data = serde_json::to_value(row)?;
+    set(&mut data, "_id", serde_json::to_value(id)?);
+
+    Ok((id.to_hex(), data))
+}
+
+#[async_trait]
+impl NetworkHistoryStore for PostgresRepository<NetworkHistory> {
+    async fn upsert_network_history(
+        &self,
+        network_history: &NetworkHistory,
+    ) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO network_history (start_time, data) VALUES ($1, $2)
+            ON CONFLICT (start_time) DO UPDATE SET data = EXCLUDED.data",
+            &[
+                &network_history.start_time,
+                &serde_json::to_value(network_history)?,
+            ],
+        )
+        .await
+    }
+
+    async fn fetch_network_history_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<NetworkHistory>, StoreError> {
+        let sort_by = if NetworkHistory::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+
+        let rows = self
+            .select_values(
+                "SELECT data FROM network_history
+                WHERE start_time >= $1 AND (data->>'endTime')::DOUBLE PRECISION <= $2",
+                &[&query.from, &query.to],
+            )
+            .await?;
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl PoolStatsStore for PostgresRepository<PoolStats> {
+    async fn upsert_pool_stats(&self, pool_stats: &PoolStats) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO pool_stats (pool, start_time, data) VALUES ($1, $2, $3)
+            ON CONFLICT (pool, start_time) DO UPDATE SET data = EXCLUDED.data",
+            &[
+                &pool_stats.pool,
+                &pool_stats.start_time,
+                &serde_json::to_value(pool_stats)?,
+            ],
+        )
+        .await
+    }
+
+    async fn fetch_pool_stats_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PoolStats>, StoreError> {
+        let sort_by = if PoolStats::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+
+        let rows = self
+            .select_values(
+                "SELECT data FROM pool_stats
+                WHERE pool = $1 AND start_time >= $2
+                    AND (data->>'endTime')::DOUBLE PRECISION <= $3",
+                &[
+                    &query.pool.clone().unwrap_or_default(),
+                    &query.from,
+                    &query.to,
+                ],
+            )
+            .await?;
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl MemberPositionsStore for PostgresRepository<MemberPosition> {
+    async fn upsert_member_position(
+        &self,
+        member_position: &MemberPosition,
+    ) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO member_positions (address, pool, start_time, data) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (address, pool, start_time) DO UPDATE SET data = EXCLUDED.data",
+            &[
+                &member_position.address,
+                &member_position.pool,
+                &member_position.start_time,
+                &serde_json::to_value(member_position)?,
+            ],
+        )
+        .await
+    }
+
+    async fn fetch_member_positions_data(
+        &self,
+        address: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<MemberPosition>, StoreError> {
+        let rows = self
+            .select_values(
+                "SELECT data FROM member_positions
+                WHERE address = $1 AND ($2::TEXT IS NULL OR pool = $2) AND start_time >= $3
+                    AND (data->>'endTime')::DOUBLE PRECISION <= $4",
+                &[&address, &query.pool, &query.from, &query.to],
+            )
+            .await?;
+
+        parse(member_position_buckets(rows, query))
+    }
+}
+
+// Actions and the cursors of their ingestion runs, with the keys of each action in `action_keys`.
+pub struct PostgresActionsRepository {
+    actions: PostgresRepository<Action>,
+    sync_states: PostgresRepository<ActionsSyncState>,
+}
+
+impl PostgresActionsRepository {
+    pub fn new(db: Arc<PostgresDatabase>) -> Self {
+        PostgresActionsRepository {
+            actions: PostgresRepository::new(db.clone()),
+            sync_states: PostgresRepository::new(db),
+        }
+    }
+}
+
+#[async_trait]
+impl ActionsStore for PostgresActionsRepository {
+    async fn upsert_action(&self, action: &Action) -> Result<(), StoreError> {
+        let mut writer = self.actions.db.writer.lock().await;
+        let transaction = writer.transaction().await?;
+
+        // The inbound transactions are compared as stored, in the text of the JSONB.
+        let id: i64 = transaction
+            .query_one(
+                "INSERT INTO actions (date, type, in_txs, data)
+                VALUES ($1, $2, ($3::JSONB->'in')::TEXT, $3)
+                ON CONFLICT (date, type, in_txs) DO UPDATE SET data = EXCLUDED.data
+                RETURNING id",
+                &[
+                    &action.date,
+                    &action.action_type,
+                    &serde_json::to_value(action)?,
+                ],
+            )
+            .await?
+            .get(0);
+
+        transaction
+            .execute("DELETE FROM action_keys WHERE action_id = $1", &[&id])
+            .await?;
+
+        for (kind, value) in action_keys(action) {
+            transaction
+                .execute(
+                    "INSERT INTO action_keys (action_id, kind, value, date) VALUES ($1, $2, $3, $4)",
+                    &[&id, &kind, &value, &action.date],
+                )
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn fetch_actions(
+        &self,
+        filter: &ActionsFilter,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<Action>), StoreError> {
+        let (from, to) = filter.date_bounds();
+        let condition = "($1::TEXT IS NULL OR id IN (
+                SELECT action_id FROM action_keys WHERE kind = 'address' AND value = $1))
+            AND ($2::TEXT IS NULL OR id IN (
+                SELECT action_id FROM action_keys WHERE kind = 'pool' AND value = $2))
+            AND ($3::TEXT IS NULL OR type = $3)
+            AND ($4::BIGINT IS NULL OR date >= $4)
+            AND ($5::BIGINT IS NULL OR date <= $5)";
+        let params: [&(dyn ToSql + Sync); 5] = [
+            &filter.address,
+            &filter.pool,
+            &filter.action_type,
+            &from,
+            &to,
+        ];
+
+        let total = self
+            .actions
+            .count(
+                &format!("SELECT count(*) FROM actions WHERE {}", condition),
+                &params,
+            )
+            .await?;
+
+        let mut all_params = params.to_vec();
+        let offset = offset(count, page);
+        all_params.extend_from_slice(&[&count, &offset]);
+
+        let actions = self
+            .actions
+            .select(
+                &format!(
+                    "SELECT data FROM actions WHERE {} ORDER BY date DESC LIMIT $6 OFFSET $7",
+                    condition
+                ),
+                &all_params,
+            )
+            .await?;
+
+        Ok((total, actions))
+    }
+
+    async fn fetch_actions_by_tx_id(&self, tx_id: &str) -> Result<Vec<Action>, StoreError> {
+        self.actions
+            .select(
+                "SELECT data FROM actions WHERE id IN (
+                    SELECT action_id FROM action_keys WHERE kind = 'txID' AND value = $1
+                )
+                ORDER BY date DESC",
+                &[&tx_id],
+            )
+            .await
+    }
+
+    async fn get_sync_state(&self, id: &str) -> Result<Option<ActionsSyncState>, StoreError> {
+        Ok(self
+            .sync_states
+            .select("SELECT data FROM actions_sync_state WHERE id = $1", &[&id])
+            .await?
+            .pop())
+    }
+
+    async fn save_sync_state(&self, state: &ActionsSyncState) -> Result<(), StoreError> {
+        self.sync_states
+            .execute(
+                "INSERT INTO actions_sync_state (id, data) VALUES ($1, $2)
+                ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data",
+                &[&state.id, &serde_json::to_value(state)?],
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for PostgresRepository<QuarantineRecord> {
+    async fn insert_quarantine_record(&self, record: &QuarantineRecord) -> Result<(), StoreError> {
+        let (id, data) = with_id(record)?;
+
+        self.execute(
+            "INSERT INTO quarantine (id, dataset, replayed_at, quarantined_at, data)
+            VALUES ($1, $2, $3, $4, $5)",
+            &[
+                &id,
+                &record.dataset,
+                &record.replayed_at,
+                &record.quarantined_at,
+                &data,
+            ],
+        )
+        .await
+    }
+
+    async fn fetch_quarantine_records(
+        &self,
+        dataset: Option<String>,
+        include_replayed: bool,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<QuarantineRecord>), StoreError> {
+        let condition = "($1::TEXT IS NULL OR dataset = $1) AND ($2 OR replayed_at IS NULL)";
+
+        let total = self
+            .count(
+                &format!("SELECT count(*) FROM quarantine WHERE {}", condition),
+                &[&dataset, &include_replayed],
+            )
+            .await?;
+
+        let records = self
+            .select(
+                &format!(
+                    "SELECT data FROM quarantine WHERE {}
+                    ORDER BY quarantined_at DESC LIMIT $3 OFFSET $4",
+                    condition
+                ),
+                &[&dataset, &include_replayed, &count, &offset(count, page)],
+            )
+            .await?;
+
+        Ok((total, records))
+    }
+
+    async fn fetch_pending_quarantine_records(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<QuarantineRecord>, StoreError> {
+        self.select(
+            "SELECT data FROM quarantine
+            WHERE ($1::TEXT IS NULL OR dataset = $1) AND replayed_at IS NULL
+            ORDER BY quarantined_at",
+            &[&dataset],
+        )
+        .await
+    }
+
+    async fn fetch_quarantine_record(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<QuarantineRecord>, StoreError> {
+        Ok(self
+            .select("SELECT data FROM quarantine WHERE id = $1", &[&id.to_hex()])
+            .await?
+            .pop())
+    }
+
+    async fn mark_replayed(&self, id: ObjectId, replayed_at: f64) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE quarantine SET replayed_at = $2,
+                data = jsonb_set(data, '{replayedAt}', to_jsonb($2::DOUBLE PRECISION))
+            WHERE id = $1",
+            &[&id.to_hex(), &replayed_at],
+        )
+        .await
+    }
+
+    async fn update_error(&self, id: ObjectId, error: &str) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE quarantine SET data = jsonb_set(data, '{error}', to_jsonb($2::TEXT))
+            WHERE id = $1",
+            &[&id.to_hex(), &error],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl SchemaDriftStore for PostgresRepository<SchemaDriftField> {
+    async fn record_field(
+        &self,
+        dataset: &str,
+        field: &str,
+        source_url: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let data = serde_json::to_value(SchemaDriftField {
+            dataset: dataset.to_string(),
+            field: field.to_string(),
+            first_seen_at: seen_at,
+            last_seen_at: seen_at,
+            first_source_url: source_url.to_string(),
+        })?;
+
+        self.execute(
+            "INSERT INTO schema_drift (dataset, field, first_seen_at, data) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (dataset, field) DO UPDATE SET
+                data = jsonb_set(schema_drift.data, '{lastSeenAt}', to_jsonb($3::DOUBLE PRECISION))",
+            &[&dataset, &field, &seen_at, &data],
+        )
+        .await
+    }
+
+    async fn fetch_schema_drift(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<SchemaDriftField>, StoreError> {
+        self.select(
+            "SELECT data FROM schema_drift WHERE ($1::TEXT IS NULL OR dataset = $1)
+            ORDER BY first_seen_at DESC",
+            &[&dataset],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl ValidationMetricsStore for PostgresRepository<ValidationMetric> {
+    async fn record_violation(
+        &self,
+        dataset: &str,
+        rule: &str,
+        rejected: bool,
+        message: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let counter = if rejected { "rejected" } else { "warned" };
+        let data = serde_json::to_value(ValidationMetric {
+            dataset: dataset.to_string(),
+            rule: rule.to_string(),
+            warned: i64::from(!rejected),
+            rejected: i64::from(rejected),
+            last_violation_at: seen_at,
+            last_violation: message.to_string(),
+        })?;
+
+        self.execute(
+            "INSERT INTO validation_metrics (dataset, rule, data) VALUES ($1, $2, $3)
+            ON CONFLICT (dataset, rule) DO UPDATE SET data = validation_metrics.data
+                || jsonb_build_object(
+                    $4::TEXT, (validation_metrics.data->>$4)::BIGINT + 1,
+                    'lastViolationAt', $5::DOUBLE PRECISION,
+                    'lastViolation', $6::TEXT
+                )",
+            &[&dataset, &rule, &data, &counter, &seen_at, &message],
+        )
+        .await
+    }
+
+    async fn fetch_validation_metrics(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<ValidationMetric>, StoreError> {
+        self.select(
+            "SELECT data FROM validation_metrics WHERE ($1::TEXT IS NULL OR dataset = $1)
+            ORDER BY dataset, rule",
+            &[&dataset],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl CoverageStore for PostgresRepository<CoverageGap> {
+    async fn insert_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        let (_, data) = with_id(gap)?;
+
+        self.execute(
+            "INSERT INTO coverage_gaps (dataset, pool, start_time, status, data)
+            VALUES ($1, $2, $3, $4, $5)",
+            &[
+                &gap.dataset,
+                &gap.pool.clone().unwrap_or_default(),
+                &gap.start_time,
+                &gap.status.to_str(),
+                &data,
+            ],
+        )
+        .await
+    }
+
+    async fn fetch_gaps(
+        &self,
+        dataset: Option<String>,
+        status: Option<GapStatus>,
+    ) -> Result<Vec<CoverageGap>, StoreError> {
+        self.select(
+            "SELECT data FROM coverage_gaps
+            WHERE ($1::TEXT IS NULL OR dataset = $1) AND ($2::TEXT IS NULL OR status = $2)
+            ORDER BY dataset, pool, start_time",
+            &[&dataset, &status.map(|status| status.to_str())],
+        )
+        .await
+    }
+
+    async fn update_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE coverage_gaps SET status = $4, data = data || jsonb_build_object(
+                'endTime', $5::DOUBLE PRECISION,
+                'missingIntervals', $6::BIGINT,
+                'status', $4::TEXT,
+                'attempts', $7::BIGINT,
+                'updatedAt', $8::DOUBLE PRECISION
+            )
+            WHERE dataset = $1 AND pool = $2 AND start_time = $3",
+            &[
+                &gap.dataset,
+                &gap.pool.clone().unwrap_or_default(),
+                &gap.start_time,
+                &gap.status.to_str(),
+                &gap.end_time,
+                &gap.missing_intervals,
+                &gap.attempts,
+                &gap.updated_at,
+            ],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl UpstreamDisagreementStore for PostgresRepository<UpstreamDisagreement> {
+    async fn insert_disagreements(
+        &self,
+        disagreements: &[UpstreamDisagreement],
+    ) -> Result<(), StoreError> {
+        for disagreement in disagreements {
+            let (id, data) = with_id(disagreement)?;
+
+            self.execute(
+                "INSERT INTO upstream_disagreements (id, dataset, detected_at, data)
+                VALUES ($1, $2, $3, $4)",
+                &[&id, &disagreement.dataset, &disagreement.detected_at, &data],
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_disagreements(
+        &self,
+        dataset: Option<String>,
+        count: i64,
+        page: i64,
+    ) -> Result<Vec<UpstreamDisagreement>, StoreError> {
+        self.select(
+            "SELECT data FROM upstream_disagreements WHERE ($1::TEXT IS NULL OR dataset = $1)
+            ORDER BY detected_at DESC LIMIT $2 OFFSET $3",
+            &[&dataset, &count, &offset(count, page)],
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl MigrationStore for PostgresRepository<AppliedMigration> {
+    async fn fetch_applied(&self) -> Result<Vec<AppliedMigration>, StoreError> {
+        self.select("SELECT data FROM _migrations ORDER BY id", &[])
+            .await
+    }
+
+    async fn record_applied(&self, migration: &AppliedMigration) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO _migrations (id, data) VALUES ($1, $2)",
+            &[&migration.id, &serde_json::to_value(migration)?],
+        )
+        .await
+    }
+}
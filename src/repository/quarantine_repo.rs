@@ -0,0 +1,142 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::FindOptions,
+    Collection,
+};
+
+use crate::{models::quarantine_model::QuarantineRecord, repository::history_store::StoreError};
+
+#[async_trait]
+pub trait QuarantineStore: Send + Sync {
+    async fn insert_quarantine_record(&self, record: &QuarantineRecord) -> Result<(), StoreError>;
+
+    // Newest first, with the number of records matching.
+    async fn fetch_quarantine_records(
+        &self,
+        dataset: Option<String>,
+        include_replayed: bool,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<QuarantineRecord>), StoreError>;
+
+    // Records still waiting for a replay, oldest first so pages are replayed in ingestion order.
+    async fn fetch_pending_quarantine_records(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<QuarantineRecord>, StoreError>;
+
+    async fn fetch_quarantine_record(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<QuarantineRecord>, StoreError>;
+
+    async fn mark_replayed(&self, id: ObjectId, replayed_at: f64) -> Result<(), StoreError>;
+
+    // A failed replay keeps the record pending with the error of the latest model.
+    async fn update_error(&self, id: ObjectId, error: &str) -> Result<(), StoreError>;
+}
+
+pub struct QuarantineRepository {
+    col: Collection<QuarantineRecord>,
+}
+
+impl QuarantineRepository {
+    pub async fn init(col: Collection<QuarantineRecord>) -> Result<Self, Box<dyn Error>> {
+        Ok(QuarantineRepository { col })
+    }
+
+    fn filter(dataset: Option<String>, include_replayed: bool) -> Document {
+        let mut filter = doc! {};
+
+        if let Some(dataset) = dataset {
+            filter.insert("dataset", dataset);
+        }
+        if !include_replayed {
+            filter.insert("replayedAt", mongodb::bson::Bson::Null);
+        }
+
+        filter
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for QuarantineRepository {
+    async fn insert_quarantine_record(&self, record: &QuarantineRecord) -> Result<(), StoreError> {
+        self.col.insert_one(record, None).await?;
+
+        Ok(())
+    }
+
+    async fn fetch_quarantine_records(
+        &self,
+        dataset: Option<String>,
+        include_replayed: bool,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<QuarantineRecord>), StoreError> {
+        let filter = Self::filter(dataset, include_replayed);
+
+        let total = self.col.count_documents(filter.clone(), None).await?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "quarantinedAt": -1 })
+            .skip(((page - 1).max(0) * count) as u64)
+            .limit(count)
+            .build();
+
+        let records = self.col.find(filter, options).await?.try_collect().await?;
+
+        Ok((total, records))
+    }
+
+    async fn fetch_pending_quarantine_records(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<QuarantineRecord>, StoreError> {
+        let options = FindOptions::builder()
+            .sort(doc! { "quarantinedAt": 1 })
+            .build();
+
+        Ok(self
+            .col
+            .find(Self::filter(dataset, false), options)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn fetch_quarantine_record(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<QuarantineRecord>, StoreError> {
+        Ok(self.col.find_one(doc! { "_id": id }, None).await?)
+    }
+
+    async fn mark_replayed(&self, id: ObjectId, replayed_at: f64) -> Result<(), StoreError> {
+        self.col
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "replayedAt": replayed_at } },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_error(&self, id: ObjectId, error: &str) -> Result<(), StoreError> {
+        self.col
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "error": error } },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,24 @@
 use std::error::Error;
 
-use futures::TryStreamExt;
+use async_trait::async_trait;
 use mongodb::{
     bson::{doc, Document},
-    results::InsertOneResult,
     Collection,
 };
 
-use crate::{models::rune_pool_history_model::RunePoolHistory, utils::time_interval::TimeInterval};
+use crate::{
+    models::rune_pool_history_model::RunePoolHistory,
+    repository::{
+        bulk_repo::{bulk_upsert, BulkUpsertResult},
+        coverage_repo::{
+            count_missing_granularity, delete_intervals, fetch_first_incomplete, fetch_history,
+            fetch_intervals, fetch_latest, fetch_start_times, granularity_filter,
+            mark_missing_granularity,
+        },
+        history_store::{HistoryQuery, HistoryRecord, HistoryStore, StoreError},
+    },
+    utils::time_interval::TimeInterval,
+};
 
 pub struct RunePoolHistoryRepository {
     col: Collection<RunePoolHistory>,
@@ -17,92 +28,113 @@ impl RunePoolHistoryRepository {
     pub async fn init(col: Collection<RunePoolHistory>) -> Result<Self, Box<dyn Error>> {
         Ok(RunePoolHistoryRepository { col })
     }
-    pub async fn insert_rune_pool_history(
-        &self,
-        rune_pool_history: &RunePoolHistory,
-    ) -> Result<InsertOneResult, Box<dyn Error>> {
-        let insert_details = self
-            .col
-            .insert_one(rune_pool_history, None)
-            .await
-            .expect("Failed to insert Rune Pool history.");
-
-        Ok(insert_details)
+}
+
+impl HistoryRecord for RunePoolHistory {
+    fn is_sortable(field: &str) -> bool {
+        RunePoolHistory::has_field(field)
     }
+}
 
-    pub async fn fetch_rune_pool_history_data(
+#[async_trait]
+impl HistoryStore<RunePoolHistory> for RunePoolHistoryRepository {
+    async fn upsert(
         &self,
-        from: f64,
-        to: f64,
-        count: f64,
-        interval: TimeInterval,
-        page: i64,
-        sort_by: String,
-    ) -> Result<Vec<RunePoolHistory>, mongodb::error::Error> {
-        let filter = doc! {
-            "startTime": { "$gte": from },
-            "endTime":{"$lte":to},
-        };
+        rune_pool_histories: &[RunePoolHistory],
+    ) -> Result<BulkUpsertResult, StoreError> {
+        let rows: Vec<(Document, &RunePoolHistory)> = rune_pool_histories
+            .iter()
+            .map(|rune_pool_history| {
+                let filter = doc! {
+                    "startTime": rune_pool_history.start_time,
+                    "granularity": granularity_filter(&rune_pool_history.granularity),
+                };
 
-        let mut sort_by = sort_by;
+                (filter, rune_pool_history)
+            })
+            .collect();
 
-        if !RunePoolHistory::has_field(&sort_by) {
-            sort_by = String::from("startTime");
-        }
+        Ok(bulk_upsert(&self.col, &rows).await?)
+    }
 
-        let sort_stage = doc! { &sort_by: -1 };
+    async fn fetch_history(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<RunePoolHistory>, StoreError> {
+        fetch_history(&self.col, doc! {}, query).await
+    }
 
-        let skip = (page - 1).max(0) * (count as i64);
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        Ok(fetch_start_times(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let interval_seconds = interval.as_seconds();
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<RunePoolHistory>, StoreError> {
+        Ok(fetch_intervals(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let pipeline = vec![
-            doc! { "$match": filter },
-            doc! { "$sort": { "startTime": 1 } },
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(delete_intervals(
+            &self.col,
             doc! {
-                "$group": {
-                    "_id": {
-                        "$toDate": {
-                            "$subtract": [
-                                "$startTime",
-                                { "$mod": ["$startTime", interval_seconds] }
-                            ]
-                        }
-                    },
-                    "count": { "$last": "$count" },
-                    "units": { "$last": "$units" },
-                    "startTime": { "$first": "$startTime" },
-                    "endTime": { "$last": "$endTime" }
-                }
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
             },
-            doc! { "$project": {
-                "_id": 0,
-                "startTime": 1,
-                "endTime": 1,
-                "count": 1,
-                "units": 1
-            }},
-            doc! { "$sort": { "startTime": 1 } },
-            doc! { "$skip": skip },
-            doc! { "$limit": count as i64 },
-            doc! { "$sort": sort_stage },
-        ];
-
-        let cursor = self.col.aggregate(pipeline, None).await?;
-
-        let results: Vec<RunePoolHistory> = cursor
-            .try_collect::<Vec<Document>>()
-            .await?
-            .into_iter()
-            .map(|doc| {
-                // Convert each Document to DepthHistory
-                mongodb::bson::from_document(doc).map_err(|e| {
-                    // Convert bson::de::Error to mongodb::error::Error
-                    mongodb::error::Error::from(e)
-                })
-            })
-            .collect::<Result<Vec<RunePoolHistory>, _>>()?;
+        )
+        .await?)
+    }
+
+    async fn fetch_latest(
+        &self,
+        _pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<RunePoolHistory>, StoreError> {
+        Ok(fetch_latest(&self.col, doc! {}, end_time).await?)
+    }
+
+    async fn fetch_first_incomplete(
+        &self,
+        _pool: Option<&str>,
+    ) -> Result<Option<RunePoolHistory>, StoreError> {
+        Ok(fetch_first_incomplete(&self.col, doc! {}).await?)
+    }
+
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_granularity(&self.col).await?)
+    }
 
-        Ok(results)
+    async fn mark_missing_granularity(&self, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_granularity(&self.col, limit).await?)
     }
 }
@@ -0,0 +1,156 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use mongodb::{
+    bson::{doc, Document},
+    Collection,
+};
+
+use crate::{
+    models::savers_history_model::SaversHistory,
+    repository::{
+        bulk_repo::{bulk_upsert, BulkUpsertResult},
+        coverage_repo::{
+            count_missing_granularity, delete_intervals, fetch_first_incomplete, fetch_history,
+            fetch_intervals, fetch_latest, fetch_start_times, granularity_filter,
+            mark_missing_granularity,
+        },
+        history_store::{HistoryQuery, HistoryRecord, HistoryStore, StoreError},
+    },
+    utils::time_interval::TimeInterval,
+};
+
+pub struct SaversHistoryRepository {
+    col: Collection<SaversHistory>,
+}
+
+impl SaversHistoryRepository {
+    pub async fn init(col: Collection<SaversHistory>) -> Result<Self, Box<dyn Error>> {
+        Ok(SaversHistoryRepository { col })
+    }
+}
+
+impl HistoryRecord for SaversHistory {
+    fn is_sortable(field: &str) -> bool {
+        SaversHistory::has_field(field)
+    }
+}
+
+#[async_trait]
+impl HistoryStore<SaversHistory> for SaversHistoryRepository {
+    async fn upsert(
+        &self,
+        savers_histories: &[SaversHistory],
+    ) -> Result<BulkUpsertResult, StoreError> {
+        let rows: Vec<(Document, &SaversHistory)> = savers_histories
+            .iter()
+            .map(|savers_history| {
+                let filter = doc! {
+                    "pool": &savers_history.pool,
+                    "startTime": savers_history.start_time,
+                    "granularity": granularity_filter(&savers_history.granularity),
+                };
+
+                (filter, savers_history)
+            })
+            .collect();
+
+        Ok(bulk_upsert(&self.col, &rows).await?)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<SaversHistory>, StoreError> {
+        fetch_history(
+            &self.col,
+            doc! { "pool": query.pool.clone().unwrap_or_default() },
+            query,
+        )
+        .await
+    }
+
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        Ok(fetch_start_times(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
+
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<SaversHistory>, StoreError> {
+        Ok(fetch_intervals(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
+
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(delete_intervals(
+            &self.col,
+            doc! {
+                "pool": pool.unwrap_or_default(),
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
+
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<SaversHistory>, StoreError> {
+        let filter = match pool {
+            Some(pool) => doc! { "pool": pool },
+            None => doc! {},
+        };
+
+        Ok(fetch_latest(&self.col, filter, end_time).await?)
+    }
+
+    async fn fetch_first_incomplete(
+        &self,
+        pool: Option<&str>,
+    ) -> Result<Option<SaversHistory>, StoreError> {
+        let filter = match pool {
+            Some(pool) => doc! { "pool": pool },
+            None => doc! {},
+        };
+
+        Ok(fetch_first_incomplete(&self.col, filter).await?)
+    }
+
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_granularity(&self.col).await?)
+    }
+
+    async fn mark_missing_granularity(&self, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_granularity(&self.col, limit).await?)
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{FindOptions, UpdateOptions},
+    Collection,
+};
+
+use crate::{models::schema_drift_model::SchemaDriftField, repository::history_store::StoreError};
+
+#[async_trait]
+pub trait SchemaDriftStore: Send + Sync {
+    // Keeps the first time and source a field was seen, only the last seen time moves forward.
+    async fn record_field(
+        &self,
+        dataset: &str,
+        field: &str,
+        source_url: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError>;
+
+    // Newest fields first.
+    async fn fetch_schema_drift(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<SchemaDriftField>, StoreError>;
+}
+
+pub struct SchemaDriftRepository {
+    col: Collection<SchemaDriftField>,
+}
+
+impl SchemaDriftRepository {
+    pub async fn init(col: Collection<SchemaDriftField>) -> Result<Self, Box<dyn Error>> {
+        Ok(SchemaDriftRepository { col })
+    }
+}
+
+#[async_trait]
+impl SchemaDriftStore for SchemaDriftRepository {
+    async fn record_field(
+        &self,
+        dataset: &str,
+        field: &str,
+        source_url: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let options = UpdateOptions::builder().upsert(true).build();
+
+        self.col
+            .update_one(
+                doc! { "dataset": dataset, "field": field },
+                doc! {
+                    "$setOnInsert": { "firstSeenAt": seen_at, "firstSourceUrl": source_url },
+                    "$set": { "lastSeenAt": seen_at },
+                },
+                options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_schema_drift(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<SchemaDriftField>, StoreError> {
+        let filter = match dataset {
+            Some(dataset) => doc! { "dataset": dataset },
+            None => doc! {},
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! { "firstSeenAt": -1 })
+            .build();
+
+        Ok(self.col.find(filter, options).await?.try_collect().await?)
+    }
+}
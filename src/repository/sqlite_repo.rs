@@ -0,0 +1,992 @@
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    models::{
+        actions_model::{Action, ActionsSyncState},
+        coverage_model::{CoverageGap, GapStatus},
+        member_positions_model::MemberPosition,
+        migration_model::AppliedMigration,
+        network_history_model::NetworkHistory,
+        pool_stats_model::PoolStats,
+        quarantine_model::QuarantineRecord,
+        schema_drift_model::SchemaDriftField,
+        upstream_disagreement_model::UpstreamDisagreement,
+        validation_model::ValidationMetric,
+    },
+    repository::{
+        actions_repo::{action_keys, ActionsFilter, ActionsStore},
+        bulk_repo::{BulkUpsertResult, BulkWriteFailure},
+        coverage_repo::CoverageStore,
+        history_store::{
+            choose_granularity, rollup_page, HistoryQuery, HistoryRecord, HistoryStore, StoreError,
+        },
+        member_positions_repo::MemberPositionsStore,
+        memory_repo::{member_position_buckets, number, parse, set, snapshot_rollup},
+        migration_repo::MigrationStore,
+        network_history_repo::NetworkHistoryStore,
+        pool_stats_repo::PoolStatsStore,
+        quarantine_repo::QuarantineStore,
+        schema_drift_repo::SchemaDriftStore,
+        upstream_disagreement_repo::UpstreamDisagreementStore,
+        validation_metrics_repo::ValidationMetricsStore,
+    },
+    utils::time_interval::TimeInterval,
+};
+
+// Schema changes in the order they are applied, each one once per file.
+const MIGRATIONS: [(&str, &str); 3] = [
+    (
+        "0001_history_tables",
+        include_str!("../../migrations/sqlite/0001_history_tables.sql"),
+    ),
+    (
+        "0002_pool_history_and_documents",
+        include_str!("../../migrations/sqlite/0002_pool_history_and_documents.sql"),
+    ),
+    (
+        "0003_collection_tables",
+        include_str!("../../migrations/sqlite/0003_collection_tables.sql"),
+    ),
+];
+
+// The file all networks are stored in. Queries are short and local, they run on the calling task
+// while holding the connection.
+pub type SqliteDatabase = Arc<Mutex<Connection>>;
+
+// Opens the file at `path`, creating it and applying the pending migrations.
+pub fn open(path: &str) -> Result<SqliteDatabase, StoreError> {
+    let mut connection = Connection::open(Path::new(path))?;
+
+    connection.execute_batch(
+        "PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            id TEXT PRIMARY KEY,
+            applied_at REAL NOT NULL
+        );",
+    )?;
+
+    for (id, sql) in MIGRATIONS {
+        let transaction = connection.transaction()?;
+
+        let applied = transaction
+            .query_row(
+                "SELECT id FROM schema_migrations WHERE id = ?1",
+                [id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+
+        if applied {
+            continue;
+        }
+
+        transaction.execute_batch(sql)?;
+        transaction.execute(
+            "INSERT INTO schema_migrations (id, applied_at) VALUES (?1, ?2)",
+            params![id, Utc::now().timestamp() as f64],
+        )?;
+        transaction.commit()?;
+
+        println!("Applied sqlite migration {} to {}", id, path);
+    }
+
+    Ok(Arc::new(Mutex::new(connection)))
+}
+
+// A history dataset of one network in its table of `0001_history_tables` or
+// `0002_pool_history_and_documents`.
+pub struct SqliteHistoryRepository<T> {
+    db: SqliteDatabase,
+    network: String,
+    table: &'static str,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T: HistoryRecord> SqliteHistoryRepository<T> {
+    pub fn new(db: SqliteDatabase, network: &str, table: &'static str) -> Self {
+        SqliteHistoryRepository {
+            db,
+            network: network.to_string(),
+            table,
+            model: PhantomData,
+        }
+    }
+
+    fn upsert_row(&self, connection: &Connection, data: &Value) -> Result<bool, StoreError> {
+        let pool = data.get("pool").and_then(Value::as_str).unwrap_or_default();
+        let granularity = data
+            .get("granularity")
+            .and_then(Value::as_str)
+            .unwrap_or(TimeInterval::Hour.to_str());
+        let start_time = number(data, "startTime");
+
+        let exists = connection
+            .query_row(
+                &format!(
+                    "SELECT 1 FROM {} WHERE network = ?1 AND pool = ?2 AND granularity = ?3
+                        AND start_time = ?4",
+                    self.table
+                ),
+                params![self.network, pool, granularity, start_time],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+
+        connection.execute(
+            &format!(
+                "INSERT INTO {} (network, pool, granularity, start_time, end_time, is_complete, data)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (network, pool, granularity, start_time) DO UPDATE SET
+                    end_time = excluded.end_time,
+                    is_complete = excluded.is_complete,
+                    data = excluded.data",
+                self.table
+            ),
+            params![
+                self.network,
+                pool,
+                granularity,
+                start_time,
+                number(data, "endTime"),
+                data.get("isComplete")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+                data.to_string(),
+            ],
+        )?;
+
+        Ok(!exists)
+    }
+
+    // Stored rows of the network matching `condition`, its parameters start at `?3`.
+    fn fetch_rows(
+        &self,
+        pool: Option<&str>,
+        condition: &str,
+        order: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<Value>, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT data FROM {} WHERE network = ?1 AND (?2 IS NULL OR pool = ?2) AND {}
+            ORDER BY {}",
+            self.table, condition, order
+        ))?;
+
+        let mut all_params: Vec<&dyn rusqlite::ToSql> = vec![&self.network, &pool];
+        all_params.extend_from_slice(params);
+
+        let rows = statement
+            .query_map(all_params.as_slice(), |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        rows.iter()
+            .map(|row| serde_json::from_str(row).map_err(StoreError::from))
+            .collect()
+    }
+
+    fn fetch_one(
+        &self,
+        pool: Option<&str>,
+        condition: &str,
+        order: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> Result<Option<T>, StoreError> {
+        let rows = self.fetch_rows(
+            pool,
+            &format!("granularity = 'hour' AND {}", condition),
+            &format!("{} LIMIT 1", order),
+            params,
+        )?;
+
+        match rows.into_iter().next() {
+            Some(row) => Ok(Some(serde_json::from_value(row)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: HistoryRecord> HistoryStore<T> for SqliteHistoryRepository<T> {
+    async fn upsert(&self, rows: &[T]) -> Result<BulkUpsertResult, StoreError> {
+        let mut result = BulkUpsertResult::default();
+
+        let mut connection = self.db.lock().unwrap();
+        let transaction = connection.transaction()?;
+
+        for (index, row) in rows.iter().enumerate() {
+            let data = serde_json::to_value(row)?;
+
+            match self.upsert_row(&transaction, &data) {
+                Ok(true) => result.upserted += 1,
+                Ok(false) => result.matched += 1,
+                Err(e) => result.failed.push(BulkWriteFailure {
+                    index,
+                    filter: doc! {
+                        "startTime": number(&data, "startTime"),
+                        "granularity": data.get("granularity").and_then(Value::as_str),
+                    },
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        transaction.commit()?;
+
+        Ok(result)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<T>, StoreError> {
+        let bounds: Vec<(String, f64, f64)> = {
+            let connection = self.db.lock().unwrap();
+
+            let mut statement = connection.prepare(&format!(
+                "SELECT granularity, min(start_time), max(end_time) FROM {}
+                WHERE network = ?1 AND (?2 IS NULL OR pool = ?2)
+                    AND start_time >= ?3 AND end_time <= ?4
+                GROUP BY granularity",
+                self.table
+            ))?;
+
+            let bounds = statement
+                .query_map(
+                    params![self.network, query.pool, query.from, query.to],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )?
+                .collect::<Result<_, _>>()?;
+
+            bounds
+        };
+
+        let granularity = choose_granularity(query.interval, query.from, query.to, |granularity| {
+            bounds
+                .iter()
+                .find(|(name, _, _)| name == granularity.to_str())
+                .map(|(_, first_start_time, last_end_time)| (*first_start_time, *last_end_time))
+        });
+
+        let rows = self.fetch_rows(
+            query.pool.as_deref(),
+            "granularity = ?3 AND start_time >= ?4 AND end_time <= ?5",
+            "start_time",
+            &[&granularity.to_str(), &query.from, &query.to],
+        )?;
+
+        rollup_page(rows, granularity, query)
+    }
+
+    async fn fetch_start_times(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT start_time FROM {}
+            WHERE network = ?1 AND (?2 IS NULL OR pool = ?2) AND granularity = ?3
+                AND start_time >= ?4 AND start_time < ?5
+            ORDER BY start_time",
+            self.table
+        ))?;
+
+        let start_times = statement
+            .query_map(
+                params![self.network, pool, granularity.to_str(), from, to],
+                |row| row.get(0),
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(start_times)
+    }
+
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<T>, StoreError> {
+        let rows = self.fetch_rows(
+            pool,
+            "granularity = ?3 AND start_time >= ?4 AND start_time < ?5",
+            "start_time",
+            &[&granularity.to_str(), &from, &to],
+        )?;
+
+        parse(rows)
+    }
+
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let deleted = connection.execute(
+            &format!(
+                "DELETE FROM {}
+                WHERE network = ?1 AND (?2 IS NULL OR pool = ?2) AND granularity = ?3
+                    AND start_time >= ?4 AND start_time < ?5",
+                self.table
+            ),
+            params![self.network, pool, granularity.to_str(), from, to],
+        )?;
+
+        Ok(deleted as u64)
+    }
+
+    async fn fetch_latest(
+        &self,
+        pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<T>, StoreError> {
+        self.fetch_one(pool, "end_time <= ?3", "end_time DESC", &[&end_time])
+    }
+
+    async fn fetch_first_incomplete(&self, pool: Option<&str>) -> Result<Option<T>, StoreError> {
+        self.fetch_one(pool, "is_complete = 0", "start_time", &[])
+    }
+
+    async fn count_missing_pool(&self) -> Result<u64, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let count: i64 = connection.query_row(
+            &format!(
+                "SELECT count(*) FROM {} WHERE network = ?1 AND pool = ''",
+                self.table
+            ),
+            [&self.network],
+            |row| row.get(0),
+        )?;
+
+        Ok(count as u64)
+    }
+
+    async fn mark_missing_pool(&self, pool: &str, limit: i64) -> Result<u64, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let marked = connection.execute(
+            &format!(
+                "UPDATE {0} SET pool = ?2, data = json_set(data, '$.pool', ?2)
+                WHERE rowid IN (
+                    SELECT rowid FROM {0} WHERE network = ?1 AND pool = '' LIMIT ?3
+                )",
+                self.table
+            ),
+            params![self.network, pool, limit],
+        )?;
+
+        Ok(marked as u64)
+    }
+}
+
+// A collection of one network that isn't a history dataset, in its table of
+// `0003_collection_tables`. The model is kept whole in `data`, the fields it is looked up and sorted
+// by are copied out to columns.
+pub struct SqliteRepository<T> {
+    db: SqliteDatabase,
+    network: String,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> SqliteRepository<T> {
+    pub fn new(db: SqliteDatabase, network: &str) -> Self {
+        SqliteRepository {
+            db,
+            network: network.to_string(),
+            model: PhantomData,
+        }
+    }
+
+    // The network goes first, the parameters of `sql` start at `?2`.
+    fn with_network<'a>(&'a self, params: &[&'a dyn ToSql]) -> Vec<&'a dyn ToSql> {
+        let mut all_params: Vec<&dyn ToSql> = vec![&self.network];
+        all_params.extend_from_slice(params);
+
+        all_params
+    }
+
+    // The `data` of the rows `sql` selects.
+    fn select_values(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Vec<Value>, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let mut statement = connection.prepare(sql)?;
+
+        let rows = statement
+            .query_map(self.with_network(params).as_slice(), |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        rows.iter()
+            .map(|row| serde_json::from_str(row).map_err(StoreError::from))
+            .collect()
+    }
+
+    fn select(&self, sql: &str, params: &[&dyn ToSql]) -> Result<Vec<T>, StoreError> {
+        parse(self.select_values(sql, params)?)
+    }
+
+    fn count(&self, sql: &str, params: &[&dyn ToSql]) -> Result<u64, StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        let count: i64 =
+            connection.query_row(sql, self.with_network(params).as_slice(), |row| row.get(0))?;
+
+        Ok(count as u64)
+    }
+
+    fn execute(&self, sql: &str, params: &[&dyn ToSql]) -> Result<(), StoreError> {
+        let connection = self.db.lock().unwrap();
+
+        connection.execute(sql, self.with_network(params).as_slice())?;
+
+        Ok(())
+    }
+}
+
+// Rows skipped before a page.
+fn offset(count: i64, page: i64) -> i64 {
+    (page - 1).max(0) * count
+}
+
+// `row` with a new `_id`, the key of the collections Mongo gives the ids of.
+fn with_id<T: Serialize>(row: &T) -> Result<(String, String), StoreError> {
+    let id = ObjectId::new();
+    let mut data = serde_json::to_value(row)?;
+    set(&mut data, "_id", serde_json::to_value(id)?);
+
+    Ok((id.to_hex(), data.to_string()))
+}
+
+#[async_trait]
+impl NetworkHistoryStore for SqliteRepository<NetworkHistory> {
+    async fn upsert_network_history(
+        &self,
+        network_history: &NetworkHistory,
+    ) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO network_history (network, start_time, data) VALUES (?1, ?2, ?3)
+            ON CONFLICT (network, start_time) DO UPDATE SET data = excluded.data",
+            &[
+                &network_history.start_time,
+                &serde_json::to_string(network_history)?,
+            ],
+        )
+    }
+
+    async fn fetch_network_history_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<NetworkHistory>, StoreError> {
+        let sort_by = if NetworkHistory::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+
+        let rows = self.select_values(
+            "SELECT data FROM network_history
+            WHERE network = ?1 AND start_time >= ?2 AND json_extract(data, '$.endTime') <= ?3",
+            &[&query.from, &query.to],
+        )?;
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl PoolStatsStore for SqliteRepository<PoolStats> {
+    async fn upsert_pool_stats(&self, pool_stats: &PoolStats) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO pool_stats (network, pool, start_time, data) VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (network, pool, start_time) DO UPDATE SET data = excluded.data",
+            &[
+                &pool_stats.pool,
+                &pool_stats.start_time,
+                &serde_json::to_string(pool_stats)?,
+            ],
+        )
+    }
+
+    async fn fetch_pool_stats_data(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PoolStats>, StoreError> {
+        let sort_by = if PoolStats::has_field(&query.sort_by) {
+            query.sort_by.as_str()
+        } else {
+            "startTime"
+        };
+
+        let rows = self.select_values(
+            "SELECT data FROM pool_stats
+            WHERE network = ?1 AND pool = ?2 AND start_time >= ?3
+                AND json_extract(data, '$.endTime') <= ?4",
+            &[
+                &query.pool.clone().unwrap_or_default(),
+                &query.from,
+                &query.to,
+            ],
+        )?;
+
+        parse(snapshot_rollup(rows, query, sort_by))
+    }
+}
+
+#[async_trait]
+impl MemberPositionsStore for SqliteRepository<MemberPosition> {
+    async fn upsert_member_position(
+        &self,
+        member_position: &MemberPosition,
+    ) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO member_positions (network, address, pool, start_time, data)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (network, address, pool, start_time) DO UPDATE SET data = excluded.data",
+            &[
+                &member_position.address,
+                &member_position.pool,
+                &member_position.start_time,
+                &serde_json::to_string(member_position)?,
+            ],
+        )
+    }
+
+    async fn fetch_member_positions_data(
+        &self,
+        address: &str,
+        query: &HistoryQuery,
+    ) -> Result<Vec<MemberPosition>, StoreError> {
+        let rows = self.select_values(
+            "SELECT data FROM member_positions
+            WHERE network = ?1 AND address = ?2 AND (?3 IS NULL OR pool = ?3) AND start_time >= ?4
+                AND json_extract(data, '$.endTime') <= ?5",
+            &[&address, &query.pool, &query.from, &query.to],
+        )?;
+
+        parse(member_position_buckets(rows, query))
+    }
+}
+
+// Actions and the cursors of their ingestion runs of one network, with the keys of each action in
+// `action_keys`.
+pub struct SqliteActionsRepository {
+    actions: SqliteRepository<Action>,
+    sync_states: SqliteRepository<ActionsSyncState>,
+}
+
+impl SqliteActionsRepository {
+    pub fn new(db: SqliteDatabase, network: &str) -> Self {
+        SqliteActionsRepository {
+            actions: SqliteRepository::new(db.clone(), network),
+            sync_states: SqliteRepository::new(db, network),
+        }
+    }
+}
+
+#[async_trait]
+impl ActionsStore for SqliteActionsRepository {
+    async fn upsert_action(&self, action: &Action) -> Result<(), StoreError> {
+        let network = &self.actions.network;
+        let mut connection = self.actions.db.lock().unwrap();
+        let transaction = connection.transaction()?;
+
+        // The inbound transactions are compared as stored, in the text of the JSON.
+        let id: i64 = transaction.query_row(
+            "INSERT INTO actions (network, date, type, in_txs, data)
+            VALUES (?1, ?2, ?3, json_extract(?4, '$.in'), ?4)
+            ON CONFLICT (network, date, type, in_txs) DO UPDATE SET data = excluded.data
+            RETURNING id",
+            params![
+                network,
+                action.date,
+                action.action_type,
+                serde_json::to_string(action)?
+            ],
+            |row| row.get(0),
+        )?;
+
+        transaction.execute("DELETE FROM action_keys WHERE action_id = ?1", [id])?;
+
+        for (kind, value) in action_keys(action) {
+            transaction.execute(
+                "INSERT INTO action_keys (action_id, network, kind, value, date)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, network, kind, value, action.date],
+            )?;
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    async fn fetch_actions(
+        &self,
+        filter: &ActionsFilter,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<Action>), StoreError> {
+        let (from, to) = filter.date_bounds();
+        let condition = "network = ?1
+            AND (?2 IS NULL OR id IN (SELECT action_id FROM action_keys
+                WHERE network = ?1 AND kind = 'address' AND value = ?2))
+            AND (?3 IS NULL OR id IN (SELECT action_id FROM action_keys
+                WHERE network = ?1 AND kind = 'pool' AND value = ?3))
+            AND (?4 IS NULL OR type = ?4)
+            AND (?5 IS NULL OR date >= ?5)
+            AND (?6 IS NULL OR date <= ?6)";
+        let params: [&dyn ToSql; 5] = [
+            &filter.address,
+            &filter.pool,
+            &filter.action_type,
+            &from,
+            &to,
+        ];
+
+        let total = self.actions.count(
+            &format!("SELECT count(*) FROM actions WHERE {}", condition),
+            &params,
+        )?;
+
+        let mut all_params = params.to_vec();
+        let offset = offset(count, page);
+        all_params.extend_from_slice(&[&count, &offset]);
+
+        let actions = self.actions.select(
+            &format!(
+                "SELECT data FROM actions WHERE {} ORDER BY date DESC LIMIT ?7 OFFSET ?8",
+                condition
+            ),
+            &all_params,
+        )?;
+
+        Ok((total, actions))
+    }
+
+    async fn fetch_actions_by_tx_id(&self, tx_id: &str) -> Result<Vec<Action>, StoreError> {
+        self.actions.select(
+            "SELECT data FROM actions WHERE network = ?1 AND id IN (
+                SELECT action_id FROM action_keys
+                WHERE network = ?1 AND kind = 'txID' AND value = ?2
+            )
+            ORDER BY date DESC",
+            &[&tx_id],
+        )
+    }
+
+    async fn get_sync_state(&self, id: &str) -> Result<Option<ActionsSyncState>, StoreError> {
+        Ok(self
+            .sync_states
+            .select(
+                "SELECT data FROM actions_sync_state WHERE network = ?1 AND id = ?2",
+                &[&id],
+            )?
+            .pop())
+    }
+
+    async fn save_sync_state(&self, state: &ActionsSyncState) -> Result<(), StoreError> {
+        self.sync_states.execute(
+            "INSERT INTO actions_sync_state (network, id, data) VALUES (?1, ?2, ?3)
+            ON CONFLICT (network, id) DO UPDATE SET data = excluded.data",
+            &[&state.id, &serde_json::to_string(state)?],
+        )
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for SqliteRepository<QuarantineRecord> {
+    async fn insert_quarantine_record(&self, record: &QuarantineRecord) -> Result<(), StoreError> {
+        let (id, data) = with_id(record)?;
+
+        self.execute(
+            "INSERT INTO quarantine (network, id, dataset, replayed_at, quarantined_at, data)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            &[
+                &id,
+                &record.dataset,
+                &record.replayed_at,
+                &record.quarantined_at,
+                &data,
+            ],
+        )
+    }
+
+    async fn fetch_quarantine_records(
+        &self,
+        dataset: Option<String>,
+        include_replayed: bool,
+        count: i64,
+        page: i64,
+    ) -> Result<(u64, Vec<QuarantineRecord>), StoreError> {
+        let condition =
+            "network = ?1 AND (?2 IS NULL OR dataset = ?2) AND (?3 OR replayed_at IS NULL)";
+
+        let total = self.count(
+            &format!("SELECT count(*) FROM quarantine WHERE {}", condition),
+            &[&dataset, &include_replayed],
+        )?;
+
+        let records = self.select(
+            &format!(
+                "SELECT data FROM quarantine WHERE {}
+                ORDER BY quarantined_at DESC LIMIT ?4 OFFSET ?5",
+                condition
+            ),
+            &[&dataset, &include_replayed, &count, &offset(count, page)],
+        )?;
+
+        Ok((total, records))
+    }
+
+    async fn fetch_pending_quarantine_records(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<QuarantineRecord>, StoreError> {
+        self.select(
+            "SELECT data FROM quarantine
+            WHERE network = ?1 AND (?2 IS NULL OR dataset = ?2) AND replayed_at IS NULL
+            ORDER BY quarantined_at",
+            &[&dataset],
+        )
+    }
+
+    async fn fetch_quarantine_record(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<QuarantineRecord>, StoreError> {
+        Ok(self
+            .select(
+                "SELECT data FROM quarantine WHERE network = ?1 AND id = ?2",
+                &[&id.to_hex()],
+            )?
+            .pop())
+    }
+
+    async fn mark_replayed(&self, id: ObjectId, replayed_at: f64) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE quarantine SET replayed_at = ?3, data = json_set(data, '$.replayedAt', ?3)
+            WHERE network = ?1 AND id = ?2",
+            &[&id.to_hex(), &replayed_at],
+        )
+    }
+
+    async fn update_error(&self, id: ObjectId, error: &str) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE quarantine SET data = json_set(data, '$.error', ?3)
+            WHERE network = ?1 AND id = ?2",
+            &[&id.to_hex(), &error],
+        )
+    }
+}
+
+#[async_trait]
+impl SchemaDriftStore for SqliteRepository<SchemaDriftField> {
+    async fn record_field(
+        &self,
+        dataset: &str,
+        field: &str,
+        source_url: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let data = serde_json::to_string(&SchemaDriftField {
+            dataset: dataset.to_string(),
+            field: field.to_string(),
+            first_seen_at: seen_at,
+            last_seen_at: seen_at,
+            first_source_url: source_url.to_string(),
+        })?;
+
+        self.execute(
+            "INSERT INTO schema_drift (network, dataset, field, first_seen_at, data)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (network, dataset, field) DO UPDATE SET
+                data = json_set(schema_drift.data, '$.lastSeenAt', ?4)",
+            &[&dataset, &field, &seen_at, &data],
+        )
+    }
+
+    async fn fetch_schema_drift(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<SchemaDriftField>, StoreError> {
+        self.select(
+            "SELECT data FROM schema_drift WHERE network = ?1 AND (?2 IS NULL OR dataset = ?2)
+            ORDER BY first_seen_at DESC",
+            &[&dataset],
+        )
+    }
+}
+
+#[async_trait]
+impl ValidationMetricsStore for SqliteRepository<ValidationMetric> {
+    async fn record_violation(
+        &self,
+        dataset: &str,
+        rule: &str,
+        rejected: bool,
+        message: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let counter = if rejected { "$.rejected" } else { "$.warned" };
+        let data = serde_json::to_string(&ValidationMetric {
+            dataset: dataset.to_string(),
+            rule: rule.to_string(),
+            warned: i64::from(!rejected),
+            rejected: i64::from(rejected),
+            last_violation_at: seen_at,
+            last_violation: message.to_string(),
+        })?;
+
+        self.execute(
+            "INSERT INTO validation_metrics (network, dataset, rule, data) VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (network, dataset, rule) DO UPDATE SET data = json_set(
+                validation_metrics.data,
+                ?5, json_extract(validation_metrics.data, ?5) + 1,
+                '$.lastViolationAt', ?6,
+                '$.lastViolation', ?7
+            )",
+            &[&dataset, &rule, &data, &counter, &seen_at, &message],
+        )
+    }
+
+    async fn fetch_validation_metrics(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<ValidationMetric>, StoreError> {
+        self.select(
+            "SELECT data FROM validation_metrics
+            WHERE network = ?1 AND (?2 IS NULL OR dataset = ?2)
+            ORDER BY dataset, rule",
+            &[&dataset],
+        )
+    }
+}
+
+#[async_trait]
+impl CoverageStore for SqliteRepository<CoverageGap> {
+    async fn insert_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        let (_, data) = with_id(gap)?;
+
+        self.execute(
+            "INSERT INTO coverage_gaps (network, dataset, pool, start_time, status, data)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            &[
+                &gap.dataset,
+                &gap.pool.clone().unwrap_or_default(),
+                &gap.start_time,
+                &gap.status.to_str(),
+                &data,
+            ],
+        )
+    }
+
+    async fn fetch_gaps(
+        &self,
+        dataset: Option<String>,
+        status: Option<GapStatus>,
+    ) -> Result<Vec<CoverageGap>, StoreError> {
+        self.select(
+            "SELECT data FROM coverage_gaps
+            WHERE network = ?1 AND (?2 IS NULL OR dataset = ?2) AND (?3 IS NULL OR status = ?3)
+            ORDER BY dataset, pool, start_time",
+            &[&dataset, &status.map(|status| status.to_str())],
+        )
+    }
+
+    async fn update_gap(&self, gap: &CoverageGap) -> Result<(), StoreError> {
+        self.execute(
+            "UPDATE coverage_gaps SET status = ?5, data = json_set(
+                data,
+                '$.endTime', ?6,
+                '$.missingIntervals', ?7,
+                '$.status', ?5,
+                '$.attempts', ?8,
+                '$.updatedAt', ?9
+            )
+            WHERE network = ?1 AND dataset = ?2 AND pool = ?3 AND start_time = ?4",
+            &[
+                &gap.dataset,
+                &gap.pool.clone().unwrap_or_default(),
+                &gap.start_time,
+                &gap.status.to_str(),
+                &gap.end_time,
+                &gap.missing_intervals,
+                &gap.attempts,
+                &gap.updated_at,
+            ],
+        )
+    }
+}
+
+#[async_trait]
+impl UpstreamDisagreementStore for SqliteRepository<UpstreamDisagreement> {
+    async fn insert_disagreements(
+        &self,
+        disagreements: &[UpstreamDisagreement],
+    ) -> Result<(), StoreError> {
+        for disagreement in disagreements {
+            let (id, data) = with_id(disagreement)?;
+
+            self.execute(
+                "INSERT INTO upstream_disagreements (network, id, dataset, detected_at, data)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                &[&id, &disagreement.dataset, &disagreement.detected_at, &data],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_disagreements(
+        &self,
+        dataset: Option<String>,
+        count: i64,
+        page: i64,
+    ) -> Result<Vec<UpstreamDisagreement>, StoreError> {
+        self.select(
+            "SELECT data FROM upstream_disagreements
+            WHERE network = ?1 AND (?2 IS NULL OR dataset = ?2)
+            ORDER BY detected_at DESC LIMIT ?3 OFFSET ?4",
+            &[&dataset, &count, &offset(count, page)],
+        )
+    }
+}
+
+#[async_trait]
+impl MigrationStore for SqliteRepository<AppliedMigration> {
+    async fn fetch_applied(&self) -> Result<Vec<AppliedMigration>, StoreError> {
+        self.select(
+            "SELECT data FROM _migrations WHERE network = ?1 ORDER BY id",
+            &[],
+        )
+    }
+
+    async fn record_applied(&self, migration: &AppliedMigration) -> Result<(), StoreError> {
+        self.execute(
+            "INSERT INTO _migrations (network, id, data) VALUES (?1, ?2, ?3)",
+            &[&migration.id, &serde_json::to_string(migration)?],
+        )
+    }
+}
@@ -0,0 +1,457 @@
+use std::env;
+extern crate dotenv;
+use dotenv::dotenv;
+
+use mongodb::{Client, Collection};
+
+use crate::{
+    models::{
+        actions_model::{Action, ActionsSyncState},
+        coverage_model::CoverageGap,
+        depth_history_model::DepthHistory,
+        earnings_history_model::{EarningsHistory, EarningsHistoryPool},
+        index_model::IndexUsage,
+        liquidity_changes_history_model::LiquidityChangesHistory,
+        member_positions_model::MemberPosition,
+        migration_model::AppliedMigration,
+        network_history_model::NetworkHistory,
+        pool_stats_model::PoolStats,
+        quarantine_model::QuarantineRecord,
+        rune_pool_history_model::RunePoolHistory,
+        savers_history_model::SaversHistory,
+        schema_drift_model::SchemaDriftField,
+        swaps_history_model::SwapsHistory,
+        upstream_disagreement_model::UpstreamDisagreement,
+        validation_model::ValidationMetric,
+    },
+    utils::network::Network,
+};
+
+use super::{
+    actions_repo::{ActionsRepository, ActionsStore},
+    coverage_repo::{CoverageRepository, CoverageStore},
+    depth_history_repo::DepthHistoryRepository,
+    earnings_history_repo::EarningsHistoryRepository,
+    history_store::HistoryStore,
+    index_repo::{IndexRepository, IndexStore},
+    liquidity_changes_history_repo::LiquidityChangesHistoryRepository,
+    member_positions_repo::{MemberPositionsRepository, MemberPositionsStore},
+    memory_repo::{MemoryActionsRepository, MemoryRepository},
+    migration_repo::{MigrationRepository, MigrationStore},
+    network_history_repo::{NetworkHistoryRepository, NetworkHistoryStore},
+    pool_stats_repo::{PoolStatsRepository, PoolStatsStore},
+    quarantine_repo::{QuarantineRepository, QuarantineStore},
+    rune_pool_history_repo::RunePoolHistoryRepository,
+    savers_history_repo::SaversHistoryRepository,
+    schema_drift_repo::{SchemaDriftRepository, SchemaDriftStore},
+    swaps_history_repo::SwapsHistoryRepository,
+    upstream_disagreement_repo::{UpstreamDisagreementRepository, UpstreamDisagreementStore},
+    validation_metrics_repo::{ValidationMetricsRepository, ValidationMetricsStore},
+};
+
+#[cfg(feature = "postgres")]
+use super::postgres_repo::{
+    PostgresActionsRepository, PostgresDatabase, PostgresHistoryRepository, PostgresRepository,
+};
+#[cfg(feature = "sqlite")]
+use super::sqlite_repo::{
+    SqliteActionsRepository, SqliteDatabase, SqliteHistoryRepository, SqliteRepository,
+};
+
+// Where the networks are stored, chosen by the scheme of `DATABASE_URL`. Without it the data goes
+// to the MongoDB at `MONGOURI`.
+pub enum Backend {
+    Mongo(Client),
+    // Every collection has a table, the indexes are the only ones left in memory.
+    #[cfg(feature = "postgres")]
+    Postgres(Box<tokio_postgres::Config>),
+    // One file for every network, with the same tables as Postgres in it.
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteDatabase),
+    // Nothing is kept across restarts, for running the service without a database.
+    Memory,
+}
+
+// Stores of one network, in the network's own database.
+pub struct Stores {
+    pub network: Network,
+    pub depth_history_repo: Box<dyn HistoryStore<DepthHistory>>,
+    pub earnings_history_repo: Box<dyn HistoryStore<EarningsHistory>>,
+    pub rune_pool_history_repo: Box<dyn HistoryStore<RunePoolHistory>>,
+    pub swaps_history_repo: Box<dyn HistoryStore<SwapsHistory>>,
+    pub savers_history_repo: Box<dyn HistoryStore<SaversHistory>>,
+    pub liquidity_changes_history_repo: Box<dyn HistoryStore<LiquidityChangesHistory>>,
+    pub actions_repo: Box<dyn ActionsStore>,
+    pub network_history_repo: Box<dyn NetworkHistoryStore>,
+    pub member_positions_repo: Box<dyn MemberPositionsStore>,
+    pub pool_stats_repo: Box<dyn PoolStatsStore>,
+    pub quarantine_repo: Box<dyn QuarantineStore>,
+    pub schema_drift_repo: Box<dyn SchemaDriftStore>,
+    pub validation_metrics_repo: Box<dyn ValidationMetricsStore>,
+    pub coverage_repo: Box<dyn CoverageStore>,
+    pub upstream_disagreement_repo: Box<dyn UpstreamDisagreementStore>,
+    pub index_repo: Box<dyn IndexStore>,
+    pub migration_repo: Box<dyn MigrationStore>,
+}
+
+impl Stores {
+    pub async fn connect() -> Result<Backend, &'static str> {
+        dotenv().ok();
+
+        if let Ok(url) = env::var("DATABASE_URL") {
+            return Self::backend(&url).await;
+        }
+
+        let uri = match env::var("MONGOURI") {
+            Ok(v) => v.to_string(),
+            Err(_) => return Err("Error loading the mongodb uri."),
+        };
+
+        let client: Option<Client> = Some(Client::with_uri_str(uri).await.unwrap());
+
+        let client = match client {
+            Some(clt) => clt,
+            None => return Err("Failed connecting to the mongodb client, check the mongouri."),
+        };
+
+        Ok(Backend::Mongo(client))
+    }
+
+    // The backend a `DATABASE_URL` points to.
+    pub async fn backend(url: &str) -> Result<Backend, &'static str> {
+        match url.split_once("://") {
+            Some(("memory", _)) => Ok(Backend::Memory),
+            Some(("mongodb", _)) | Some(("mongodb+srv", _)) => Client::with_uri_str(url)
+                .await
+                .map(Backend::Mongo)
+                .map_err(|_| "Failed connecting to the mongodb client, check the database url."),
+            Some(("postgres", _)) | Some(("postgresql", _)) => postgres_backend(url),
+            Some(("sqlite", path)) => sqlite_backend(path),
+            _ => Err(
+                "Unsupported DATABASE_URL, use mongodb://, postgres://, sqlite:// or memory://.",
+            ),
+        }
+    }
+
+    pub async fn init(backend: &Backend, network: Network) -> Result<Self, &'static str> {
+        match backend {
+            Backend::Mongo(client) => Self::init_mongo(client, network).await,
+            Backend::Memory => Ok(Self::init_memory(network)),
+            #[cfg(feature = "postgres")]
+            Backend::Postgres(config) => Self::init_postgres(config, network).await,
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(db) => Self::init_sqlite(db, network).await,
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn init_sqlite(db: &SqliteDatabase, network: Network) -> Result<Self, &'static str> {
+        let name = network.name.clone();
+        let mut stores = Self::init_memory(network);
+
+        stores.depth_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "depth_history",
+        ));
+        stores.swaps_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "swaps_history",
+        ));
+        stores.earnings_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "earnings_history",
+        ));
+        stores.rune_pool_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "rune_pool_history",
+        ));
+        stores.savers_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "savers_history",
+        ));
+        stores.liquidity_changes_history_repo = Box::new(SqliteHistoryRepository::new(
+            db.clone(),
+            &name,
+            "liquidity_changes_history",
+        ));
+
+        stores.actions_repo = Box::new(SqliteActionsRepository::new(db.clone(), &name));
+        stores.network_history_repo =
+            Box::new(SqliteRepository::<NetworkHistory>::new(db.clone(), &name));
+        stores.member_positions_repo =
+            Box::new(SqliteRepository::<MemberPosition>::new(db.clone(), &name));
+        stores.pool_stats_repo = Box::new(SqliteRepository::<PoolStats>::new(db.clone(), &name));
+        stores.quarantine_repo =
+            Box::new(SqliteRepository::<QuarantineRecord>::new(db.clone(), &name));
+        stores.schema_drift_repo =
+            Box::new(SqliteRepository::<SchemaDriftField>::new(db.clone(), &name));
+        stores.validation_metrics_repo =
+            Box::new(SqliteRepository::<ValidationMetric>::new(db.clone(), &name));
+        stores.coverage_repo = Box::new(SqliteRepository::<CoverageGap>::new(db.clone(), &name));
+        stores.upstream_disagreement_repo = Box::new(
+            SqliteRepository::<UpstreamDisagreement>::new(db.clone(), &name),
+        );
+        stores.migration_repo =
+            Box::new(SqliteRepository::<AppliedMigration>::new(db.clone(), &name));
+
+        Ok(stores)
+    }
+
+    // Each network gets a schema named after its database.
+    #[cfg(feature = "postgres")]
+    async fn init_postgres(
+        config: &tokio_postgres::Config,
+        network: Network,
+    ) -> Result<Self, &'static str> {
+        let db = PostgresDatabase::connect(config, &network.database)
+            .await
+            .map_err(|e| {
+                eprintln!(
+                    "Failed to prepare the postgres schema {}: {}",
+                    network.database, e
+                );
+                "Failed to prepare the postgres database."
+            })?;
+
+        let mut stores = Self::init_memory(network);
+
+        stores.depth_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "depth_history",
+            None,
+        ));
+        stores.swaps_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "swaps_history",
+            None,
+        ));
+        stores.earnings_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "earnings_history",
+            Some("earnings_history_pool"),
+        ));
+        stores.rune_pool_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "rune_pool_history",
+            None,
+        ));
+        stores.savers_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "savers_history",
+            None,
+        ));
+        stores.liquidity_changes_history_repo = Box::new(PostgresHistoryRepository::new(
+            db.clone(),
+            "liquidity_changes_history",
+            None,
+        ));
+
+        stores.actions_repo = Box::new(PostgresActionsRepository::new(db.clone()));
+        stores.network_history_repo =
+            Box::new(PostgresRepository::<NetworkHistory>::new(db.clone()));
+        stores.member_positions_repo =
+            Box::new(PostgresRepository::<MemberPosition>::new(db.clone()));
+        stores.pool_stats_repo = Box::new(PostgresRepository::<PoolStats>::new(db.clone()));
+        stores.quarantine_repo = Box::new(PostgresRepository::<QuarantineRecord>::new(db.clone()));
+        stores.schema_drift_repo =
+            Box::new(PostgresRepository::<SchemaDriftField>::new(db.clone()));
+        stores.validation_metrics_repo =
+            Box::new(PostgresRepository::<ValidationMetric>::new(db.clone()));
+        stores.coverage_repo = Box::new(PostgresRepository::<CoverageGap>::new(db.clone()));
+        stores.upstream_disagreement_repo =
+            Box::new(PostgresRepository::<UpstreamDisagreement>::new(db.clone()));
+        stores.migration_repo = Box::new(PostgresRepository::<AppliedMigration>::new(db));
+
+        Ok(stores)
+    }
+
+    fn init_memory(network: Network) -> Self {
+        Stores {
+            network,
+            depth_history_repo: Box::new(MemoryRepository::new()),
+            earnings_history_repo: Box::new(MemoryRepository::new()),
+            rune_pool_history_repo: Box::new(MemoryRepository::new()),
+            swaps_history_repo: Box::new(MemoryRepository::new()),
+            savers_history_repo: Box::new(MemoryRepository::new()),
+            liquidity_changes_history_repo: Box::new(MemoryRepository::new()),
+            actions_repo: Box::<MemoryActionsRepository>::default(),
+            network_history_repo: Box::new(MemoryRepository::new()),
+            member_positions_repo: Box::new(MemoryRepository::new()),
+            pool_stats_repo: Box::new(MemoryRepository::new()),
+            quarantine_repo: Box::new(MemoryRepository::new()),
+            schema_drift_repo: Box::new(MemoryRepository::new()),
+            validation_metrics_repo: Box::new(MemoryRepository::new()),
+            coverage_repo: Box::new(MemoryRepository::new()),
+            upstream_disagreement_repo: Box::new(MemoryRepository::new()),
+            index_repo: Box::new(MemoryRepository::<IndexUsage>::new()),
+            migration_repo: Box::new(MemoryRepository::<AppliedMigration>::new()),
+        }
+    }
+
+    async fn init_mongo(client: &Client, network: Network) -> Result<Self, &'static str> {
+        let db = client.database(&network.database);
+
+        // Creating the indexes a collection is missing is safe on every start, the ones built with
+        // other options are only reported.
+        let index_repo = IndexRepository::new(db.clone());
+
+        match index_repo.ensure_indexes().await {
+            Ok(problems) => {
+                for problem in problems {
+                    eprintln!(
+                        "Index {} on {}.{} is {}",
+                        problem.keys, network.database, problem.collection, problem.problem
+                    );
+                }
+            }
+            Err(e) => eprintln!("Failed to check the indexes of {}: {}", network.database, e),
+        }
+
+        let depth_history_collection: Collection<DepthHistory> = db.collection("depth_history");
+        let earnings_history_collection: Collection<EarningsHistory> =
+            db.collection("earnings_history_new");
+
+        let earnings_history_pool_collection: Collection<EarningsHistoryPool> =
+            db.collection("earnings_history_pool");
+
+        let swaps_history_collection: Collection<SwapsHistory> = db.collection("swaps_history");
+        let rune_pool_collection: Collection<RunePoolHistory> = db.collection("rune_pool_history");
+        let savers_history_collection: Collection<SaversHistory> = db.collection("savers_history");
+        let liquidity_changes_history_collection: Collection<LiquidityChangesHistory> =
+            db.collection("liquidity_changes_history");
+        let actions_collection: Collection<Action> = db.collection("actions");
+        let actions_sync_state_collection: Collection<ActionsSyncState> =
+            db.collection("actions_sync_state");
+        let network_history_collection: Collection<NetworkHistory> =
+            db.collection("network_history");
+        let member_positions_collection: Collection<MemberPosition> =
+            db.collection("member_positions");
+        let pool_stats_collection: Collection<PoolStats> = db.collection("pool_stats");
+        let quarantine_collection: Collection<QuarantineRecord> = db.collection("quarantine");
+        let schema_drift_collection: Collection<SchemaDriftField> = db.collection("schema_drift");
+        let validation_metrics_collection: Collection<ValidationMetric> =
+            db.collection("validation_metrics");
+        let coverage_collection: Collection<CoverageGap> = db.collection("coverage_gaps");
+        let upstream_disagreement_collection: Collection<UpstreamDisagreement> =
+            db.collection("upstream_disagreements");
+        let migration_collection: Collection<AppliedMigration> = db.collection("_migrations");
+
+        let depth_history_repo = DepthHistoryRepository::init(depth_history_collection)
+            .await
+            .unwrap();
+        let earnings_history_repo = EarningsHistoryRepository::init(
+            earnings_history_collection,
+            earnings_history_pool_collection,
+        )
+        .await
+        .unwrap();
+
+        let swaps_history_repo = SwapsHistoryRepository::init(swaps_history_collection)
+            .await
+            .unwrap();
+
+        let rune_pool_history_repo = RunePoolHistoryRepository::init(rune_pool_collection)
+            .await
+            .unwrap();
+
+        let savers_history_repo = SaversHistoryRepository::init(savers_history_collection)
+            .await
+            .unwrap();
+
+        let liquidity_changes_history_repo =
+            LiquidityChangesHistoryRepository::init(liquidity_changes_history_collection)
+                .await
+                .unwrap();
+
+        let actions_repo =
+            ActionsRepository::init(actions_collection, actions_sync_state_collection)
+                .await
+                .unwrap();
+
+        let network_history_repo = NetworkHistoryRepository::init(network_history_collection)
+            .await
+            .unwrap();
+
+        let member_positions_repo = MemberPositionsRepository::init(member_positions_collection)
+            .await
+            .unwrap();
+
+        let pool_stats_repo = PoolStatsRepository::init(pool_stats_collection)
+            .await
+            .unwrap();
+
+        let quarantine_repo = QuarantineRepository::init(quarantine_collection)
+            .await
+            .unwrap();
+
+        let schema_drift_repo = SchemaDriftRepository::init(schema_drift_collection)
+            .await
+            .unwrap();
+
+        let validation_metrics_repo =
+            ValidationMetricsRepository::init(validation_metrics_collection)
+                .await
+                .unwrap();
+
+        let coverage_repo = CoverageRepository::init(coverage_collection).await.unwrap();
+
+        let upstream_disagreement_repo =
+            UpstreamDisagreementRepository::init(upstream_disagreement_collection)
+                .await
+                .unwrap();
+
+        Ok(Stores {
+            network,
+            depth_history_repo: Box::new(depth_history_repo),
+            earnings_history_repo: Box::new(earnings_history_repo),
+            rune_pool_history_repo: Box::new(rune_pool_history_repo),
+            swaps_history_repo: Box::new(swaps_history_repo),
+            savers_history_repo: Box::new(savers_history_repo),
+            liquidity_changes_history_repo: Box::new(liquidity_changes_history_repo),
+            actions_repo: Box::new(actions_repo),
+            network_history_repo: Box::new(network_history_repo),
+            member_positions_repo: Box::new(member_positions_repo),
+            pool_stats_repo: Box::new(pool_stats_repo),
+            quarantine_repo: Box::new(quarantine_repo),
+            schema_drift_repo: Box::new(schema_drift_repo),
+            validation_metrics_repo: Box::new(validation_metrics_repo),
+            coverage_repo: Box::new(coverage_repo),
+            upstream_disagreement_repo: Box::new(upstream_disagreement_repo),
+            index_repo: Box::new(index_repo),
+            migration_repo: Box::new(MigrationRepository::new(migration_collection)),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_backend(url: &str) -> Result<Backend, &'static str> {
+    url.parse()
+        .map(|config| Backend::Postgres(Box::new(config)))
+        .map_err(|_| "Invalid postgres DATABASE_URL.")
+}
+
+#[cfg(not(feature = "postgres"))]
+fn postgres_backend(_url: &str) -> Result<Backend, &'static str> {
+    Err("DATABASE_URL is a postgres url, build with the postgres feature to use it.")
+}
+
+// `sqlite://cache.db` is relative to the working directory, `sqlite:///var/cache.db` absolute.
+#[cfg(feature = "sqlite")]
+fn sqlite_backend(path: &str) -> Result<Backend, &'static str> {
+    super::sqlite_repo::open(path)
+        .map(Backend::Sqlite)
+        .map_err(|e| {
+            eprintln!("Failed to open the sqlite database {}: {}", path, e);
+            "Failed to open the sqlite database."
+        })
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_backend(_path: &str) -> Result<Backend, &'static str> {
+    Err("DATABASE_URL is a sqlite url, build with the sqlite feature to use it.")
+}
@@ -1,13 +1,24 @@
 use std::error::Error;
 
-use futures::TryStreamExt;
+use async_trait::async_trait;
 use mongodb::{
     bson::{doc, Document},
-    results::InsertOneResult,
     Collection,
 };
 
-use crate::{models::swaps_history_model::SwapsHistory, utils::time_interval::TimeInterval};
+use crate::{
+    models::swaps_history_model::SwapsHistory,
+    repository::{
+        bulk_repo::{bulk_upsert, BulkUpsertResult},
+        coverage_repo::{
+            count_missing_granularity, delete_intervals, fetch_first_incomplete, fetch_history,
+            fetch_intervals, fetch_latest, fetch_start_times, granularity_filter,
+            mark_missing_granularity,
+        },
+        history_store::{HistoryQuery, HistoryRecord, HistoryStore, StoreError},
+    },
+    utils::time_interval::TimeInterval,
+};
 
 pub struct SwapsHistoryRepository {
     col: Collection<SwapsHistory>,
@@ -17,161 +28,153 @@ impl SwapsHistoryRepository {
     pub async fn init(col: Collection<SwapsHistory>) -> Result<Self, Box<dyn Error>> {
         Ok(SwapsHistoryRepository { col })
     }
+}
+
+impl HistoryRecord for SwapsHistory {
+    // Counts, volumes and fees are flows over the interval, the price is state.
+    const SUMMED_FIELDS: &'static [&'static str] = &[
+        "toAssetCount",
+        "toRuneCount",
+        "toTradeCount",
+        "fromTradeCount",
+        "synthMintCount",
+        "synthRedeemCount",
+        "totalCount",
+        "toAssetVolume",
+        "toRuneVolume",
+        "toTradeVolume",
+        "fromTradeVolume",
+        "synthMintVolume",
+        "synthRedeemVolume",
+        "totalVolume",
+        "toAssetVolumeUSD",
+        "toRuneVolumeUSD",
+        "toTradeVolumeUSD",
+        "fromTradeVolumeUSD",
+        "synthMintVolumeUSD",
+        "synthRedeemVolumeUSD",
+        "totalVolumeUSD",
+        "toAssetFees",
+        "toRuneFees",
+        "toTradeFees",
+        "fromTradeFees",
+        "synthMintFees",
+        "synthRedeemFees",
+        "totalFees",
+    ];
+
+    // Each slip is averaged over the swaps it was measured on.
+    const WEIGHTED_FIELDS: &'static [(&'static str, &'static str)] = &[
+        ("toAssetAverageSlip", "toAssetCount"),
+        ("toRuneAverageSlip", "toRuneCount"),
+        ("toTradeAverageSlip", "toTradeCount"),
+        ("fromTradeAverageSlip", "fromTradeCount"),
+        ("synthMintAverageSlip", "synthMintCount"),
+        ("synthRedeemAverageSlip", "synthRedeemCount"),
+        ("averageSlip", "totalCount"),
+    ];
+
+    fn is_sortable(field: &str) -> bool {
+        SwapsHistory::has_field(field)
+    }
+}
 
-    pub async fn insert_swaps_history(
+#[async_trait]
+impl HistoryStore<SwapsHistory> for SwapsHistoryRepository {
+    async fn upsert(
         &self,
-        swaps_history: &SwapsHistory,
-    ) -> Result<InsertOneResult, Box<dyn Error>> {
-        let insert_details: InsertOneResult = self
-            .col
-            .insert_one(swaps_history, None)
-            .await
-            .expect("Failed to insert swaps history.");
-
-        Ok(insert_details)
+        swaps_histories: &[SwapsHistory],
+    ) -> Result<BulkUpsertResult, StoreError> {
+        let rows: Vec<(Document, &SwapsHistory)> = swaps_histories
+            .iter()
+            .map(|swaps_history| {
+                let filter = doc! {
+                    "startTime": swaps_history.start_time,
+                    "granularity": granularity_filter(&swaps_history.granularity),
+                };
+
+                (filter, swaps_history)
+            })
+            .collect();
+
+        Ok(bulk_upsert(&self.col, &rows).await?)
+    }
+
+    async fn fetch_history(&self, query: &HistoryQuery) -> Result<Vec<SwapsHistory>, StoreError> {
+        fetch_history(&self.col, doc! {}, query).await
     }
 
-    pub async fn fetch_swaps_history_data(
+    async fn fetch_start_times(
         &self,
         from: f64,
         to: f64,
-        count: f64,
-        interval: TimeInterval,
-        page: i64,
-        sort_by: String,
-    ) -> Result<Vec<SwapsHistory>, mongodb::error::Error> {
-        let filter = doc! {
-            "startTime": { "$gte": from },
-            "endTime":{"$lte":to},
-        };
-
-        let mut sort_by = sort_by;
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<f64>, StoreError> {
+        Ok(fetch_start_times(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        if !SwapsHistory::has_field(&sort_by) {
-            sort_by = String::from("startTime");
-        }
+    async fn fetch_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<Vec<SwapsHistory>, StoreError> {
+        Ok(fetch_intervals(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let sort_stage = doc! { &sort_by: -1 };
+    async fn delete_intervals(
+        &self,
+        from: f64,
+        to: f64,
+        _pool: Option<&str>,
+        granularity: TimeInterval,
+    ) -> Result<u64, StoreError> {
+        Ok(delete_intervals(
+            &self.col,
+            doc! {
+                "startTime": { "$gte": from, "$lt": to },
+                "granularity": granularity_filter(granularity.to_str()),
+            },
+        )
+        .await?)
+    }
 
-        let skip = (page - 1).max(0) * (count as i64);
+    async fn fetch_latest(
+        &self,
+        _pool: Option<&str>,
+        end_time: f64,
+    ) -> Result<Option<SwapsHistory>, StoreError> {
+        Ok(fetch_latest(&self.col, doc! {}, end_time).await?)
+    }
 
-        let interval_seconds = interval.as_seconds();
+    async fn fetch_first_incomplete(
+        &self,
+        _pool: Option<&str>,
+    ) -> Result<Option<SwapsHistory>, StoreError> {
+        Ok(fetch_first_incomplete(&self.col, doc! {}).await?)
+    }
 
-        let pipeline = vec![
-            doc! { "$match": filter },
-            doc! { "$sort": { "startTime": 1 } },
-            doc! {
-                "$group": {
-                    "_id": {
-                        "$toDate": {
-                            "$subtract": [
-                                "$startTime",
-                                { "$mod": ["$startTime", interval_seconds] }
-                            ]
-                        }
-                    },
-                    "toAssetCount": { "$last": "$toAssetCount" },
-                    "toRuneCount": { "$last": "$toRuneCount" },
-                    "toTradeCount": { "$last": "$toTradeCount" },
-                    "fromTradeCount": { "$last": "$fromTradeCount" },
-                    "synthMintCount": { "$last": "$synthMintCount" },
-                    "synthRedeemCount": { "$last": "$synthRedeemCount" },
-                    "totalCount": { "$last": "$totalCount" },
-                    "toAssetVolume": { "$last": "$toAssetVolume" },
-                    "toRuneVolume": { "$last": "$toRuneVolume" },
-                    "toTradeVolume": { "$last": "$toTradeVolume" },
-                    "fromTradeVolume": { "$last": "$fromTradeVolume" },
-                    "synthMintVolume": { "$last": "$synthMintVolume" },
-                    "synthRedeemVolume": { "$last": "$synthRedeemVolume" },
-                    "totalVolume": { "$last": "$totalVolume" },
-                    "toAssetVolumeUSD": { "$last": "$toAssetVolumeUSD" },
-                    "toRuneVolumeUSD": { "$last": "$toRuneVolumeUSD" },
-                    "toTradeVolumeUSD": { "$last": "$toTradeVolumeUSD" },
-                    "fromTradeVolumeUSD": { "$last": "$fromTradeVolumeUSD" },
-                    "synthMintVolumeUSD": { "$last": "$synthMintVolumeUSD" },
-                    "synthRedeemVolumeUSD": { "$last": "$synthRedeemVolumeUSD" },
-                    "totalVolumeUSD": { "$last": "$totalVolumeUSD" },
-                    "toAssetFees": { "$last": "$toAssetFees" },
-                    "toRuneFees": { "$last": "$toRuneFees" },
-                    "toTradeFees": { "$last": "$toTradeFees" },
-                    "fromTradeFees": { "$last": "$fromTradeFees" },
-                    "synthMintFees": { "$last": "$synthMintFees" },
-                    "synthRedeemFees": { "$last": "$synthRedeemFees" },
-                    "totalFees": { "$last": "$totalFees" },
-                    "toAssetAverageSlip": { "$last": "$toAssetAverageSlip" },
-                    "toRuneAverageSlip": { "$last": "$toRuneAverageSlip" },
-                    "toTradeAverageSlip": { "$last": "$toTradeAverageSlip" },
-                    "fromTradeAverageSlip": { "$last": "$fromTradeAverageSlip" },
-                    "synthMintAverageSlip": { "$last": "$synthMintAverageSlip" },
-                    "synthRedeemAverageSlip": { "$last": "$synthRedeemAverageSlip" },
-                    "averageSlip": { "$last": "$averageSlip" },
-                    "runePriceUSD": { "$last": "$runePriceUSD" },
-                    "startTime": { "$first": "$startTime" },
-                    "endTime": { "$last": "$endTime" }
-                }
-            },
-            doc! { "$project": {
-                "_id": 0,
-                "startTime": 1,
-                "endTime": 1,
-                "toAssetCount": 1,
-                "toRuneCount": 1,
-                "toTradeCount": 1,
-                "fromTradeCount": 1,
-                "synthMintCount": 1,
-                "synthRedeemCount": 1,
-                "totalCount": 1,
-                "toAssetVolume": 1,
-                "toRuneVolume": 1,
-                "toTradeVolume": 1,
-                "fromTradeVolume": 1,
-                "synthMintVolume": 1,
-                "synthRedeemVolume": 1,
-                "totalVolume": 1,
-                "toAssetVolumeUSD": 1,
-                "toRuneVolumeUSD": 1,
-                "toTradeVolumeUSD": 1,
-                "fromTradeVolumeUSD": 1,
-                "synthMintVolumeUSD": 1,
-                "synthRedeemVolumeUSD": 1,
-                "totalVolumeUSD": 1,
-                "toAssetFees": 1,
-                "toRuneFees": 1,
-                "toTradeFees": 1,
-                "fromTradeFees": 1,
-                "synthMintFees": 1,
-                "synthRedeemFees": 1,
-                "totalFees": 1,
-                "toAssetAverageSlip": 1,
-                "toRuneAverageSlip": 1,
-                "toTradeAverageSlip": 1,
-                "fromTradeAverageSlip": 1,
-                "synthMintAverageSlip": 1,
-                "synthRedeemAverageSlip": 1,
-                "averageSlip": 1,
-                "runePriceUSD": 1
-            }},
-            doc! {"$sort" : {"startTime" : 1}},
-            doc! {"$skip": skip},
-            doc! { "$limit": count as i64 },
-            doc! {"$sort" : sort_stage},
-        ];
-
-        let cursor = self.col.aggregate(pipeline, None).await?;
-
-        let results: Vec<SwapsHistory> = cursor
-            .try_collect::<Vec<Document>>()
-            .await?
-            .into_iter()
-            .map(|doc| {
-                // Convert each Document to DepthHistory
-                mongodb::bson::from_document(doc).map_err(|e| {
-                    // Convert bson::de::Error to mongodb::error::Error
-                    mongodb::error::Error::from(e)
-                })
-            })
-            .collect::<Result<Vec<SwapsHistory>, _>>()?;
+    async fn count_missing_granularity(&self) -> Result<u64, StoreError> {
+        Ok(count_missing_granularity(&self.col).await?)
+    }
 
-        Ok(results)
+    async fn mark_missing_granularity(&self, limit: i64) -> Result<u64, StoreError> {
+        Ok(mark_missing_granularity(&self.col, limit).await?)
     }
 }
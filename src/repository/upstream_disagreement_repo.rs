@@ -0,0 +1,68 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Collection};
+
+use crate::{
+    models::upstream_disagreement_model::UpstreamDisagreement,
+    repository::history_store::StoreError,
+};
+
+#[async_trait]
+pub trait UpstreamDisagreementStore: Send + Sync {
+    async fn insert_disagreements(
+        &self,
+        disagreements: &[UpstreamDisagreement],
+    ) -> Result<(), StoreError>;
+
+    // Latest first.
+    async fn fetch_disagreements(
+        &self,
+        dataset: Option<String>,
+        count: i64,
+        page: i64,
+    ) -> Result<Vec<UpstreamDisagreement>, StoreError>;
+}
+
+pub struct UpstreamDisagreementRepository {
+    col: Collection<UpstreamDisagreement>,
+}
+
+impl UpstreamDisagreementRepository {
+    pub async fn init(col: Collection<UpstreamDisagreement>) -> Result<Self, Box<dyn Error>> {
+        Ok(UpstreamDisagreementRepository { col })
+    }
+}
+
+#[async_trait]
+impl UpstreamDisagreementStore for UpstreamDisagreementRepository {
+    async fn insert_disagreements(
+        &self,
+        disagreements: &[UpstreamDisagreement],
+    ) -> Result<(), StoreError> {
+        self.col.insert_many(disagreements, None).await?;
+
+        Ok(())
+    }
+
+    async fn fetch_disagreements(
+        &self,
+        dataset: Option<String>,
+        count: i64,
+        page: i64,
+    ) -> Result<Vec<UpstreamDisagreement>, StoreError> {
+        let filter = match dataset {
+            Some(dataset) => doc! { "dataset": dataset },
+            None => doc! {},
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! { "detectedAt": -1 })
+            .skip(((page - 1).max(0) * count) as u64)
+            .limit(count)
+            .build();
+
+        Ok(self.col.find(filter, options).await?.try_collect().await?)
+    }
+}
@@ -0,0 +1,84 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    options::{FindOptions, UpdateOptions},
+    Collection,
+};
+
+use crate::{models::validation_model::ValidationMetric, repository::history_store::StoreError};
+
+#[async_trait]
+pub trait ValidationMetricsStore: Send + Sync {
+    // Counts a violation of a rule, as a warning or a rejection, and keeps the latest one.
+    async fn record_violation(
+        &self,
+        dataset: &str,
+        rule: &str,
+        rejected: bool,
+        message: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError>;
+
+    async fn fetch_validation_metrics(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<ValidationMetric>, StoreError>;
+}
+
+pub struct ValidationMetricsRepository {
+    col: Collection<ValidationMetric>,
+}
+
+impl ValidationMetricsRepository {
+    pub async fn init(col: Collection<ValidationMetric>) -> Result<Self, Box<dyn Error>> {
+        Ok(ValidationMetricsRepository { col })
+    }
+}
+
+#[async_trait]
+impl ValidationMetricsStore for ValidationMetricsRepository {
+    async fn record_violation(
+        &self,
+        dataset: &str,
+        rule: &str,
+        rejected: bool,
+        message: &str,
+        seen_at: f64,
+    ) -> Result<(), StoreError> {
+        let options = UpdateOptions::builder().upsert(true).build();
+
+        let (warned, rejected): (i64, i64) = if rejected { (0, 1) } else { (1, 0) };
+
+        self.col
+            .update_one(
+                doc! { "dataset": dataset, "rule": rule },
+                doc! {
+                    "$inc": { "warned": warned, "rejected": rejected },
+                    "$set": { "lastViolationAt": seen_at, "lastViolation": message },
+                },
+                options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_validation_metrics(
+        &self,
+        dataset: Option<String>,
+    ) -> Result<Vec<ValidationMetric>, StoreError> {
+        let filter = match dataset {
+            Some(dataset) => doc! { "dataset": dataset },
+            None => doc! {},
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! { "dataset": 1, "rule": 1 })
+            .build();
+
+        Ok(self.col.find(filter, options).await?.try_collect().await?)
+    }
+}
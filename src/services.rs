@@ -1,4 +1,19 @@
+pub mod actions_service;
+pub mod coverage_service;
 pub mod depth_history_service;
 pub mod earnings_history_service;
+pub mod health_service;
+pub mod index_service;
+pub mod liquidity_changes_history_service;
+pub mod member_positions_service;
+pub mod network_history_service;
+pub mod pool_stats_service;
+pub mod quarantine_service;
+pub mod retention_service;
+pub mod rollup_service;
 pub mod rune_pool_history_service;
+pub mod savers_history_service;
+pub mod schema_drift_service;
 pub mod swaps_history_service;
+pub mod upstream_service;
+pub mod validation_service;
@@ -0,0 +1,238 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::actions_model::{ActionsMeta, ActionsResponse, ActionsSyncState},
+    repository::stores::Stores,
+    utils::{
+        ingestion,
+        midgard_client::midgard_get,
+        query_parameters::{ActionsQueryParameters, QueryParameters},
+    },
+};
+
+// Midgard doesn't return more than 50 actions per page.
+const ACTIONS_PAGE_LIMIT: i64 = 50;
+
+const ACTIONS_TYPES: &str = "swap,addLiquidity,withdraw";
+
+// Pages through Midgard actions newer than the cursor's `fromTimestamp`, storing the cursor after
+// every page so an interrupted run resumes from the last stored `nextPageToken`.
+pub async fn sync_actions(
+    db: &Data<Stores>,
+    cursor_id: String,
+    pool: String,
+    from: i64,
+) -> Result<usize, String> {
+    let mut state = match db.actions_repo.get_sync_state(&cursor_id).await {
+        Ok(Some(state)) => state,
+        Ok(None) => ActionsSyncState {
+            id: cursor_id,
+            from_timestamp: from,
+            next_page_token: None,
+            newest_date: from * 1_000_000_000,
+        },
+        Err(e) => return Err(format!("Failed to load actions sync state: {:?}", e)),
+    };
+
+    let mut actions_count = 0;
+
+    loop {
+        let mut url = format!(
+            "/v2/actions?asset={}&type={}&limit={}&fromTimestamp={}",
+            pool, ACTIONS_TYPES, ACTIONS_PAGE_LIMIT, state.from_timestamp
+        );
+
+        if let Some(next_page_token) = &state.next_page_token {
+            url.push_str(&format!("&nextPageToken={}", next_page_token));
+        }
+
+        let resp = match midgard_get(&db.network, &url).await {
+            Ok(response) => match response.json::<ActionsResponse>().await {
+                Ok(resp) => resp,
+                Err(e) => return Err(format!("Failed to deserialize response: {:?}", e)),
+            },
+            Err(e) => return Err(format!("Failed to fetch data: {:?}", e)),
+        };
+
+        for action in &resp.actions {
+            if let Err(e) = db.actions_repo.upsert_action(action).await {
+                return Err(format!("Failed to insert action into database: {:?}", e));
+            }
+            state.newest_date = state.newest_date.max(action.date);
+            actions_count += 1;
+        }
+
+        match resp.meta.next_page_token {
+            Some(next_page_token) if !resp.actions.is_empty() => {
+                state.next_page_token = Some(next_page_token);
+            }
+            _ => {
+                // The run is complete, the next one only needs the actions after the newest one.
+                state.next_page_token = None;
+                state.from_timestamp = state.newest_date / 1_000_000_000;
+            }
+        }
+
+        if let Err(e) = db.actions_repo.save_sync_state(&state).await {
+            return Err(format!("Failed to save actions sync state: {:?}", e));
+        }
+
+        if state.next_page_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(actions_count)
+}
+
+pub async fn fetch_and_update_actions(db: &Data<Stores>, from: f64, pool: String) -> bool {
+    let cursor_id = format!("actions:{}", pool);
+
+    // Without any stored history there is nothing to line up with, so only the last hour is synced
+    // and older actions are left to `/fetch-and-insert-actions`.
+    let from = if from > 0.0 {
+        from as i64
+    } else {
+        Utc::now().timestamp() - 3600
+    };
+
+    match sync_actions(db, cursor_id, pool, from).await {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("{}", e);
+            false
+        }
+    }
+}
+
+#[get("/fetch-and-insert-actions")]
+pub async fn fetch_and_insert_actions(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, _, _, _, _, _, pool) = query.process_query_parameters();
+
+    let cursor_id = format!("actions-backfill:{}", pool);
+
+    // A finished backfill restarts from the requested time, an interrupted one is resumed.
+    match db.actions_repo.get_sync_state(&cursor_id).await {
+        Ok(Some(state)) if state.next_page_token.is_some() => {
+            println!("Resuming actions backfill from {}", state.from_timestamp);
+        }
+        Ok(_) => {
+            let state = ActionsSyncState {
+                id: cursor_id.clone(),
+                from_timestamp: from as i64,
+                next_page_token: None,
+                newest_date: from as i64 * 1_000_000_000,
+            };
+            if db.actions_repo.save_sync_state(&state).await.is_err() {
+                return HttpResponse::InternalServerError()
+                    .body("Failed to save actions sync state");
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to load actions sync state: {:?}", e);
+            return HttpResponse::InternalServerError().body("Failed to load actions sync state");
+        }
+    }
+
+    // The backfill takes one slot of the ingestion executor for its whole run.
+    match ingestion::run(sync_actions(&db, cursor_id, pool, from as i64)).await {
+        Ok(actions_count) => {
+            println!("inserted {} actions", actions_count);
+            HttpResponse::Ok().body("Successfully fetched and inserted actions into database.")
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            HttpResponse::InternalServerError().body(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/actions",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("address" = Option<String>, Query, description = "Returns only the actions with this address in their inbound or outbound transactions."),
+        ("pool" = Option<String>, Query, description = "Returns only the actions involving this pool (e.g., BTC.BTC)."),
+        ("type" = Option<String>, Query, description = "Returns only the actions of this type (e.g., swap, addLiquidity, withdraw). Ignored if the type is unknown."),
+        ("from" = Option<i64>, Query, description = "Start time of the actions in Unix timestamp format."),
+        ("to" = Option<i64>, Query, description = "End time of the actions in Unix timestamp format."),
+        ("count" = Option<i64>, Query, description = "Number of actions to fetch. Defaults to `50` if not provided or if the provided value is out of range (must be > 0 and <= 400)."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched actions, newest first.", body = ActionsResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Actions",
+    operation_id = "fetchActions"
+)]
+#[get("")]
+pub async fn actions_api(
+    db: Data<Stores>,
+    query: web::Query<ActionsQueryParameters>,
+) -> HttpResponse {
+    let (filter, count, page) = query.process_query_parameters();
+
+    match db.actions_repo.fetch_actions(&filter, count, page).await {
+        Ok((total, actions)) => HttpResponse::Ok().json(ActionsResponse {
+            meta: ActionsMeta::default(),
+            count: total as f64,
+            actions,
+        }),
+        Err(e) => {
+            eprintln!("Error occured {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch actions")
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/actions/{txid}",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("txid" = String, Path, description = "Inbound or outbound transaction id of the action.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the actions of the transaction.", body = ActionsResponse),
+        (status = 404, description = "No action found for the transaction id."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Actions",
+    operation_id = "fetchActionsByTxId"
+)]
+#[get("/{txid}")]
+pub async fn action_by_tx_id_api(db: Data<Stores>, path: web::Path<String>) -> HttpResponse {
+    let tx_id = path.into_inner();
+
+    match db.actions_repo.fetch_actions_by_tx_id(&tx_id).await {
+        Ok(actions) if actions.is_empty() => {
+            HttpResponse::NotFound().body(format!("No action found for transaction {}", tx_id))
+        }
+        Ok(actions) => HttpResponse::Ok().json(ActionsResponse {
+            meta: ActionsMeta::default(),
+            count: actions.len() as f64,
+            actions,
+        }),
+        Err(e) => {
+            eprintln!("Error occured {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch actions")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config
+        .service(fetch_and_insert_actions)
+        .service(actions_api)
+        .service(action_by_tx_id_api);
+}
@@ -0,0 +1,501 @@
+use actix_web::{
+    get, post,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::coverage_model::{CoverageGap, CoverageResponse, GapStatus},
+    repository::{history_store::StoreError, stores::Stores},
+    services::{
+        depth_history_service, earnings_history_service, liquidity_changes_history_service,
+        retention_service, rollup_service, rune_pool_history_service, savers_history_service,
+        swaps_history_service,
+    },
+    utils::{
+        ingestion,
+        midgard_client::{
+            DEPTH_HISTORY, EARNINGS_HISTORY, LIQUIDITY_CHANGES_HISTORY, RUNE_POOL_HISTORY,
+            SAVERS_HISTORY, SWAPS_HISTORY,
+        },
+        network::Network,
+        query_parameters::QuarantineQueryParameters,
+        scheduler::tracked_pools,
+        time_interval::TimeInterval,
+    },
+};
+
+const HOUR: f64 = 3600.0;
+
+// Refetches of a gap before it is reported as unrepairable.
+const MAX_REPAIR_ATTEMPTS: i64 = 3;
+
+// Share of the intervals of a granularity between `from` and `to` that are stored. Months and
+// longer are counted at their nominal length.
+pub fn coverage_ratio(stored: usize, from: f64, to: f64, granularity: TimeInterval) -> f64 {
+    let expected = ((to - from) / granularity.as_seconds() as f64).ceil();
+
+    if expected <= 0.0 {
+        1.0
+    } else {
+        (stored as f64 / expected).min(1.0)
+    }
+}
+
+// Range the served `(start, end)` intervals span. Queries may sort them by any field, newest first
+// by default.
+pub fn covered_range(intervals: impl Iterator<Item = (f64, f64)>) -> (f64, f64) {
+    intervals.fold(
+        (f64::MAX, f64::MIN),
+        |(from, to), (start_time, end_time)| (from.min(start_time), to.max(end_time)),
+    )
+}
+
+// Runs of missing hours between consecutive stored start times, as start of the first missing
+// hour and end of the last one.
+fn find_gaps(start_times: &[f64]) -> Vec<(f64, f64)> {
+    start_times
+        .windows(2)
+        .filter(|window| window[1] - window[0] > HOUR)
+        .map(|window| (window[0] + HOUR, window[1]))
+        .collect()
+}
+
+// What is left of the gap from `start` to `end` once the `expired` spans, oldest first, are taken
+// out of it.
+fn without_expired(start: f64, end: f64, expired: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut pieces = vec![];
+    let mut from = start;
+
+    for (expired_start, expired_end) in expired {
+        if *expired_end <= from || *expired_start >= end {
+            continue;
+        }
+
+        if *expired_start > from {
+            pieces.push((from, *expired_start));
+        }
+
+        from = from.max(*expired_end);
+    }
+
+    if from < end {
+        pieces.push((from, end));
+    }
+
+    pieces
+}
+
+// Hourly datasets, the per pool ones once for each of `pools`.
+fn hourly_datasets(pools: Vec<String>) -> Vec<(&'static str, Option<String>)> {
+    let mut datasets = vec![
+        (SWAPS_HISTORY, None),
+        (EARNINGS_HISTORY, None),
+        (RUNE_POOL_HISTORY, None),
+    ];
+
+    for pool in pools {
+        datasets.push((DEPTH_HISTORY, Some(pool.clone())));
+        datasets.push((SAVERS_HISTORY, Some(pool.clone())));
+        datasets.push((LIQUIDITY_CHANGES_HISTORY, Some(pool)));
+    }
+
+    datasets
+}
+
+// Datasets picked by `dataset` and `pool`, every hourly dataset and tracked pool if not given.
+pub fn selected_datasets(
+    network: &Network,
+    dataset: Option<&str>,
+    pool: Option<&str>,
+) -> Vec<(&'static str, Option<String>)> {
+    let pools = match pool {
+        Some(pool) => vec![pool.to_string()],
+        None => tracked_pools(network),
+    };
+
+    let mut datasets = hourly_datasets(pools);
+
+    if let Some(dataset) = dataset {
+        datasets.retain(|(name, _)| *name == dataset);
+    }
+
+    datasets
+}
+
+async fn fetch_start_times(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<Vec<f64>, StoreError> {
+    match dataset {
+        DEPTH_HISTORY => {
+            db.depth_history_repo
+                .fetch_start_times(from, to, pool, TimeInterval::Hour)
+                .await
+        }
+        SWAPS_HISTORY => {
+            db.swaps_history_repo
+                .fetch_start_times(from, to, None, TimeInterval::Hour)
+                .await
+        }
+        EARNINGS_HISTORY => {
+            db.earnings_history_repo
+                .fetch_start_times(from, to, None, TimeInterval::Hour)
+                .await
+        }
+        RUNE_POOL_HISTORY => {
+            db.rune_pool_history_repo
+                .fetch_start_times(from, to, None, TimeInterval::Hour)
+                .await
+        }
+        SAVERS_HISTORY => {
+            db.savers_history_repo
+                .fetch_start_times(from, to, pool, TimeInterval::Hour)
+                .await
+        }
+        LIQUIDITY_CHANGES_HISTORY => {
+            db.liquidity_changes_history_repo
+                .fetch_start_times(from, to, pool, TimeInterval::Hour)
+                .await
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+// Refetches one page of hours in a slot of the ingestion executor, like the scheduled fetches.
+async fn refetch(
+    db: &Data<Stores>,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    count: f64,
+) -> bool {
+    let _permit = ingestion::permit().await;
+
+    let interval = String::from("hour");
+    let pool = pool.map(str::to_string).unwrap_or_default();
+
+    match dataset {
+        DEPTH_HISTORY => {
+            depth_history_service::fetch_and_update_depth_history(
+                db.clone(),
+                from,
+                count,
+                interval,
+                pool,
+            )
+            .await
+        }
+        SWAPS_HISTORY => {
+            swaps_history_service::fetch_and_update_swaps_history(
+                db,
+                from,
+                count,
+                interval,
+                db.network.default_pool.clone(),
+            )
+            .await
+        }
+        EARNINGS_HISTORY => {
+            earnings_history_service::fetch_and_update_earnigns_history(db, from, count, interval)
+                .await
+        }
+        RUNE_POOL_HISTORY => {
+            rune_pool_history_service::fetch_and_update_rune_pool_history(db, from, count, interval)
+                .await
+        }
+        SAVERS_HISTORY => {
+            savers_history_service::fetch_and_update_savers_history(db, from, count, interval, pool)
+                .await
+        }
+        LIQUIDITY_CHANGES_HISTORY => {
+            liquidity_changes_history_service::fetch_and_update_liquidity_changes_history(
+                db, from, count, interval, pool,
+            )
+            .await
+        }
+        _ => false,
+    }
+}
+
+// Refetches the hours of a dataset from `from` to `to`, 400 intervals at a time, then rebuilds their
+// rollups. Returns whether every fetch succeeded.
+pub async fn backfill(
+    db: &Data<Stores>,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<bool, StoreError> {
+    let mut result = true;
+    let mut start = from;
+
+    while start < to {
+        let count = ((to - start) / HOUR).ceil().min(400.0);
+
+        result &= refetch(db, dataset, pool, start, count).await;
+
+        start += count * HOUR;
+    }
+
+    rollup_service::rebuild_dataset(db, dataset, pool, from, to).await?;
+
+    Ok(result)
+}
+
+// Runs of missing hours of a dataset between `from` and `to`, and the hours stored more than once.
+pub async fn verify(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<(Vec<(f64, f64)>, Vec<f64>), StoreError> {
+    let start_times = fetch_start_times(db, dataset, pool, from, to).await?;
+
+    let mut duplicates: Vec<f64> = start_times
+        .windows(2)
+        .filter(|window| window[0] == window[1])
+        .map(|window| window[0])
+        .collect();
+    duplicates.dedup();
+
+    Ok((find_gaps(&start_times), duplicates))
+}
+
+// Records the gaps of a dataset that aren't known yet. What is left of a partially repaired gap
+// falls inside the known gap and isn't recorded again. The hours the retention expired are held by
+// their rollups and aren't gaps, refetching them would only have them expired again.
+pub async fn scan_gaps(db: &Stores, dataset: &str, pool: Option<&str>) -> Result<(), StoreError> {
+    let start_times = fetch_start_times(db, dataset, pool, 0.0, f64::MAX).await?;
+
+    let expired = match (start_times.first(), start_times.last()) {
+        (Some(first), Some(last)) => {
+            retention_service::expired_hours(db, dataset, pool, *first, *last).await?
+        }
+        _ => vec![],
+    };
+
+    let known_gaps: Vec<CoverageGap> = db
+        .coverage_repo
+        .fetch_gaps(Some(dataset.to_string()), None)
+        .await?
+        .into_iter()
+        .filter(|gap| gap.pool.as_deref() == pool)
+        .collect();
+
+    let now = Utc::now().timestamp() as f64;
+
+    let gaps = find_gaps(&start_times)
+        .into_iter()
+        .flat_map(|(start_time, end_time)| without_expired(start_time, end_time, &expired));
+
+    for (start_time, end_time) in gaps {
+        let missing_intervals = ((end_time - start_time) / HOUR).round() as i64;
+
+        let is_known = known_gaps.iter().any(|gap| {
+            gap.status != GapStatus::Repaired
+                && gap.start_time <= start_time
+                && end_time <= gap.end_time
+        });
+
+        if is_known {
+            continue;
+        }
+
+        // A repaired gap showing up again is reopened.
+        if let Some(gap) = known_gaps.iter().find(|gap| gap.start_time == start_time) {
+            db.coverage_repo
+                .update_gap(&CoverageGap {
+                    end_time,
+                    missing_intervals,
+                    status: GapStatus::Found,
+                    attempts: 0,
+                    updated_at: now,
+                    ..gap.clone()
+                })
+                .await?;
+            continue;
+        }
+
+        let gap = CoverageGap {
+            id: None,
+            dataset: dataset.to_string(),
+            pool: pool.map(str::to_string),
+            start_time,
+            end_time,
+            missing_intervals,
+            status: GapStatus::Found,
+            attempts: 0,
+            detected_at: now,
+            updated_at: now,
+        };
+
+        if let Err(e) = db.coverage_repo.insert_gap(&gap).await {
+            eprintln!("Failed to record {} gap: {:?}", dataset, e);
+        }
+    }
+
+    Ok(())
+}
+
+// Refetches the hours of a gap, then checks what is stored now.
+async fn repair_gap(db: &Data<Stores>, gap: &CoverageGap) -> Result<(), StoreError> {
+    backfill(
+        db,
+        &gap.dataset,
+        gap.pool.as_deref(),
+        gap.start_time,
+        gap.end_time,
+    )
+    .await?;
+
+    let stored = fetch_start_times(
+        db,
+        &gap.dataset,
+        gap.pool.as_deref(),
+        gap.start_time,
+        gap.end_time,
+    )
+    .await?
+    .len() as i64;
+
+    let attempts = gap.attempts + 1;
+
+    let status = if stored >= gap.missing_intervals {
+        GapStatus::Repaired
+    } else if attempts >= MAX_REPAIR_ATTEMPTS {
+        GapStatus::Unrepairable
+    } else {
+        GapStatus::Found
+    };
+
+    db.coverage_repo
+        .update_gap(&CoverageGap {
+            status,
+            attempts,
+            updated_at: Utc::now().timestamp() as f64,
+            ..gap.clone()
+        })
+        .await?;
+
+    Ok(())
+}
+
+// Scans every hourly dataset for gaps and refetches the ones waiting for a repair.
+pub async fn check_coverage(db: &Data<Stores>) -> bool {
+    let mut result = true;
+
+    for (dataset, pool) in selected_datasets(&db.network, None, None) {
+        if let Err(e) = scan_gaps(db, dataset, pool.as_deref()).await {
+            eprintln!("Failed to scan {} gaps: {:?}", dataset, e);
+            result = false;
+        }
+    }
+
+    let gaps = match db
+        .coverage_repo
+        .fetch_gaps(None, Some(GapStatus::Found))
+        .await
+    {
+        Ok(gaps) => gaps,
+        Err(e) => {
+            eprintln!("Failed to fetch gaps: {:?}", e);
+            return false;
+        }
+    };
+
+    for gap in gaps {
+        if let Err(e) = repair_gap(db, &gap).await {
+            eprintln!("Failed to repair {} gap: {:?}", gap.dataset, e);
+            result = false;
+        }
+    }
+
+    result
+}
+
+async fn coverage_response(
+    db: &Stores,
+    dataset: Option<String>,
+) -> Result<CoverageResponse, StoreError> {
+    let mut response = CoverageResponse {
+        found: vec![],
+        repaired: vec![],
+        unrepairable: vec![],
+    };
+
+    for gap in db.coverage_repo.fetch_gaps(dataset, None).await? {
+        match gap.status {
+            GapStatus::Found => response.found.push(gap),
+            GapStatus::Repaired => response.repaired.push(gap),
+            GapStatus::Unrepairable => response.unrepairable.push(gap),
+        }
+    }
+
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/coverage",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Dataset of the gaps (e.g., depth_history, savers_history). Returns every dataset if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the gaps of the hourly datasets, by status.", body = CoverageResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchCoverage"
+)]
+#[get("/coverage")]
+pub async fn coverage_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, _, _, _) = query.process_query_parameters();
+
+    match coverage_response(&db, dataset).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => {
+            eprintln!("Failed to fetch coverage: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch coverage")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{network}/admin/coverage/scan",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain).")
+    ),
+    responses(
+        (status = 200, description = "Scanned the hourly datasets and refetched the gaps found, returns the gaps by status.", body = CoverageResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "scanCoverage"
+)]
+#[post("/coverage/scan")]
+pub async fn scan_coverage_api(db: Data<Stores>) -> HttpResponse {
+    check_coverage(&db).await;
+
+    match coverage_response(&db, None).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => {
+            eprintln!("Failed to fetch coverage: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch coverage")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(coverage_api).service(scan_coverage_api);
+}
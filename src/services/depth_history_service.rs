@@ -8,47 +8,57 @@ use actix_web::{
 use chrono::Utc;
 
 use crate::{
-    models::depth_history_model::{DepthHistoryMeta, DepthHistoryResponse},
-    repository::mongodb_repository::MongoDB,
-    utils::query_parameters::QueryParameters,
+    models::depth_history_model::{DepthHistory, DepthHistoryMeta, DepthHistoryResponse},
+    models::schema_drift_model::HasExtraFields,
+    repository::{bulk_repo::check_bulk_upsert, history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
+    utils::{
+        ingestion,
+        midgard_client::{fetch_history_page, FetchError, DEPTH_HISTORY},
+        query_parameters::QueryParameters,
+        time_interval::TimeInterval,
+        validation::IntervalValidator,
+    },
 };
 
 pub async fn fetch_and_update_depth_history(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     from: f64,
     count: f64,
     interval: String,
     pool: String,
 ) -> bool {
     let url = format!(
-        "https://midgard.ninerealms.com/v2/history/depths/{}?interval={}&count={}&from={}",
+        "/v2/history/depths/{}?interval={}&count={}&from={}",
         pool, interval, count, from
     );
 
-    match reqwest::get(&url).await {
-        Ok(response) => match response.json::<DepthHistoryResponse>().await {
-            Ok(resp) => {
-                for depth_history in resp.intervals {
-                    match db
-                        .depth_history_repo
-                        .insert_depth_history(&depth_history)
-                        .await
-                    {
-                        Ok(_) => (),
-                        Err(_) => {
-                            eprintln!("Failed to insert depth history data into database");
-                            return false;
-                        }
-                    }
-                }
+    match fetch_history_page::<DepthHistoryMeta, DepthHistory>(
+        &db,
+        DEPTH_HISTORY,
+        Some(&pool),
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            let mut intervals = resp.intervals;
+
+            for depth_history in intervals.iter_mut() {
+                depth_history.pool = pool.clone();
             }
-            Err(e) => {
-                eprintln!("Failed to deserialize response: {:?}", e);
+
+            if !check_bulk_upsert(
+                DEPTH_HISTORY,
+                db.depth_history_repo.upsert(&intervals).await,
+            ) {
+                eprintln!("Failed to insert depth history data into database");
                 return false;
             }
-        },
+        }
         Err(e) => {
-            eprintln!("Failed to fetch data: {:?}", e);
+            eprintln!("{}", e);
             return false;
         }
     }
@@ -58,7 +68,7 @@ pub async fn fetch_and_update_depth_history(
 
 #[get("/fetch-and-insert-depth")]
 pub async fn fetch_and_insert_depth_history(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, _, _, _, pool) = query.process_query_parameters();
@@ -74,39 +84,48 @@ pub async fn fetch_and_insert_depth_history(
             break;
         }
 
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
         let url = format!(
-            "https://midgard.ninerealms.com/v2/history/depths/{}?interval={}&count={}&from={}",
+            "/v2/history/depths/{}?interval={}&count={}&from={}",
             pool,
             interval.to_str(),
             count,
             from
         );
 
-        match reqwest::get(&url).await {
-            Ok(response) => match response.json::<DepthHistoryResponse>().await {
-                Ok(resp) => {
-                    from = resp.meta.end_time.clone();
-                    for depth_history in resp.intervals {
-                        match db
-                            .depth_history_repo
-                            .insert_depth_history(&depth_history)
-                            .await
-                        {
-                            Ok(_) => (),
-                            Err(_) => {
-                                eprintln!("Failed to insert depth history data into database");
-                                return HttpResponse::InternalServerError()
-                                    .body("Failed to insert depth history data into database");
-                            }
-                        }
-                    }
+        match fetch_history_page::<DepthHistoryMeta, DepthHistory>(
+            &db,
+            DEPTH_HISTORY,
+            Some(&pool),
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time.clone();
+                let mut intervals = resp.intervals;
+
+                for depth_history in intervals.iter_mut() {
+                    depth_history.pool = pool.clone();
                 }
-                Err(e) => {
-                    eprintln!("Failed to deserialize response: {:?}", e);
-                    return HttpResponse::InternalServerError().body("Failed to parse data");
+
+                if !check_bulk_upsert(
+                    DEPTH_HISTORY,
+                    db.depth_history_repo.upsert(&intervals).await,
+                ) {
+                    eprintln!("Failed to insert depth history data into database");
+                    return HttpResponse::InternalServerError()
+                        .body("Failed to insert depth history data into database");
                 }
-            },
-            Err(e) => {
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to parse data");
+            }
+            Err(FetchError::Request(e)) => {
                 eprintln!("Failed to fetch data: {:?}", e);
                 return HttpResponse::InternalServerError().body("Failed to fetch data");
             }
@@ -122,15 +141,17 @@ pub async fn fetch_and_insert_depth_history(
 
 #[utoipa::path(
     get,
-    path = "/depth-history",
+    path = "/{network}/depth-history",
     params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
         ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
         ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
         ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
         ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
         ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
         ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., timestamp, price). Defaults to `startTime` if not provided or if the field is not present in the model."),
-        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC.")
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC), one of the tracked pools. Defaults to `BTC.BTC` if not provided."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
     ),
     responses(
         (status = 200, description = "Successfully fetched depth history data", body = Vec<DepthHistoryResponse>),
@@ -142,26 +163,38 @@ pub async fn fetch_and_insert_depth_history(
 )]
 #[get("")]
 pub async fn depth_history_api(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
 
     println!("{} {} {} {} {} {}", from, count, to, pool, sort_by, page);
 
-    let intervals = db
+    let mut intervals = db
         .depth_history_repo
-        .fetch_depth_history_data(from, to, count, interval, page, sort_by)
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: Some(pool.clone()),
+        })
         .await
         .unwrap_or_else(|_| vec![]);
 
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
     if intervals.len() == 0 {
         return HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.");
     } else {
         let start_record = intervals.first().unwrap();
         let end_record = intervals.last().unwrap();
 
-        let meta = DepthHistoryMeta {
+        let mut meta = DepthHistoryMeta {
             start_time: start_record.start_time,
             end_time: end_record.end_time,
             price_shift_loss: end_record.asset_price - start_record.asset_price,
@@ -176,8 +209,32 @@ pub async fn depth_history_api(
             end_lp_units: end_record.liquidity_units,
             end_member_count: end_record.members_count,
             end_synth_units: end_record.synth_units,
+            coverage: None,
+            native_asset: None,
         };
 
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .depth_history_repo
+            .fetch_start_times(start_time, end_time, Some(&pool), granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
+
         let response = DepthHistoryResponse { meta, intervals };
 
         HttpResponse::Ok().json(response)
@@ -1,57 +1,62 @@
 use actix_web::web;
 
-use crate::models::earnings_history_model::EarningsHistoryMeta;
+use crate::models::earnings_history_model::{EarningsHistory, EarningsHistoryMeta};
+use crate::repository::bulk_repo::check_bulk_upsert;
+use crate::utils::ingestion;
+use crate::utils::midgard_client::{fetch_history_page, FetchError, EARNINGS_HISTORY};
 use crate::utils::query_parameters::QueryParameters;
+use crate::utils::time_interval::TimeInterval;
+use crate::utils::validation::IntervalValidator;
 use crate::{
     models::earnings_history_model::EarningsHistoryResponse,
-    repository::mongodb_repository::MongoDB,
+    models::schema_drift_model::HasExtraFields,
+    repository::{history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
 };
 use actix_web::{get, web::Data, HttpResponse};
 use chrono::Utc;
 
 pub async fn fetch_and_update_earnigns_history(
-    db: &Data<MongoDB>,
+    db: &Data<Stores>,
     from: f64,
     count: f64,
     interval: String,
 ) -> bool {
     let url = format!(
-        "https://midgard.ninerealms.com/v2/history/earnings?interval={}&count={}&from={}",
+        "/v2/history/earnings?interval={}&count={}&from={}",
         interval, count, from
     );
 
-    match reqwest::get(&url).await {
-        Ok(response) => match response.json::<EarningsHistoryResponse>().await {
-            Ok(resp) => {
-                for mut earnings_history in resp.intervals {
-                    earnings_history.pools = earnings_history
-                        .pools
-                        .into_iter()
-                        .filter(|pool| pool.pool == "BTC.BTC")
-                        .collect();
-
-                    if !earnings_history.pools.is_empty() {
-                        match db
-                            .earnings_history_repo
-                            .insert_earnings_history(&earnings_history)
-                            .await
-                        {
-                            Ok(_) => (),
-                            Err(_) => {
-                                eprintln!("Failed to insert earnings data into database");
-                                return false;
-                            }
-                        }
-                    }
-                }
+    match fetch_history_page::<EarningsHistoryMeta, EarningsHistory>(
+        db,
+        EARNINGS_HISTORY,
+        None,
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            let mut intervals = resp.intervals;
+
+            for earnings_history in intervals.iter_mut() {
+                earnings_history
+                    .pools
+                    .retain(|pool| pool.pool == db.network.default_pool);
             }
-            Err(e) => {
-                eprintln!("Failed to deserialize response: {:?}", e);
+
+            intervals.retain(|earnings_history| !earnings_history.pools.is_empty());
+
+            if !check_bulk_upsert(
+                EARNINGS_HISTORY,
+                db.earnings_history_repo.upsert(&intervals).await,
+            ) {
+                eprintln!("Failed to insert earnings data into database");
                 return false;
             }
-        },
+        }
         Err(e) => {
-            eprintln!("Failed to fetch data: {:?}", e);
+            eprintln!("{}", e);
             return false;
         }
     }
@@ -60,7 +65,7 @@ pub async fn fetch_and_update_earnigns_history(
 }
 #[get("/fetch-and-insert-earnings")]
 pub async fn fetch_and_insert_earnings_history(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, _, _, _, _) = query.process_query_parameters();
@@ -76,8 +81,11 @@ pub async fn fetch_and_insert_earnings_history(
             break;
         }
 
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
         let url = format!(
-            "https://midgard.ninerealms.com/v2/history/earnings?interval={}&count={}&from={}",
+            "/v2/history/earnings?interval={}&count={}&from={}",
             interval.to_str(),
             count,
             from
@@ -85,52 +93,41 @@ pub async fn fetch_and_insert_earnings_history(
 
         println!("{}", url);
 
-        match reqwest::get(&url).await {
-            Ok(response) => match response.json::<EarningsHistoryResponse>().await {
-                Ok(resp) => {
-                    from = resp.meta.end_time.clone();
-                    for mut earnings_history in resp.intervals {
-                        earnings_history.pools = earnings_history
-                            .pools
-                            .into_iter()
-                            .filter(|pool| pool.pool == "BTC.BTC")
-                            .collect();
-
-                        if !earnings_history.pools.is_empty() {
-                            match db
-                                .earnings_history_repo
-                                .insert_earnings_history(&earnings_history)
-                                .await
-                            {
-                                Ok(_) => (),
-                                Err(_) => {
-                                    eprintln!("Failed to insert earnings data into database");
-                                    return HttpResponse::InternalServerError()
-                                        .body("Failed to insert earnings data into database");
-                                }
-                            }
-                        }
-
-                        // match db
-                        //     .earnings_history_repo
-                        //     .insert_earnings_history(&earnings_history)
-                        //     .await
-                        // {
-                        //     Ok(_) => (),
-                        //     Err(_) => {
-                        //         eprintln!("Failed to insert earnings data into database");
-                        //         return HttpResponse::InternalServerError()
-                        //             .body("Failed to insert earnings data into database");
-                        //     }
-                        // }
-                    }
+        match fetch_history_page::<EarningsHistoryMeta, EarningsHistory>(
+            &db,
+            EARNINGS_HISTORY,
+            None,
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time.clone();
+                let mut intervals = resp.intervals;
+
+                for earnings_history in intervals.iter_mut() {
+                    earnings_history
+                        .pools
+                        .retain(|pool| pool.pool == db.network.default_pool);
                 }
-                Err(e) => {
-                    eprintln!("Failed to deserialize response: {:?}", e);
-                    return HttpResponse::InternalServerError().body("Failed to parse data");
+
+                intervals.retain(|earnings_history| !earnings_history.pools.is_empty());
+
+                if !check_bulk_upsert(
+                    EARNINGS_HISTORY,
+                    db.earnings_history_repo.upsert(&intervals).await,
+                ) {
+                    eprintln!("Failed to insert earnings data into database");
+                    return HttpResponse::InternalServerError()
+                        .body("Failed to insert earnings data into database");
                 }
-            },
-            Err(e) => {
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to parse data");
+            }
+            Err(FetchError::Request(e)) => {
                 eprintln!("Failed to fetch data: {:?}", e);
                 return HttpResponse::InternalServerError().body("Failed to fetch data");
             }
@@ -145,15 +142,17 @@ pub async fn fetch_and_insert_earnings_history(
 
 #[utoipa::path(
     get,
-    path = "/earnings-history",
+    path = "/{network}/earnings-history",
     params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
         ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
         ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
         ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
         ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
         ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
         ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., timestamp, price). Defaults to `startTime` if not provided or if the field is not present in the model."),
-        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC.")
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
     ),
     responses(
         (status = 200, description = "Successfully fetched earnings history data", body = Vec<EarningsHistoryResponse>),
@@ -165,7 +164,7 @@ pub async fn fetch_and_insert_earnings_history(
 )]
 #[get("")]
 pub async fn earnings_history_api(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
@@ -175,22 +174,34 @@ pub async fn earnings_history_api(
         from, count, to, pool, sort_by, page, interval
     );
 
-    let intervals = db
+    let mut intervals = db
         .earnings_history_repo
-        .fetch_earnings_history_data(from, to, count, interval, page, sort_by, pool)
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: None,
+        })
         .await
         .unwrap_or_else(|err| {
             eprintln!("Error occured {}", err);
             vec![]
         });
 
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
     if intervals.len() == 0 {
         return HttpResponse::Ok().body("No data available for the specified interval or query parameters may be incorrectly specified.");
     } else {
         let start_record = intervals.first().unwrap();
         let end_record = intervals.last().unwrap();
 
-        let meta = EarningsHistoryMeta {
+        let mut meta = EarningsHistoryMeta {
             start_time: start_record.start_time,
             end_time: end_record.end_time,
             liquidity_fees: end_record.liquidity_fees,
@@ -200,8 +211,32 @@ pub async fn earnings_history_api(
             liquidity_earnings: end_record.liquidity_earnings,
             avg_node_count: end_record.avg_node_count,
             rune_price_usd: end_record.rune_price_usd,
+            coverage: None,
+            native_asset: None,
         };
 
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .earnings_history_repo
+            .fetch_start_times(start_time, end_time, None, granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
+
         let response = EarningsHistoryResponse { intervals, meta };
 
         HttpResponse::Ok().json(response)
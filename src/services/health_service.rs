@@ -0,0 +1,53 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+
+use crate::{
+    models::health_model::{HealthResponse, UpstreamStatus},
+    repository::stores::Stores,
+    utils::{
+        circuit_breaker::BreakerState,
+        midgard_upstreams::{preferred_upstreams, Upstream},
+    },
+};
+
+#[utoipa::path(
+    get,
+    path = "/{network}/health",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain).")
+    ),
+    responses(
+        (status = 200, description = "Service status of the network, with the state of every Midgard endpoint in the order they are tried.", body = HealthResponse)
+    ),
+    tag = "Health",
+    operation_id = "fetchHealth"
+)]
+#[get("")]
+pub async fn health_api(db: Data<Stores>) -> HttpResponse {
+    let midgard: Vec<UpstreamStatus> = preferred_upstreams(&db.network)
+        .into_iter()
+        .map(Upstream::status)
+        .collect();
+
+    let status = if midgard.iter().all(|upstream| {
+        !upstream.lagging && upstream.breaker.state == BreakerState::Closed.to_str()
+    }) {
+        "ok"
+    } else {
+        "degraded"
+    };
+
+    HttpResponse::Ok().json(HealthResponse {
+        network: db.network.name.clone(),
+        native_asset: db.network.native_asset.clone(),
+        status: status.to_string(),
+        midgard,
+    })
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(health_api);
+}
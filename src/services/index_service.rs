@@ -0,0 +1,43 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+
+use crate::{models::index_model::IndexReportResponse, repository::stores::Stores};
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/indexes",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain).")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the expected indexes that are missing or built with other options, and the usage of every index, most used first in each collection. Both are empty unless the data is stored in MongoDB.", body = IndexReportResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchIndexes"
+)]
+#[get("/indexes")]
+pub async fn indexes_api(db: Data<Stores>) -> HttpResponse {
+    let problems = match db.index_repo.check_indexes().await {
+        Ok(problems) => problems,
+        Err(e) => {
+            eprintln!("Failed to check indexes: {:?}", e);
+            return HttpResponse::InternalServerError().body("Failed to check indexes");
+        }
+    };
+
+    match db.index_repo.fetch_index_usage().await {
+        Ok(usage) => HttpResponse::Ok().json(IndexReportResponse { problems, usage }),
+        Err(e) => {
+            eprintln!("Failed to fetch index usage: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch index usage")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(indexes_api);
+}
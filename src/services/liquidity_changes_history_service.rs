@@ -0,0 +1,256 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::liquidity_changes_history_model::{
+        LiquidityChangesHistory, LiquidityChangesHistoryMeta, LiquidityChangesHistoryResponse,
+    },
+    models::schema_drift_model::HasExtraFields,
+    repository::{bulk_repo::check_bulk_upsert, history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
+    utils::{
+        ingestion,
+        midgard_client::{fetch_history_page, FetchError, LIQUIDITY_CHANGES_HISTORY},
+        query_parameters::QueryParameters,
+        time_interval::TimeInterval,
+        validation::IntervalValidator,
+    },
+};
+
+pub async fn fetch_and_update_liquidity_changes_history(
+    db: &Data<Stores>,
+    from: f64,
+    count: f64,
+    interval: String,
+    pool: String,
+) -> bool {
+    let url = format!(
+        "/v2/history/liquidity_changes?pool={}&interval={}&count={}&from={}",
+        pool, interval, count, from
+    );
+
+    match fetch_history_page::<LiquidityChangesHistoryMeta, LiquidityChangesHistory>(
+        db,
+        LIQUIDITY_CHANGES_HISTORY,
+        Some(&pool),
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            let mut intervals = resp.intervals;
+
+            for liquidity_changes_history in intervals.iter_mut() {
+                liquidity_changes_history.pool = pool.clone();
+            }
+
+            if !check_bulk_upsert(
+                LIQUIDITY_CHANGES_HISTORY,
+                db.liquidity_changes_history_repo.upsert(&intervals).await,
+            ) {
+                eprintln!("Failed to insert liquidity changes history data into database");
+                return false;
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    }
+
+    true
+}
+
+#[get("/fetch-and-insert-liquidity-changes")]
+pub async fn fetch_and_insert_liquidity_changes_history(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, count, interval, _, _, _, pool) = query.process_query_parameters();
+
+    let mut from = from;
+
+    loop {
+        let current_time = Utc::now().timestamp() as f64;
+
+        if from >= current_time {
+            println!("Start time has reached or exceeded the current time, breaking the loop.");
+            break;
+        }
+
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
+        let url = format!(
+            "/v2/history/liquidity_changes?pool={}&interval={}&count={}&from={}",
+            pool,
+            interval.to_str(),
+            count,
+            from
+        );
+
+        match fetch_history_page::<LiquidityChangesHistoryMeta, LiquidityChangesHistory>(
+            &db,
+            LIQUIDITY_CHANGES_HISTORY,
+            Some(&pool),
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time;
+                let mut intervals = resp.intervals;
+
+                for liquidity_changes_history in intervals.iter_mut() {
+                    liquidity_changes_history.pool = pool.clone();
+                }
+
+                if !check_bulk_upsert(
+                    LIQUIDITY_CHANGES_HISTORY,
+                    db.liquidity_changes_history_repo.upsert(&intervals).await,
+                ) {
+                    eprintln!("Failed to insert liquidity changes history data into database");
+                    return HttpResponse::InternalServerError()
+                        .body("Failed to insert liquidity changes history data into database");
+                }
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .body("Failed to parse liquidity changes data");
+            }
+            Err(FetchError::Request(e)) => {
+                eprintln!("Failed to fetch data: {:?}", e);
+                return HttpResponse::InternalServerError()
+                    .body("Failed to fetch liquidity changes data");
+            }
+        }
+    }
+
+    HttpResponse::Ok()
+        .body("Successfully fetched and inserted liquidity changes history data into database.")
+}
+
+// Liquidity changes are flows, so the meta reports the totals over all the returned intervals.
+pub fn get_meta_information(intervals: &[LiquidityChangesHistory]) -> LiquidityChangesHistoryMeta {
+    let start_record = intervals.first().unwrap();
+    let end_record = intervals.last().unwrap();
+
+    let sum = |value: fn(&LiquidityChangesHistory) -> f64| intervals.iter().map(value).sum();
+
+    LiquidityChangesHistoryMeta {
+        start_time: start_record.start_time,
+        end_time: end_record.end_time,
+        add_asset_liquidity_volume: sum(|i| i.add_asset_liquidity_volume),
+        add_rune_liquidity_volume: sum(|i| i.add_rune_liquidity_volume),
+        add_liquidity_volume: sum(|i| i.add_liquidity_volume),
+        add_liquidity_count: sum(|i| i.add_liquidity_count),
+        add_asset_liquidity_volume_usd: sum(|i| i.add_asset_liquidity_volume_usd),
+        add_rune_liquidity_volume_usd: sum(|i| i.add_rune_liquidity_volume_usd),
+        add_liquidity_volume_usd: sum(|i| i.add_liquidity_volume_usd),
+        withdraw_asset_volume: sum(|i| i.withdraw_asset_volume),
+        withdraw_rune_volume: sum(|i| i.withdraw_rune_volume),
+        withdraw_volume: sum(|i| i.withdraw_volume),
+        withdraw_count: sum(|i| i.withdraw_count),
+        withdraw_asset_volume_usd: sum(|i| i.withdraw_asset_volume_usd),
+        withdraw_rune_volume_usd: sum(|i| i.withdraw_rune_volume_usd),
+        withdraw_volume_usd: sum(|i| i.withdraw_volume_usd),
+        impermanent_loss_protection_paid: sum(|i| i.impermanent_loss_protection_paid),
+        net: sum(|i| i.net),
+        rune_price_usd: end_record.rune_price_usd,
+        coverage: None,
+        native_asset: None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/liquidity-changes-history",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
+        ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
+        ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
+        ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., addLiquidityVolume, net). Defaults to `startTime` if not provided or if the field is not present in the model."),
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Defaults to `BTC.BTC` if not provided."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched liquidity changes history data.", body = Vec<LiquidityChangesHistoryResponse>),
+        (status = 404, description = "No liquidity changes history found for the provided parameters."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Liquidity Changes History",
+    operation_id = "fetchLiquidityChangesHistoryData"
+)]
+#[get("")]
+pub async fn liquidity_changes_history_api(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
+
+    let mut intervals = db
+        .liquidity_changes_history_repo
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: Some(pool.clone()),
+        })
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
+    if intervals.is_empty() {
+        HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.")
+    } else {
+        let mut meta = get_meta_information(&intervals);
+
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .liquidity_changes_history_repo
+            .fetch_start_times(start_time, end_time, Some(&pool), granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
+
+        let response = LiquidityChangesHistoryResponse { meta, intervals };
+
+        HttpResponse::Ok().json(response)
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config
+        .service(fetch_and_insert_liquidity_changes_history)
+        .service(liquidity_changes_history_api);
+}
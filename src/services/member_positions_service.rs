@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::{
+        depth_history_model::DepthHistory,
+        member_positions_model::{MemberPosition, MemberPositionsResponse, MemberResponse},
+        schema_drift_model::HasExtraFields,
+    },
+    repository::{history_store::HistoryQuery, stores::Stores},
+    utils::{
+        midgard_client::{midgard_get, parse_snapshot, MEMBER_POSITIONS},
+        network::Network,
+        query_parameters::QueryParameters,
+    },
+};
+
+// Addresses to snapshot on every tick, read from the comma separated `MEMBER_WATCH_LIST` (or
+// `<NETWORK>_MEMBER_WATCH_LIST`).
+pub fn member_watch_list(network: &Network) -> Vec<String> {
+    network
+        .setting("MEMBER_WATCH_LIST")
+        .unwrap_or_default()
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect()
+}
+
+pub async fn fetch_and_update_member_positions(db: &Data<Stores>, addresses: &[String]) -> bool {
+    let mut success = true;
+
+    for address in addresses {
+        let url = format!("/v2/member/{}", address);
+
+        match midgard_get(&db.network, &url).await {
+            Ok(response) => {
+                match parse_snapshot::<MemberResponse>(db, MEMBER_POSITIONS, response).await {
+                    Ok(resp) => {
+                        let timestamp = Utc::now().timestamp() as f64;
+
+                        for member_pool in resp.pools {
+                            let member_position =
+                                MemberPosition::from_member_pool(address, member_pool, timestamp);
+
+                            if db
+                                .member_positions_repo
+                                .upsert_member_position(&member_position)
+                                .await
+                                .is_err()
+                            {
+                                eprintln!(
+                                    "Failed to insert member position of {} into database",
+                                    address
+                                );
+                                success = false;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        // Midgard answers 404 for addresses without any position.
+                        eprintln!("Failed to deserialize response for {}: {:?}", address, e);
+                        success = false;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch data: {:?}", e);
+                success = false;
+            }
+        }
+    }
+
+    success
+}
+
+// Value of the member share of the pool, from the stored depth of the pool in the hour ending with
+// the interval. The depth history is ingested for every tracked pool, positions in other pools
+// have no value. Positions often share a pool and interval, each depth is looked up once in
+// `depths`.
+async fn set_position_value(
+    db: &Data<Stores>,
+    depths: &mut HashMap<(String, i64), Option<DepthHistory>>,
+    member_position: &mut MemberPosition,
+) {
+    let key = (
+        member_position.pool.clone(),
+        member_position.end_time as i64,
+    );
+
+    if !depths.contains_key(&key) {
+        let depth = db
+            .depth_history_repo
+            .fetch_latest(Some(&member_position.pool), member_position.end_time)
+            .await
+            .ok()
+            .flatten();
+        depths.insert(key.clone(), depth);
+    }
+
+    if let Some(depth) = &depths[&key] {
+        if depth.units > 0.0 {
+            let share = member_position.liquidity_units / depth.units;
+
+            member_position.asset_value = Some(share * depth.asset_depth);
+            member_position.rune_value = Some(share * depth.rune_depth);
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/members/{address}/history",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("address" = String, Path, description = "Address of the liquidity provider, it must be in `MEMBER_WATCH_LIST` to have snapshots."),
+        ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
+        ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
+        ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
+        ("pool" = Option<String>, Query, description = "Returns only the positions in this pool (e.g., BTC.BTC). Returns all the pools of the member if not provided."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched member position history.", body = MemberPositionsResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Member Positions History",
+    operation_id = "fetchMemberPositionsHistory"
+)]
+#[get("/{address}/history")]
+pub async fn member_positions_history_api(
+    db: Data<Stores>,
+    path: web::Path<String>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let address = path.into_inner();
+    let (from, count, interval, to, page, sort_by, _) = query.process_query_parameters();
+
+    let intervals = db
+        .member_positions_repo
+        .fetch_member_positions_data(
+            &address,
+            &HistoryQuery {
+                from,
+                to,
+                count,
+                interval,
+                page,
+                sort_by,
+                pool: query.pool.clone(),
+            },
+        )
+        .await;
+
+    match intervals {
+        Ok(mut intervals) => {
+            let mut depths = HashMap::new();
+
+            for member_position in intervals.iter_mut() {
+                set_position_value(&db, &mut depths, member_position).await;
+            }
+
+            if !query.include_extra() {
+                intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+            }
+
+            HttpResponse::Ok().json(MemberPositionsResponse { address, intervals })
+        }
+        Err(e) => {
+            eprintln!("Error occured {}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch member positions")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(member_positions_history_api);
+}
@@ -0,0 +1,127 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::network_history_model::{
+        NetworkHistory, NetworkHistoryMeta, NetworkHistoryResponse, NetworkResponse,
+    },
+    models::schema_drift_model::HasExtraFields,
+    repository::{history_store::HistoryQuery, stores::Stores},
+    utils::{
+        midgard_client::{midgard_get, parse_snapshot, NETWORK_HISTORY},
+        query_parameters::QueryParameters,
+    },
+};
+
+// Midgard only exposes the current network state, so history is built from one snapshot per tick.
+pub async fn fetch_and_update_network_history(db: &Data<Stores>) -> bool {
+    let url = "/v2/network";
+
+    match midgard_get(&db.network, url).await {
+        Ok(response) => {
+            match parse_snapshot::<NetworkResponse>(db, NETWORK_HISTORY, response).await {
+                Ok(resp) => {
+                    let timestamp = Utc::now().timestamp() as f64;
+                    let network_history = NetworkHistory::from_network_response(resp, timestamp);
+
+                    if db
+                        .network_history_repo
+                        .upsert_network_history(&network_history)
+                        .await
+                        .is_err()
+                    {
+                        eprintln!("Failed to insert network history data into database");
+                        return false;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to deserialize response: {:?}", e);
+                    return false;
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch data: {:?}", e);
+            return false;
+        }
+    }
+
+    true
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/network-history",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
+        ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
+        ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
+        ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., bondingAPY, totalActiveBond). Defaults to `startTime` if not provided or if the field is not present in the model."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched network history data.", body = Vec<NetworkHistoryResponse>),
+        (status = 404, description = "No network history found for the provided parameters."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Network History",
+    operation_id = "fetchNetworkHistoryData"
+)]
+#[get("")]
+pub async fn network_history_api(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, count, interval, to, page, sort_by, _) = query.process_query_parameters();
+
+    let mut intervals = db
+        .network_history_repo
+        .fetch_network_history_data(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: None,
+        })
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
+    if intervals.is_empty() {
+        HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.")
+    } else {
+        let start_record = intervals.first().unwrap();
+        let end_record = intervals.last().unwrap();
+
+        let meta = NetworkHistoryMeta {
+            start_time: start_record.start_time,
+            end_time: end_record.end_time,
+            start_bonding_apy: start_record.bonding_apy,
+            end_bonding_apy: end_record.bonding_apy,
+            start_total_active_bond: start_record.total_active_bond,
+            end_total_active_bond: end_record.total_active_bond,
+            start_active_node_count: start_record.active_node_count,
+            end_active_node_count: end_record.active_node_count,
+            native_asset: Some(db.network.native_asset.clone()),
+        };
+        let response = NetworkHistoryResponse { meta, intervals };
+
+        HttpResponse::Ok().json(response)
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(network_history_api);
+}
@@ -0,0 +1,133 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::{
+        pool_stats_model::{PoolStats, PoolStatsMeta, PoolStatsResponse},
+        schema_drift_model::HasExtraFields,
+    },
+    repository::{history_store::HistoryQuery, stores::Stores},
+    utils::{
+        midgard_client::{midgard_get, parse_snapshot, POOL_STATS},
+        query_parameters::QueryParameters,
+    },
+};
+
+// Period Midgard computes the stats over, the APY and fees are annualized from it.
+const POOL_STATS_PERIOD: &str = "30d";
+
+pub async fn fetch_and_update_pool_stats(db: &Data<Stores>, pool: String) -> bool {
+    let url = format!("/v2/pool/{}/stats?period={}", pool, POOL_STATS_PERIOD);
+
+    match midgard_get(&db.network, &url).await {
+        Ok(response) => match parse_snapshot::<PoolStats>(db, POOL_STATS, response).await {
+            Ok(mut pool_stats) => {
+                let timestamp = Utc::now().timestamp() as f64;
+
+                pool_stats.pool = pool;
+                pool_stats.period = String::from(POOL_STATS_PERIOD);
+                pool_stats.start_time = timestamp - timestamp % 3600.0;
+                pool_stats.end_time = pool_stats.start_time + 3600.0;
+
+                if db
+                    .pool_stats_repo
+                    .upsert_pool_stats(&pool_stats)
+                    .await
+                    .is_err()
+                {
+                    eprintln!("Failed to insert pool stats data into database");
+                    return false;
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return false;
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to fetch data: {:?}", e);
+            return false;
+        }
+    }
+
+    true
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/pool-stats/{pool}",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("pool" = String, Path, description = "Asset pool of the stats (e.g., BTC.BTC)."),
+        ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
+        ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
+        ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
+        ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., poolAPY, swapCount). Defaults to `startTime` if not provided or if the field is not present in the model."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched pool stats history.", body = PoolStatsResponse),
+        (status = 404, description = "No pool stats found for the provided parameters."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Pool Stats History",
+    operation_id = "fetchPoolStatsHistory"
+)]
+#[get("/{pool}")]
+pub async fn pool_stats_api(
+    db: Data<Stores>,
+    path: web::Path<String>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let pool = path.into_inner();
+    let (from, count, interval, to, page, sort_by, _) = query.process_query_parameters();
+
+    let mut intervals = db
+        .pool_stats_repo
+        .fetch_pool_stats_data(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: Some(pool.clone()),
+        })
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
+    if intervals.is_empty() {
+        HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.")
+    } else {
+        let start_record = intervals.first().unwrap();
+        let end_record = intervals.last().unwrap();
+
+        let meta = PoolStatsMeta {
+            pool,
+            period: end_record.period.clone(),
+            start_time: start_record.start_time,
+            end_time: end_record.end_time,
+            start_pool_apy: start_record.pool_apy,
+            end_pool_apy: end_record.pool_apy,
+            start_annual_percentage_rate: start_record.annual_percentage_rate,
+            end_annual_percentage_rate: end_record.annual_percentage_rate,
+        };
+        let response = PoolStatsResponse { meta, intervals };
+
+        HttpResponse::Ok().json(response)
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(pool_stats_api);
+}
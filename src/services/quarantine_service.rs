@@ -0,0 +1,301 @@
+use actix_web::{
+    get, post,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+use mongodb::bson::oid::ObjectId;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    models::{
+        depth_history_model::DepthHistory,
+        earnings_history_model::EarningsHistory,
+        liquidity_changes_history_model::LiquidityChangesHistory,
+        quarantine_model::{
+            QuarantineKind, QuarantineRecord, QuarantineReplayResponse, QuarantineResponse,
+        },
+        rune_pool_history_model::RunePoolHistory,
+        savers_history_model::SaversHistory,
+        swaps_history_model::SwapsHistory,
+    },
+    repository::{
+        bulk_repo::{BulkUpsertResult, BulkWriteFailure},
+        history_store::StoreError,
+        stores::Stores,
+    },
+    utils::{
+        midgard_client::{
+            set_granularity, url_granularity, DEPTH_HISTORY, EARNINGS_HISTORY,
+            LIQUIDITY_CHANGES_HISTORY, RUNE_POOL_HISTORY, SAVERS_HISTORY, SWAPS_HISTORY,
+        },
+        query_parameters::QuarantineQueryParameters,
+    },
+};
+
+// Parses every interval before inserting any, so a record is either replayed whole or stays pending.
+fn parse_intervals<I: DeserializeOwned>(raw_intervals: Vec<Value>) -> Result<Vec<I>, String> {
+    raw_intervals
+        .into_iter()
+        .map(|raw_interval| serde_json::from_value::<I>(raw_interval).map_err(|e| e.to_string()))
+        .collect()
+}
+
+// Quarantined pages are stored as Midgard returned them, their intervals get the granularity of the
+// url they were fetched from. Single intervals already carry it.
+fn raw_intervals(record: &QuarantineRecord) -> Result<Vec<Value>, String> {
+    let raw = serde_json::from_str::<Value>(&record.raw).map_err(|e| e.to_string())?;
+
+    match record.kind {
+        QuarantineKind::Interval | QuarantineKind::Invalid => Ok(vec![raw]),
+        QuarantineKind::Page => match raw.get("intervals") {
+            Some(Value::Array(intervals)) => {
+                let mut intervals = intervals.clone();
+
+                if let Some(granularity) = url_granularity(&record.source_url) {
+                    for interval in intervals.iter_mut() {
+                        set_granularity(interval, granularity.to_str());
+                    }
+                }
+
+                Ok(intervals)
+            }
+            _ => Err(String::from("missing field `intervals`")),
+        },
+    }
+}
+
+// Upserted rows of a replay, the record stays pending if any of them was refused.
+fn replayed_rows(result: Result<BulkUpsertResult, StoreError>) -> Result<u64, String> {
+    let result = result.map_err(|e| e.to_string())?;
+
+    if result.is_written() {
+        Ok(result.matched + result.upserted)
+    } else {
+        Err(result
+            .failed
+            .iter()
+            .map(BulkWriteFailure::to_string)
+            .chain(result.write_concern_error)
+            .collect::<Vec<String>>()
+            .join(", "))
+    }
+}
+
+// Re-parses a quarantined record with the current models and inserts it into its dataset.
+async fn replay_record(db: &Stores, record: &QuarantineRecord) -> Result<u64, String> {
+    let raw_intervals = raw_intervals(record)?;
+    let pool = record.pool.clone().unwrap_or_default();
+
+    match record.dataset.as_str() {
+        DEPTH_HISTORY => {
+            let mut depth_histories = parse_intervals::<DepthHistory>(raw_intervals)?;
+
+            for depth_history in depth_histories.iter_mut() {
+                depth_history.pool = pool.clone();
+            }
+
+            replayed_rows(db.depth_history_repo.upsert(&depth_histories).await)
+        }
+        SWAPS_HISTORY => {
+            let swaps_histories = parse_intervals::<SwapsHistory>(raw_intervals)?;
+
+            replayed_rows(db.swaps_history_repo.upsert(&swaps_histories).await)
+        }
+        RUNE_POOL_HISTORY => {
+            let rune_pool_histories = parse_intervals::<RunePoolHistory>(raw_intervals)?;
+
+            replayed_rows(db.rune_pool_history_repo.upsert(&rune_pool_histories).await)
+        }
+        EARNINGS_HISTORY => {
+            let mut earnings_histories = parse_intervals::<EarningsHistory>(raw_intervals)?;
+
+            for earnings_history in earnings_histories.iter_mut() {
+                earnings_history
+                    .pools
+                    .retain(|pool| pool.pool == db.network.default_pool);
+            }
+
+            earnings_histories.retain(|earnings_history| !earnings_history.pools.is_empty());
+
+            replayed_rows(db.earnings_history_repo.upsert(&earnings_histories).await)
+        }
+        SAVERS_HISTORY => {
+            let mut savers_histories = parse_intervals::<SaversHistory>(raw_intervals)?;
+
+            for savers_history in savers_histories.iter_mut() {
+                savers_history.pool = pool.clone();
+            }
+
+            replayed_rows(db.savers_history_repo.upsert(&savers_histories).await)
+        }
+        LIQUIDITY_CHANGES_HISTORY => {
+            let mut liquidity_changes_histories =
+                parse_intervals::<LiquidityChangesHistory>(raw_intervals)?;
+
+            for liquidity_changes_history in liquidity_changes_histories.iter_mut() {
+                liquidity_changes_history.pool = pool.clone();
+            }
+
+            replayed_rows(
+                db.liquidity_changes_history_repo
+                    .upsert(&liquidity_changes_histories)
+                    .await,
+            )
+        }
+        dataset => Err(format!("unknown dataset `{}`", dataset)),
+    }
+}
+
+async fn replay_records(db: &Stores, records: Vec<QuarantineRecord>) -> QuarantineReplayResponse {
+    let mut response = QuarantineReplayResponse {
+        replayed: 0,
+        inserted: 0,
+        failed: vec![],
+    };
+
+    for record in records {
+        let id = match record.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        match replay_record(db, &record).await {
+            Ok(inserted) => {
+                let replayed_at = Utc::now().timestamp() as f64;
+                if let Err(e) = db.quarantine_repo.mark_replayed(id, replayed_at).await {
+                    eprintln!(
+                        "Failed to mark quarantine record {} as replayed: {:?}",
+                        id, e
+                    );
+                }
+                response.replayed += 1;
+                response.inserted += inserted;
+            }
+            Err(e) => {
+                eprintln!("Failed to replay quarantine record {}: {}", id, e);
+                if let Err(e) = db.quarantine_repo.update_error(id, &e).await {
+                    eprintln!("Failed to update quarantine record {}: {:?}", id, e);
+                }
+                response.failed.push(id.to_hex());
+            }
+        }
+    }
+
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/quarantine",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Dataset of the quarantined records (e.g., depth_history, swaps_history). Returns every dataset if not provided."),
+        ("include_replayed" = Option<bool>, Query, description = "Also return the records that have already been replayed. Defaults to `false`."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `50` if not provided or if the provided value is out of range (must be > 0 and <= 400)."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched quarantined records, newest first.", body = QuarantineResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchQuarantine"
+)]
+#[get("/quarantine")]
+pub async fn quarantine_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, include_replayed, count, page) = query.process_query_parameters();
+
+    match db
+        .quarantine_repo
+        .fetch_quarantine_records(dataset, include_replayed, count, page)
+        .await
+    {
+        Ok((count, records)) => HttpResponse::Ok().json(QuarantineResponse { count, records }),
+        Err(e) => {
+            eprintln!("Failed to fetch quarantine records: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch quarantine records")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{network}/admin/quarantine/{id}/replay",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("id" = String, Path, description = "Id of the quarantined record.")
+    ),
+    responses(
+        (status = 200, description = "Replayed the record, failures are reported in `failed`.", body = QuarantineReplayResponse),
+        (status = 400, description = "Invalid record id."),
+        (status = 404, description = "No quarantined record found for the provided id."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "replayQuarantineRecord"
+)]
+#[post("/quarantine/{id}/replay")]
+pub async fn replay_quarantine_record_api(
+    db: Data<Stores>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    let id = match ObjectId::parse_str(path.into_inner()) {
+        Ok(id) => id,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid quarantine record id"),
+    };
+
+    match db.quarantine_repo.fetch_quarantine_record(id).await {
+        Ok(Some(record)) => HttpResponse::Ok().json(replay_records(&db, vec![record]).await),
+        Ok(None) => HttpResponse::NotFound().body("No quarantine record found for this id"),
+        Err(e) => {
+            eprintln!("Failed to fetch quarantine record: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch quarantine record")
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/{network}/admin/quarantine/replay",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Only replay the records of this dataset. Replays every dataset if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Replayed the pending records, failures are reported in `failed`.", body = QuarantineReplayResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "replayQuarantine"
+)]
+#[post("/quarantine/replay")]
+pub async fn replay_quarantine_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, _, _, _) = query.process_query_parameters();
+
+    match db
+        .quarantine_repo
+        .fetch_pending_quarantine_records(dataset)
+        .await
+    {
+        Ok(records) => HttpResponse::Ok().json(replay_records(&db, records).await),
+        Err(e) => {
+            eprintln!("Failed to fetch quarantine records: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch quarantine records")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config
+        .service(quarantine_api)
+        .service(replay_quarantine_api)
+        .service(replay_quarantine_record_api);
+}
@@ -0,0 +1,613 @@
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    repository::{
+        history_store::{HistoryRecord, HistoryStore, StoreError},
+        memory_repo::number,
+        stores::Stores,
+    },
+    services::{
+        coverage_service::selected_datasets,
+        rollup_service::{covering, has_complete_bucket},
+    },
+    utils::{
+        midgard_client::{
+            DEPTH_HISTORY, EARNINGS_HISTORY, LIQUIDITY_CHANGES_HISTORY, RUNE_POOL_HISTORY,
+            SAVERS_HISTORY, SWAPS_HISTORY,
+        },
+        network::Network,
+        time_interval::TimeInterval,
+    },
+};
+
+const DAY: f64 = 86400.0;
+
+// Rows read or written at a time when exporting or restoring.
+const BATCH_SIZE: usize = 1000;
+
+// Days the rows of each granularity of a dataset are kept, from `<DATASET>_RETENTION` or
+// `RETENTION` (e.g. `hour=180,day=forever`, also prefixed by the network). Granularities not listed
+// are kept forever, as are those no rollup covers.
+pub fn retention(network: &Network, dataset: &str) -> Vec<(TimeInterval, f64)> {
+    let setting = network
+        .setting(&format!("{}_RETENTION", dataset.to_uppercase()))
+        .or_else(|| network.setting("RETENTION"))
+        .unwrap_or_default();
+
+    let mut retention = vec![];
+
+    for entry in setting
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let (granularity, days) = match entry.split_once('=') {
+            Some((granularity, days)) => (granularity.trim(), days.trim()),
+            None => (entry, ""),
+        };
+
+        if days == "forever" {
+            continue;
+        }
+
+        let granularity = TimeInterval::from_str(granularity).filter(|g| covering(*g).is_some());
+        let days = days.trim_end_matches('d').parse::<f64>().ok();
+
+        match (granularity, days) {
+            (Some(granularity), Some(days)) if days >= 0.0 => retention.push((granularity, days)),
+            _ => eprintln!(
+                "[{}] Ignoring the {} retention {}, expected an hour, day, month or quarter with a number of days",
+                network.name, dataset, entry
+            ),
+        }
+    }
+
+    retention
+}
+
+// `<dir>/<network>/<dataset>[/<pool>]/<granularity>/<date>.ndjson.gz`, dated by the start of the
+// covering bucket the rows were expired with.
+fn archive_path(
+    dir: &Path,
+    network: &str,
+    dataset: &str,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    start: f64,
+) -> PathBuf {
+    let mut path = dir.join(network).join(dataset);
+
+    if let Some(pool) = pool {
+        path.push(pool);
+    }
+
+    let date = DateTime::from_timestamp(start as i64, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d");
+
+    path.join(granularity.to_str())
+        .join(format!("{}.ndjson.gz", date))
+}
+
+fn is_gzipped(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "gz")
+}
+
+fn write_rows<T: Serialize>(writer: &mut (dyn Write + Send), rows: &[T]) -> Result<(), StoreError> {
+    for row in rows {
+        serde_json::to_writer(&mut *writer, row)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+// Writes the rows as gzipped NDJSON. An archive already at `path`, left by a bucket restored and
+// expired again, is merged with them by start time, the new rows taking the place of the archived
+// ones. An archive that can't be read fails the write so the rows aren't deleted. The archive is
+// only replaced once the merged one is complete.
+fn write_archive<T: HistoryRecord>(path: &Path, rows: &[T]) -> Result<(), StoreError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut merged = rows
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<Value>, _>>()?;
+
+    if path.exists() {
+        for line in read_archive(path)?.lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let archived =
+                serde_json::to_value(serde_json::from_str::<T>(&line).map_err(|e| {
+                    format!("Failed to merge into the archive {}: {}", path.display(), e)
+                })?)?;
+            let start_time = number(&archived, "startTime");
+
+            if !merged[..rows.len()]
+                .iter()
+                .any(|row| number(row, "startTime") == start_time)
+            {
+                merged.push(archived);
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| number(a, "startTime").total_cmp(&number(b, "startTime")));
+
+    let partial = path.with_extension("gz.partial");
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&partial)?),
+        Compression::default(),
+    );
+
+    write_rows(&mut encoder, &merged)?;
+    encoder.finish()?.flush()?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+// Deletes the rows of `granularity` that started before `cutoff`, a whole covering bucket at a time
+// and only once the bucket is stored complete. With `archive` the rows are archived first. Returns
+// the number of rows deleted.
+async fn expire<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    archive: Option<(&Path, &str, &str)>,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    cutoff: f64,
+) -> Result<u64, StoreError> {
+    let covering = match covering(granularity) {
+        Some(covering) => covering,
+        None => return Ok(0),
+    };
+
+    let mut buckets: Vec<f64> = store
+        .fetch_start_times(0.0, cutoff, pool, granularity)
+        .await?
+        .into_iter()
+        .map(|start_time| covering.bucket_start(start_time))
+        .collect();
+    buckets.dedup();
+
+    let mut deleted = 0;
+
+    for start in buckets {
+        let end = covering.next_start(start);
+
+        if end > cutoff {
+            break;
+        }
+
+        if !has_complete_bucket(store, pool, covering, start).await? {
+            continue;
+        }
+
+        if let Some((dir, network, dataset)) = archive {
+            let rows = store.fetch_intervals(start, end, pool, granularity).await?;
+            let path = archive_path(dir, network, dataset, pool, granularity, start);
+            write_archive(&path, &rows)?;
+        }
+
+        deleted += store
+            .delete_intervals(start, end, pool, granularity)
+            .await?;
+    }
+
+    Ok(deleted)
+}
+
+// Expires the rows of one dataset, the per pool ones for `pool`.
+pub async fn expire_dataset(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    cutoff: f64,
+) -> Result<u64, StoreError> {
+    let dir = db.network.setting("ARCHIVE_DIR").map(PathBuf::from);
+    let archive = dir
+        .as_deref()
+        .map(|dir| (dir, db.network.name.as_str(), dataset));
+
+    match dataset {
+        DEPTH_HISTORY => expire(&*db.depth_history_repo, archive, pool, granularity, cutoff).await,
+        SWAPS_HISTORY => expire(&*db.swaps_history_repo, archive, None, granularity, cutoff).await,
+        EARNINGS_HISTORY => {
+            expire(
+                &*db.earnings_history_repo,
+                archive,
+                None,
+                granularity,
+                cutoff,
+            )
+            .await
+        }
+        RUNE_POOL_HISTORY => {
+            expire(
+                &*db.rune_pool_history_repo,
+                archive,
+                None,
+                granularity,
+                cutoff,
+            )
+            .await
+        }
+        SAVERS_HISTORY => {
+            expire(&*db.savers_history_repo, archive, pool, granularity, cutoff).await
+        }
+        LIQUIDITY_CHANGES_HISTORY => {
+            expire(
+                &*db.liquidity_changes_history_repo,
+                archive,
+                pool,
+                granularity,
+                cutoff,
+            )
+            .await
+        }
+        _ => Ok(0),
+    }
+}
+
+// Spans between `from` and `to` whose hours were expired, oldest first: the complete covering
+// buckets ending before `cutoff`. Their rollups hold the hours, they aren't missing.
+async fn expired<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+    cutoff: f64,
+) -> Result<Vec<(f64, f64)>, StoreError> {
+    let covering = match covering(TimeInterval::Hour) {
+        Some(covering) => covering,
+        None => return Ok(vec![]),
+    };
+
+    let to = to.min(cutoff);
+    let mut spans = vec![];
+
+    if from >= to {
+        return Ok(spans);
+    }
+
+    for row in store
+        .fetch_intervals(covering.bucket_start(from), to, pool, covering)
+        .await?
+    {
+        let row = serde_json::to_value(row)?;
+        let start = number(&row, "startTime");
+        let end = covering.next_start(start);
+
+        if start == covering.bucket_start(start)
+            && end <= cutoff
+            && row.get("isComplete").and_then(Value::as_bool) == Some(true)
+        {
+            spans.push((start.max(from), end.min(to)));
+        }
+    }
+
+    Ok(spans)
+}
+
+// Spans between `from` and `to` whose hours the retention of the dataset expired, the per pool
+// ones for `pool`. None if its hours are kept forever.
+pub async fn expired_hours(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<Vec<(f64, f64)>, StoreError> {
+    let days = match retention(&db.network, dataset)
+        .into_iter()
+        .find(|(granularity, _)| *granularity == TimeInterval::Hour)
+    {
+        Some((_, days)) => days,
+        None => return Ok(vec![]),
+    };
+
+    let cutoff = Utc::now().timestamp() as f64 - days * DAY;
+
+    match dataset {
+        DEPTH_HISTORY => expired(&*db.depth_history_repo, pool, from, to, cutoff).await,
+        SWAPS_HISTORY => expired(&*db.swaps_history_repo, None, from, to, cutoff).await,
+        EARNINGS_HISTORY => expired(&*db.earnings_history_repo, None, from, to, cutoff).await,
+        RUNE_POOL_HISTORY => expired(&*db.rune_pool_history_repo, None, from, to, cutoff).await,
+        SAVERS_HISTORY => expired(&*db.savers_history_repo, pool, from, to, cutoff).await,
+        LIQUIDITY_CHANGES_HISTORY => {
+            expired(&*db.liquidity_changes_history_repo, pool, from, to, cutoff).await
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+// Applies the retention of every hourly dataset, archiving the rows to `ARCHIVE_DIR` if it is set.
+pub async fn expire_rows(db: &Stores) -> bool {
+    let now = Utc::now().timestamp() as f64;
+    let mut result = true;
+
+    for (dataset, pool) in selected_datasets(&db.network, None, None) {
+        for (granularity, days) in retention(&db.network, dataset) {
+            let cutoff = now - days * DAY;
+
+            match expire_dataset(db, dataset, pool.as_deref(), granularity, cutoff).await {
+                Ok(0) => {}
+                Ok(deleted) => println!(
+                    "[{}] Expired {} {} rows of {}{}",
+                    db.network.name,
+                    deleted,
+                    granularity.to_str(),
+                    dataset,
+                    pool.as_deref()
+                        .map_or(String::new(), |pool| format!(" {}", pool))
+                ),
+                Err(e) => {
+                    eprintln!("Failed to expire {} rows: {:?}", dataset, e);
+                    result = false;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// Rows of an NDJSON archive, gzipped if it ends in `.gz`.
+fn read_archive(path: &Path) -> Result<Box<dyn BufRead>, StoreError> {
+    let file = File::open(path)?;
+
+    if is_gzipped(path) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+async fn restore<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    path: &Path,
+) -> Result<usize, StoreError> {
+    let mut batch = vec![];
+    let mut restored = 0;
+
+    for line in read_archive(path)?.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        batch.push(serde_json::from_str::<T>(&line)?);
+
+        if batch.len() == BATCH_SIZE {
+            store.upsert(&batch).await?;
+            restored += batch.len();
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        store.upsert(&batch).await?;
+        restored += batch.len();
+    }
+
+    Ok(restored)
+}
+
+// Upserts the rows of an archive back into a dataset's store, returns how many were. They are
+// expired again on the next tick unless the retention was raised.
+pub async fn restore_archive(db: &Stores, dataset: &str, path: &Path) -> Result<usize, StoreError> {
+    match dataset {
+        DEPTH_HISTORY => restore(&*db.depth_history_repo, path).await,
+        SWAPS_HISTORY => restore(&*db.swaps_history_repo, path).await,
+        EARNINGS_HISTORY => restore(&*db.earnings_history_repo, path).await,
+        RUNE_POOL_HISTORY => restore(&*db.rune_pool_history_repo, path).await,
+        SAVERS_HISTORY => restore(&*db.savers_history_repo, path).await,
+        LIQUIDITY_CHANGES_HISTORY => restore(&*db.liquidity_changes_history_repo, path).await,
+        _ => Err(format!("Unknown dataset {}", dataset).into()),
+    }
+}
+
+async fn export<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    from: f64,
+    to: f64,
+    writer: &mut (dyn Write + Send),
+) -> Result<usize, StoreError> {
+    let start_times = store.fetch_start_times(from, to, pool, granularity).await?;
+
+    for (i, batch) in start_times.chunks(BATCH_SIZE).enumerate() {
+        let end = start_times.get((i + 1) * BATCH_SIZE).copied().unwrap_or(to);
+
+        let rows = store
+            .fetch_intervals(batch[0], end, pool, granularity)
+            .await?;
+        write_rows(writer, &rows)?;
+    }
+
+    Ok(start_times.len())
+}
+
+async fn export_to(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    from: f64,
+    to: f64,
+    writer: &mut (dyn Write + Send),
+) -> Result<usize, StoreError> {
+    match dataset {
+        DEPTH_HISTORY => export(&*db.depth_history_repo, pool, granularity, from, to, writer).await,
+        SWAPS_HISTORY => export(&*db.swaps_history_repo, None, granularity, from, to, writer).await,
+        EARNINGS_HISTORY => {
+            export(
+                &*db.earnings_history_repo,
+                None,
+                granularity,
+                from,
+                to,
+                writer,
+            )
+            .await
+        }
+        RUNE_POOL_HISTORY => {
+            export(
+                &*db.rune_pool_history_repo,
+                None,
+                granularity,
+                from,
+                to,
+                writer,
+            )
+            .await
+        }
+        SAVERS_HISTORY => {
+            export(
+                &*db.savers_history_repo,
+                pool,
+                granularity,
+                from,
+                to,
+                writer,
+            )
+            .await
+        }
+        LIQUIDITY_CHANGES_HISTORY => {
+            export(
+                &*db.liquidity_changes_history_repo,
+                pool,
+                granularity,
+                from,
+                to,
+                writer,
+            )
+            .await
+        }
+        _ => Err(format!("Unknown dataset {}", dataset).into()),
+    }
+}
+
+// Writes the rows of one granularity of a dataset starting from `from` to `to` as NDJSON, gzipped
+// if the path ends in `.gz`, in the format `restore_archive` reads. Returns how many were written.
+pub async fn export_dataset(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    from: f64,
+    to: f64,
+    path: &Path,
+) -> Result<usize, StoreError> {
+    // Per pool rows are only matched with their pool, fail instead of writing an empty file.
+    if pool.is_none()
+        && [DEPTH_HISTORY, SAVERS_HISTORY, LIQUIDITY_CHANGES_HISTORY].contains(&dataset)
+    {
+        return Err(format!("A pool is required to export {}", dataset).into());
+    }
+
+    let file = BufWriter::new(File::create(path)?);
+
+    if is_gzipped(path) {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        let exported = export_to(db, dataset, pool, granularity, from, to, &mut encoder).await?;
+        encoder.finish()?.flush()?;
+        Ok(exported)
+    } else {
+        let mut file = file;
+        let exported = export_to(db, dataset, pool, granularity, from, to, &mut file).await?;
+        file.flush()?;
+        Ok(exported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::rune_pool_history_model::RunePoolHistory;
+
+    fn rune_pool(start_time: f64, count: f64) -> RunePoolHistory {
+        serde_json::from_value(json!({
+            "startTime": start_time,
+            "endTime": start_time + 3600.0,
+            "count": count,
+            "units": 0,
+        }))
+        .unwrap()
+    }
+
+    // A directory of its own under the temporary one.
+    fn archive_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("retention_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn archives_are_merged_with_the_rows_expired_again() {
+        let dir = archive_dir("merged");
+        let path = archive_path(
+            &dir,
+            "mainnet",
+            RUNE_POOL_HISTORY,
+            None,
+            TimeInterval::Hour,
+            0.0,
+        );
+
+        write_archive(&path, &[rune_pool(0.0, 1.0), rune_pool(3600.0, 1.0)]).unwrap();
+        write_archive(&path, &[rune_pool(3600.0, 2.0), rune_pool(7200.0, 2.0)]).unwrap();
+
+        let rows: Vec<RunePoolHistory> = read_archive(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let rows: Vec<(f64, f64)> = rows.iter().map(|row| (row.start_time, row.count)).collect();
+        assert_eq!(rows, vec![(0.0, 1.0), (3600.0, 2.0), (7200.0, 2.0)]);
+    }
+
+    #[test]
+    fn unreadable_archives_are_kept() {
+        let dir = archive_dir("unreadable");
+        let path = archive_path(
+            &dir,
+            "mainnet",
+            RUNE_POOL_HISTORY,
+            None,
+            TimeInterval::Hour,
+            0.0,
+        );
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "not an archive").unwrap();
+
+        let result = write_archive(&path, &[rune_pool(0.0, 1.0)]);
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(contents, "not an archive");
+    }
+}
@@ -0,0 +1,318 @@
+use std::cmp::Ordering;
+
+use actix_web::{
+    post,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::{
+    models::rollup_model::{RollupRebuild, RollupRebuildResponse},
+    repository::{
+        history_store::{roll_up, HistoryRecord, HistoryStore, StoreError},
+        memory_repo::number,
+        stores::Stores,
+    },
+    services::coverage_service::selected_datasets,
+    utils::{
+        midgard_client::{
+            DEPTH_HISTORY, EARNINGS_HISTORY, LIQUIDITY_CHANGES_HISTORY, RUNE_POOL_HISTORY,
+            SAVERS_HISTORY, SWAPS_HISTORY,
+        },
+        query_parameters::RollupQueryParameters,
+        time_interval::TimeInterval,
+    },
+};
+
+// Granularities materialized in the history collections, finest first, with the granularity each
+// one is rolled up from. The queries read them like Midgard's own rows of that interval.
+const ROLLUPS: [(TimeInterval, TimeInterval); 5] = [
+    (TimeInterval::Day, TimeInterval::Hour),
+    (TimeInterval::Week, TimeInterval::Day),
+    (TimeInterval::Month, TimeInterval::Day),
+    (TimeInterval::Quarter, TimeInterval::Month),
+    (TimeInterval::Year, TimeInterval::Quarter),
+];
+
+// Rollup holding everything the rows of `granularity` do once it is complete, so they can be
+// expired. Days go by month as the months are rolled up from them. Weeks and years have none.
+pub fn covering(granularity: TimeInterval) -> Option<TimeInterval> {
+    match granularity {
+        TimeInterval::Hour => Some(TimeInterval::Day),
+        TimeInterval::Day => Some(TimeInterval::Month),
+        TimeInterval::Month => Some(TimeInterval::Quarter),
+        TimeInterval::Quarter => Some(TimeInterval::Year),
+        TimeInterval::Week | TimeInterval::Year => None,
+    }
+}
+
+// The stored `granularity` bucket starting at `start`, rolled up or fetched from Midgard.
+async fn stored_bucket<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    start: f64,
+) -> Result<Option<Value>, StoreError> {
+    let end = granularity.next_start(start);
+
+    for row in store.fetch_intervals(start, end, pool, granularity).await? {
+        let row = serde_json::to_value(row)?;
+
+        if number(&row, "startTime") == start {
+            return Ok(Some(row));
+        }
+    }
+
+    Ok(None)
+}
+
+// Whether the complete `granularity` bucket starting at `start` is stored.
+pub async fn has_complete_bucket<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    start: f64,
+) -> Result<bool, StoreError> {
+    Ok(stored_bucket(store, pool, granularity, start)
+        .await?
+        .is_some_and(|row| row.get("isComplete").and_then(Value::as_bool) == Some(true)))
+}
+
+// Granularities the rollups of `granularity` are built from, its source first down to the hours.
+fn sources(granularity: TimeInterval) -> Vec<TimeInterval> {
+    let mut sources = vec![];
+    let mut rollup = granularity;
+
+    while let Some((_, source)) = ROLLUPS.iter().find(|(other, _)| *other == rollup) {
+        sources.push(*source);
+        rollup = *source;
+    }
+
+    sources
+}
+
+// Rows the `granularity` bucket from `start` to `end` is rolled up from, oldest first. The rows of
+// its source are taken first and only the spans they leave out are read from the finer
+// granularities. Retention expires rows once a coarser rollup holds them, so the expired spans
+// come from that rollup instead of being dropped.
+async fn bucket_rows<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    granularity: TimeInterval,
+    start: f64,
+    end: f64,
+) -> Result<Vec<T>, StoreError> {
+    let mut rows: Vec<(f64, T)> = vec![];
+    let mut spans = vec![(start, end)];
+
+    for source in sources(granularity) {
+        let mut missing = vec![];
+
+        for (from, to) in spans {
+            let mut covered_to = from;
+
+            for row in store.fetch_intervals(from, to, pool, source).await? {
+                let value = serde_json::to_value(&row)?;
+                let start_time = number(&value, "startTime");
+
+                if start_time < covered_to {
+                    continue;
+                }
+
+                if start_time > covered_to {
+                    missing.push((covered_to, start_time));
+                }
+
+                covered_to = number(&value, "endTime");
+                rows.push((start_time, row));
+            }
+
+            if covered_to < to {
+                missing.push((covered_to, to));
+            }
+        }
+
+        spans = missing;
+    }
+
+    rows.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    Ok(rows.into_iter().map(|(_, row)| row).collect())
+}
+
+// Rebuilds the rollup buckets of every granularity overlapping `from` to `to`, finest first so the
+// coarser ones are built from fresh rows. A complete bucket whose first rows were expired is kept
+// as it is, and so is a bucket Midgard returned at that interval. Returns the number of buckets
+// written.
+pub async fn rebuild_rollups<T: HistoryRecord>(
+    store: &dyn HistoryStore<T>,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<usize, StoreError> {
+    let latest_end_time = match store.fetch_latest(pool, f64::MAX).await? {
+        Some(latest) => number(&serde_json::to_value(latest)?, "endTime"),
+        None => return Ok(0),
+    };
+
+    let to = to.min(latest_end_time);
+    let mut buckets = 0;
+
+    for (granularity, _) in ROLLUPS {
+        let mut start = granularity.bucket_start(from);
+
+        while start < to {
+            let end = granularity.next_start(start);
+
+            let stored = stored_bucket(store, pool, granularity, start).await?;
+
+            if stored
+                .as_ref()
+                .is_some_and(|row| row.get("rolledUp").and_then(Value::as_bool) != Some(true))
+            {
+                start = end;
+                continue;
+            }
+
+            let rows = bucket_rows(store, pool, granularity, start, end).await?;
+
+            let first_start_time = match rows.first() {
+                Some(first) => number(&serde_json::to_value(first)?, "startTime"),
+                None => end,
+            };
+
+            let is_complete = stored
+                .as_ref()
+                .and_then(|row| row.get("isComplete").and_then(Value::as_bool));
+
+            if first_start_time > start && is_complete == Some(true) {
+                start = end;
+                continue;
+            }
+
+            if let Some(bucket) = roll_up(&rows, granularity, start, end)? {
+                store.upsert(&[bucket]).await?;
+                buckets += 1;
+            }
+
+            start = end;
+        }
+    }
+
+    Ok(buckets)
+}
+
+// Rebuilds the rollups of one dataset, the per pool ones for `pool`.
+pub async fn rebuild_dataset(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<usize, StoreError> {
+    match dataset {
+        DEPTH_HISTORY => rebuild_rollups(&*db.depth_history_repo, pool, from, to).await,
+        SWAPS_HISTORY => rebuild_rollups(&*db.swaps_history_repo, None, from, to).await,
+        EARNINGS_HISTORY => rebuild_rollups(&*db.earnings_history_repo, None, from, to).await,
+        RUNE_POOL_HISTORY => rebuild_rollups(&*db.rune_pool_history_repo, None, from, to).await,
+        SAVERS_HISTORY => rebuild_rollups(&*db.savers_history_repo, pool, from, to).await,
+        LIQUIDITY_CHANGES_HISTORY => {
+            rebuild_rollups(&*db.liquidity_changes_history_repo, pool, from, to).await
+        }
+        _ => Ok(0),
+    }
+}
+
+// Brings the rollups of every hourly dataset up to date with the hours ingested since `from`.
+pub async fn refresh_rollups(db: &Stores, from: f64) -> bool {
+    let now = Utc::now().timestamp() as f64;
+    let mut result = true;
+
+    for (dataset, pool) in selected_datasets(&db.network, None, None) {
+        if let Err(e) = rebuild_dataset(db, dataset, pool.as_deref(), from, now).await {
+            eprintln!("Failed to refresh {} rollups: {:?}", dataset, e);
+            result = false;
+        }
+    }
+
+    result
+}
+
+// Rebuilds the rollups of the datasets picked by `dataset` and `pool` from `from` to `to`.
+pub async fn rebuild(
+    db: &Stores,
+    dataset: Option<&str>,
+    pool: Option<&str>,
+    from: f64,
+    to: f64,
+) -> Result<Vec<RollupRebuild>, StoreError> {
+    let mut rebuilt = vec![];
+
+    for (dataset, pool) in selected_datasets(&db.network, dataset, pool) {
+        let buckets = rebuild_dataset(db, dataset, pool.as_deref(), from, to)
+            .await
+            .map_err(|e| format!("Failed to rebuild {} rollups: {}", dataset, e))?;
+
+        rebuilt.push(RollupRebuild {
+            dataset: dataset.to_string(),
+            pool,
+            buckets,
+        });
+    }
+
+    Ok(rebuilt)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{network}/admin/rollups/rebuild",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("from" = i64, Query, description = "Start of the range in seconds. Every day, week, month, quarter and year bucket overlapping the range is rebuilt."),
+        ("to" = Option<i64>, Query, description = "End of the range in seconds. Defaults to now."),
+        ("dataset" = Option<String>, Query, description = "Dataset to rebuild (e.g., depth_history, savers_history). Rebuilds every hourly dataset if not provided."),
+        ("pool" = Option<String>, Query, description = "Pool of the per pool datasets. Rebuilds every tracked pool if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Rebuilt the rollups of the range from the stored hours, returns the buckets written per dataset.", body = RollupRebuildResponse),
+        (status = 400, description = "The start of the range is missing."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "rebuildRollups"
+)]
+#[post("/rollups/rebuild")]
+pub async fn rebuild_rollups_api(
+    db: Data<Stores>,
+    query: web::Query<RollupQueryParameters>,
+) -> HttpResponse {
+    let from = match query.from {
+        Some(from) => from as f64,
+        None => return HttpResponse::BadRequest().body("The from parameter is required"),
+    };
+    let to = query
+        .to
+        .map_or(Utc::now().timestamp() as f64, |to| to as f64);
+
+    match rebuild(
+        &db,
+        query.dataset.as_deref(),
+        query.pool.as_deref(),
+        from,
+        to,
+    )
+    .await
+    {
+        Ok(rebuilt) => HttpResponse::Ok().json(RollupRebuildResponse { from, to, rebuilt }),
+        Err(e) => {
+            eprintln!("{}", e);
+            HttpResponse::InternalServerError().body("Failed to rebuild rollups")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(rebuild_rollups_api);
+}
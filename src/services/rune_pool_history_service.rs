@@ -6,39 +6,49 @@ use actix_web::{
 use chrono::Utc;
 
 use crate::{
-    models::rune_pool_history_model::{RunePoolHistoryMeta, RunePoolHistoryResponse},
-    repository::mongodb_repository::MongoDB,
-    utils::query_parameters::QueryParameters,
+    models::rune_pool_history_model::{
+        RunePoolHistory, RunePoolHistoryMeta, RunePoolHistoryResponse,
+    },
+    models::schema_drift_model::HasExtraFields,
+    repository::{bulk_repo::check_bulk_upsert, history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
+    utils::{
+        ingestion,
+        midgard_client::{fetch_history_page, FetchError, RUNE_POOL_HISTORY},
+        query_parameters::QueryParameters,
+        time_interval::TimeInterval,
+        validation::IntervalValidator,
+    },
 };
 
 pub async fn fetch_and_update_rune_pool_history(
-    db: &Data<MongoDB>,
+    db: &Data<Stores>,
     from: f64,
     count: f64,
     interval: String,
 ) -> bool {
     let url = format!(
-        "https://midgard.ninerealms.com/v2/history/runepool?interval={}&count={}&from={}",
+        "/v2/history/runepool?interval={}&count={}&from={}",
         interval, count, from
     );
 
-    match reqwest::get(&url).await {
-        Ok(response) => match response.json::<RunePoolHistoryResponse>().await {
-            Ok(resp) => {
-                for rune_pool in resp.intervals {
-                    let _ = db
-                        .rune_pool_history_repo
-                        .insert_rune_pool_history(&rune_pool)
-                        .await;
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to deserialize response: {:?}", e);
-                return false;
-            }
-        },
+    match fetch_history_page::<RunePoolHistoryMeta, RunePoolHistory>(
+        db,
+        RUNE_POOL_HISTORY,
+        None,
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            check_bulk_upsert(
+                RUNE_POOL_HISTORY,
+                db.rune_pool_history_repo.upsert(&resp.intervals).await,
+            );
+        }
         Err(e) => {
-            eprintln!("Failed to fetch data: {:?}", e);
+            eprintln!("{}", e);
             return false;
         }
     }
@@ -47,7 +57,7 @@ pub async fn fetch_and_update_rune_pool_history(
 }
 #[get("/fetch-and-insert-rune-pool")]
 pub async fn fetch_and_insert_rune_pool_history(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, _, _, _, _) = query.process_query_parameters();
@@ -63,31 +73,37 @@ pub async fn fetch_and_insert_rune_pool_history(
             break;
         }
 
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
         let url = format!(
-            "https://midgard.ninerealms.com/v2/history/runepool?interval={}&count={}&from={}",
+            "/v2/history/runepool?interval={}&count={}&from={}",
             interval.to_str(),
             count,
             from
         );
 
-        match reqwest::get(&url).await {
-            Ok(response) => match response.json::<RunePoolHistoryResponse>().await {
-                Ok(resp) => {
-                    from = resp.meta.end_time.clone();
-                    for rune_pool in resp.intervals {
-                        let _ = db
-                            .rune_pool_history_repo
-                            .insert_rune_pool_history(&rune_pool)
-                            .await;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to deserialize response: {:?}", e);
-                    return HttpResponse::InternalServerError()
-                        .body("Failed to parse rune pool data");
-                }
-            },
-            Err(e) => {
+        match fetch_history_page::<RunePoolHistoryMeta, RunePoolHistory>(
+            &db,
+            RUNE_POOL_HISTORY,
+            None,
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time.clone();
+                check_bulk_upsert(
+                    RUNE_POOL_HISTORY,
+                    db.rune_pool_history_repo.upsert(&resp.intervals).await,
+                );
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to parse rune pool data");
+            }
+            Err(FetchError::Request(e)) => {
                 eprintln!("Failed to fetch data: {:?}", e);
                 return HttpResponse::InternalServerError().body("Failed to fetch rune pool data");
             }
@@ -103,15 +119,17 @@ pub async fn fetch_and_insert_rune_pool_history(
 
 #[utoipa::path(
     get,
-    path = "/rune-pool-history",
+    path = "/{network}/rune-pool-history",
     params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
         ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
         ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
         ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
         ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
         ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
         ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., timestamp, price). Defaults to `startTime` if not provided or if the field is not present in the model."),
-        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC.")
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
     ),
     responses(
         (status = 200, description = "Successfully fetched rune pool history data.", body = Vec<RunePoolHistoryResponse>),
@@ -123,33 +141,70 @@ pub async fn fetch_and_insert_rune_pool_history(
 )]
 #[get("")]
 pub async fn rune_pool_history_api(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
 
     println!("{} {} {} {} {} {}", from, count, to, pool, sort_by, page);
 
-    let intervals = db
+    let mut intervals = db
         .rune_pool_history_repo
-        .fetch_rune_pool_history_data(from, to, count, interval, page, sort_by)
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: None,
+        })
         .await
         .unwrap_or_else(|_| vec![]);
 
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
     if intervals.len() == 0 {
         return HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.");
     } else {
         let start_record = intervals.first().unwrap();
         let end_record = intervals.last().unwrap();
 
-        let meta = RunePoolHistoryMeta {
+        let mut meta = RunePoolHistoryMeta {
             start_time: start_record.start_time,
             end_time: end_record.end_time,
             start_units: start_record.units,
             start_count: start_record.count,
             end_units: end_record.units,
             end_count: end_record.count,
+            coverage: None,
+            native_asset: None,
         };
+
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .rune_pool_history_repo
+            .fetch_start_times(start_time, end_time, None, granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
+
         let response = RunePoolHistoryResponse { meta, intervals };
 
         HttpResponse::Ok().json(response)
@@ -0,0 +1,233 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+use chrono::Utc;
+
+use crate::{
+    models::savers_history_model::{SaversHistory, SaversHistoryMeta, SaversHistoryResponse},
+    models::schema_drift_model::HasExtraFields,
+    repository::{bulk_repo::check_bulk_upsert, history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
+    utils::{
+        ingestion,
+        midgard_client::{fetch_history_page, FetchError, SAVERS_HISTORY},
+        query_parameters::QueryParameters,
+        time_interval::TimeInterval,
+        validation::IntervalValidator,
+    },
+};
+
+pub async fn fetch_and_update_savers_history(
+    db: &Data<Stores>,
+    from: f64,
+    count: f64,
+    interval: String,
+    pool: String,
+) -> bool {
+    let url = format!(
+        "/v2/history/savers/{}?interval={}&count={}&from={}",
+        pool, interval, count, from
+    );
+
+    match fetch_history_page::<SaversHistoryMeta, SaversHistory>(
+        db,
+        SAVERS_HISTORY,
+        Some(&pool),
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            let mut intervals = resp.intervals;
+
+            for savers_history in intervals.iter_mut() {
+                savers_history.pool = pool.clone();
+            }
+
+            if !check_bulk_upsert(
+                SAVERS_HISTORY,
+                db.savers_history_repo.upsert(&intervals).await,
+            ) {
+                eprintln!("Failed to insert savers history data into database");
+                return false;
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    }
+
+    true
+}
+
+#[get("/fetch-and-insert-savers")]
+pub async fn fetch_and_insert_savers_history(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, count, interval, _, _, _, pool) = query.process_query_parameters();
+
+    let mut from = from;
+
+    loop {
+        let current_time = Utc::now().timestamp() as f64;
+
+        if from >= current_time {
+            println!("Start time has reached or exceeded the current time, breaking the loop.");
+            break;
+        }
+
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
+        let url = format!(
+            "/v2/history/savers/{}?interval={}&count={}&from={}",
+            pool,
+            interval.to_str(),
+            count,
+            from
+        );
+
+        match fetch_history_page::<SaversHistoryMeta, SaversHistory>(
+            &db,
+            SAVERS_HISTORY,
+            Some(&pool),
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time;
+                let mut intervals = resp.intervals;
+
+                for savers_history in intervals.iter_mut() {
+                    savers_history.pool = pool.clone();
+                }
+
+                if !check_bulk_upsert(
+                    SAVERS_HISTORY,
+                    db.savers_history_repo.upsert(&intervals).await,
+                ) {
+                    eprintln!("Failed to insert savers history data into database");
+                    return HttpResponse::InternalServerError()
+                        .body("Failed to insert savers history data into database");
+                }
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to parse savers data");
+            }
+            Err(FetchError::Request(e)) => {
+                eprintln!("Failed to fetch data: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to fetch savers data");
+            }
+        }
+    }
+
+    HttpResponse::Ok().body("Successfully fetched and inserted savers history data into database.")
+}
+
+#[utoipa::path(
+    get,
+    path = "/{network}/savers-history",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
+        ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
+        ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
+        ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., saversDepth, saversCount). Defaults to `startTime` if not provided or if the field is not present in the model."),
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Defaults to `BTC.BTC` if not provided."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched savers history data.", body = Vec<SaversHistoryResponse>),
+        (status = 404, description = "No savers history found for the provided parameters."),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Savers History",
+    operation_id = "fetchSaversHistoryData"
+)]
+#[get("")]
+pub async fn savers_history_api(
+    db: Data<Stores>,
+    query: web::Query<QueryParameters>,
+) -> HttpResponse {
+    let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
+
+    let mut intervals = db
+        .savers_history_repo
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: Some(pool.clone()),
+        })
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
+    if intervals.is_empty() {
+        HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.")
+    } else {
+        let start_record = intervals.first().unwrap();
+        let end_record = intervals.last().unwrap();
+
+        let mut meta = SaversHistoryMeta {
+            start_time: start_record.start_time,
+            end_time: end_record.end_time,
+            start_savers_depth: start_record.savers_depth,
+            start_units: start_record.savers_units,
+            start_savers_count: start_record.savers_count,
+            end_savers_depth: end_record.savers_depth,
+            end_units: end_record.savers_units,
+            end_savers_count: end_record.savers_count,
+            coverage: None,
+            native_asset: None,
+        };
+
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .savers_history_repo
+            .fetch_start_times(start_time, end_time, Some(&pool), granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
+
+        let response = SaversHistoryResponse { meta, intervals };
+
+        HttpResponse::Ok().json(response)
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config
+        .service(fetch_and_insert_savers_history)
+        .service(savers_history_api);
+}
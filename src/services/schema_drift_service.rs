@@ -0,0 +1,47 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+
+use crate::{
+    models::schema_drift_model::SchemaDriftResponse, repository::stores::Stores,
+    utils::query_parameters::QuarantineQueryParameters,
+};
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/schema-drift",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Dataset of the fields (e.g., depth_history, swaps_history). Returns every dataset if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the upstream fields unknown to the models, most recently appeared first.", body = SchemaDriftResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchSchemaDrift"
+)]
+#[get("/schema-drift")]
+pub async fn schema_drift_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, _, _, _) = query.process_query_parameters();
+
+    match db.schema_drift_repo.fetch_schema_drift(dataset).await {
+        Ok(fields) => HttpResponse::Ok().json(SchemaDriftResponse {
+            count: fields.len(),
+            fields,
+        }),
+        Err(e) => {
+            eprintln!("Failed to fetch schema drift: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch schema drift")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(schema_drift_api);
+}
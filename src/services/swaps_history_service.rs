@@ -6,47 +6,51 @@ use actix_web::{
 use chrono::Utc;
 
 use crate::{
+    models::schema_drift_model::HasExtraFields,
     models::swaps_history_model::{SwapsHistory, SwapsHistoryMeta, SwapsHistoryResponse},
-    repository::mongodb_repository::MongoDB,
-    utils::query_parameters::QueryParameters,
+    repository::{bulk_repo::check_bulk_upsert, history_store::HistoryQuery, stores::Stores},
+    services::coverage_service::{coverage_ratio, covered_range},
+    utils::{
+        ingestion,
+        midgard_client::{fetch_history_page, FetchError, SWAPS_HISTORY},
+        query_parameters::QueryParameters,
+        time_interval::TimeInterval,
+        validation::IntervalValidator,
+    },
 };
 
 pub async fn fetch_and_update_swaps_history(
-    db: &Data<MongoDB>,
+    db: &Data<Stores>,
     from: f64,
     count: f64,
     interval: String,
     pool: String,
 ) -> bool {
     let url = format!(
-        "https://midgard.ninerealms.com/v2/history/swaps?pool={}&interval={}&count={}&from={}",
+        "/v2/history/swaps?pool={}&interval={}&count={}&from={}",
         pool, interval, count, from
     );
 
-    match reqwest::get(&url).await {
-        Ok(response) => match response.json::<SwapsHistoryResponse>().await {
-            Ok(resp) => {
-                for swaps_history in resp.intervals {
-                    match db
-                        .swaps_history_repo
-                        .insert_swaps_history(&swaps_history)
-                        .await
-                    {
-                        Ok(_) => (),
-                        Err(_) => {
-                            eprintln!("Failed to insert data into database");
-                            return false;
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to deserialize response: {:?}", e);
+    match fetch_history_page::<SwapsHistoryMeta, SwapsHistory>(
+        db,
+        SWAPS_HISTORY,
+        None,
+        &url,
+        IntervalValidator::new(TimeInterval::from_str(&interval).as_ref(), from),
+    )
+    .await
+    {
+        Ok(resp) => {
+            if !check_bulk_upsert(
+                SWAPS_HISTORY,
+                db.swaps_history_repo.upsert(&resp.intervals).await,
+            ) {
+                eprintln!("Failed to insert data into database");
                 return false;
             }
-        },
+        }
         Err(e) => {
-            eprintln!("Failed to fetch data: {:?}", e);
+            eprintln!("{}", e);
             return false;
         }
     }
@@ -56,7 +60,7 @@ pub async fn fetch_and_update_swaps_history(
 
 #[get("/fetch-and-insert-swaps")]
 pub async fn fetch_and_insert_swaps_history(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, _, _, _, pool) = query.process_query_parameters();
@@ -71,37 +75,42 @@ pub async fn fetch_and_insert_swaps_history(
             break;
         }
 
-        let url =
-            format!(
-            "https://midgard.ninerealms.com/v2/history/swaps?pool={}&interval={}&count={}&from={}",
-            pool, interval.to_str(), count, from
+        // Each page takes a slot of the executor the scheduler runs on.
+        let _permit = ingestion::permit().await;
+
+        let url = format!(
+            "/v2/history/swaps?pool={}&interval={}&count={}&from={}",
+            pool,
+            interval.to_str(),
+            count,
+            from
         );
 
-        match reqwest::get(&url).await {
-            Ok(response) => match response.json::<SwapsHistoryResponse>().await {
-                Ok(resp) => {
-                    from = resp.meta.end_time.clone();
-                    for swaps_history in resp.intervals {
-                        match db
-                            .swaps_history_repo
-                            .insert_swaps_history(&swaps_history)
-                            .await
-                        {
-                            Ok(_) => (),
-                            Err(_) => {
-                                eprintln!("Failed to insert data into database");
-                                return HttpResponse::InternalServerError()
-                                    .body("Failed to insert data into database");
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to deserialize response: {:?}", e);
-                    return HttpResponse::InternalServerError().body("Failed to parse data");
+        match fetch_history_page::<SwapsHistoryMeta, SwapsHistory>(
+            &db,
+            SWAPS_HISTORY,
+            None,
+            &url,
+            IntervalValidator::new(Some(&interval), from),
+        )
+        .await
+        {
+            Ok(resp) => {
+                from = resp.meta.end_time.clone();
+                if !check_bulk_upsert(
+                    SWAPS_HISTORY,
+                    db.swaps_history_repo.upsert(&resp.intervals).await,
+                ) {
+                    eprintln!("Failed to insert data into database");
+                    return HttpResponse::InternalServerError()
+                        .body("Failed to insert data into database");
                 }
-            },
-            Err(e) => {
+            }
+            Err(FetchError::Parse(e)) => {
+                eprintln!("Failed to deserialize response: {:?}", e);
+                return HttpResponse::InternalServerError().body("Failed to parse data");
+            }
+            Err(FetchError::Request(e)) => {
                 eprintln!("Failed to fetch data: {:?}", e);
                 return HttpResponse::InternalServerError().body("Failed to fetch data");
             }
@@ -157,20 +166,24 @@ pub async fn get_meta_information(
         synth_redeem_average_slip: end_record.synth_redeem_average_slip,
         average_slip: end_record.average_slip,
         rune_price_usd: end_record.rune_price_usd,
+        coverage: None,
+        native_asset: None,
     }
 }
 
 #[utoipa::path(
     get,
-    path = "/swaps-history",
+    path = "/{network}/swaps-history",
     params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
         ("from" = Option<f64>, Query, description = "Start time for fetching data in Unix timestamp format. Defaults to `1648771200.0` if not provided."),
         ("count" = Option<i64>, Query, description = "Number of records to fetch. Defaults to `1.0` if not provided or if the provided value is out of range (must be > 0.0 and <= 400.0)."),
         ("interval" = Option<String>, Query, description = "Time interval for the data (e.g., day, week, month,quarter,year). Defaults to `year` if not provided."),
         ("to" = Option<f64>, Query, description = "End time for fetching data in Unix timestamp format. Defaults to current time if not provided."),
         ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided."),
         ("sort_by" = Option<String>, Query, description = "Field by which to sort the results (e.g., timestamp, price). Defaults to `startTime` if not provided or if the field is not present in the model."),
-        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC.")
+        ("pool" = Option<String>, Query, description = "Asset pool to fetch data from (e.g., BTC.BTC). Currently working only with BTC.BTC."),
+        ("include_extra" = Option<bool>, Query, description = "Also return the upstream fields unknown to the model under their own keys. Defaults to `false`.")
     ),
     responses(
         (status = 200, description = "Successfully fetched swaps history data.", body = Vec<SwapsHistoryResponse>),
@@ -182,26 +195,60 @@ pub async fn get_meta_information(
 )]
 #[get("")]
 pub async fn swaps_history_api(
-    db: Data<MongoDB>,
+    db: Data<Stores>,
     query: web::Query<QueryParameters>,
 ) -> HttpResponse {
     let (from, count, interval, to, page, sort_by, pool) = query.process_query_parameters();
 
     println!("{} {} {} {} {} {}", from, count, to, pool, sort_by, page);
 
-    let intervals = db
+    let mut intervals = db
         .swaps_history_repo
-        .fetch_swaps_history_data(from, to, count, interval, page, sort_by)
+        .fetch_history(&HistoryQuery {
+            from,
+            to,
+            count,
+            interval,
+            page,
+            sort_by,
+            pool: None,
+        })
         .await
         .unwrap_or_else(|_| vec![]);
 
+    if !query.include_extra() {
+        intervals.iter_mut().for_each(HasExtraFields::clear_extra);
+    }
+
     if intervals.len() == 0 {
         return HttpResponse::Ok().body("No data available for the specified interval or the query parameters may be incorrectly specified.");
     } else {
         let start_record = intervals.first().unwrap();
         let end_record = intervals.last().unwrap();
 
-        let meta = get_meta_information(start_record, end_record).await;
+        let mut meta = get_meta_information(start_record, end_record).await;
+
+        // Served rows all come from the same granularity, the coverage is measured on it.
+        let granularity = intervals
+            .first()
+            .and_then(|interval| TimeInterval::from_str(&interval.granularity))
+            .unwrap_or(TimeInterval::Hour);
+
+        let (start_time, end_time) = covered_range(
+            intervals
+                .iter()
+                .map(|interval| (interval.start_time, interval.end_time)),
+        );
+
+        meta.native_asset = Some(db.network.native_asset.clone());
+        meta.coverage = db
+            .swaps_history_repo
+            .fetch_start_times(start_time, end_time, None, granularity)
+            .await
+            .ok()
+            .map(|start_times| {
+                coverage_ratio(start_times.len(), start_time, end_time, granularity)
+            });
 
         let response = SwapsHistoryResponse { meta, intervals };
 
@@ -0,0 +1,53 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+
+use crate::{
+    models::upstream_disagreement_model::UpstreamDisagreementsResponse, repository::stores::Stores,
+    utils::query_parameters::QuarantineQueryParameters,
+};
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/upstream-disagreements",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Dataset of the disagreements (e.g., depth_history, swaps_history). Returns every dataset if not provided."),
+        ("count" = Option<i64>, Query, description = "Number of disagreements to fetch. Defaults to `50` if not provided or if the provided value is out of range (must be > 0 and <= 400)."),
+        ("page" = Option<i64>, Query, description = "Page number for pagination. Defaults to `1` if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the interval fields two Midgard endpoints returned different values for, most recent first.", body = UpstreamDisagreementsResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchUpstreamDisagreements"
+)]
+#[get("/upstream-disagreements")]
+pub async fn upstream_disagreements_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, _, count, page) = query.process_query_parameters();
+
+    match db
+        .upstream_disagreement_repo
+        .fetch_disagreements(dataset, count, page)
+        .await
+    {
+        Ok(disagreements) => HttpResponse::Ok().json(UpstreamDisagreementsResponse {
+            count: disagreements.len(),
+            disagreements,
+        }),
+        Err(e) => {
+            eprintln!("Failed to fetch upstream disagreements: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch upstream disagreements")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(upstream_disagreements_api);
+}
@@ -0,0 +1,53 @@
+use actix_web::{
+    get,
+    web::{self, Data},
+    HttpResponse,
+};
+
+use crate::{
+    models::validation_model::ValidationMetricsResponse,
+    repository::stores::Stores,
+    utils::{query_parameters::QuarantineQueryParameters, validation::ValidationMode},
+};
+
+#[utoipa::path(
+    get,
+    path = "/{network}/admin/validation",
+    params(
+        ("network" = String, Path, description = "Network the data is cached for (e.g., mainnet, stagenet, mayachain)."),
+        ("dataset" = Option<String>, Query, description = "Dataset of the metrics (e.g., depth_history, swaps_history). Returns every dataset if not provided.")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched the validation rule violations per dataset and rule.", body = ValidationMetricsResponse),
+        (status = 500, description = "Internal server error.")
+    ),
+    tag = "Admin",
+    operation_id = "fetchValidationMetrics"
+)]
+#[get("/validation")]
+pub async fn validation_metrics_api(
+    db: Data<Stores>,
+    query: web::Query<QuarantineQueryParameters>,
+) -> HttpResponse {
+    let (dataset, _, _, _) = query.process_query_parameters();
+
+    match db
+        .validation_metrics_repo
+        .fetch_validation_metrics(dataset)
+        .await
+    {
+        Ok(metrics) => HttpResponse::Ok().json(ValidationMetricsResponse {
+            mode: ValidationMode::from_env().to_str().to_string(),
+            count: metrics.len(),
+            metrics,
+        }),
+        Err(e) => {
+            eprintln!("Failed to fetch validation metrics: {:?}", e);
+            HttpResponse::InternalServerError().body("Failed to fetch validation metrics")
+        }
+    }
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+    config.service(validation_metrics_api);
+}
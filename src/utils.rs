@@ -1,5 +1,13 @@
 pub mod api_doc;
+pub mod circuit_breaker;
 pub mod deserialize_util;
+pub mod ingestion;
+pub mod midgard_client;
+pub mod midgard_upstreams;
+pub mod migrations;
+pub mod network;
 pub mod query_parameters;
+pub mod rate_limiter;
 pub mod scheduler;
 pub mod time_interval;
+pub mod validation;
@@ -4,13 +4,33 @@ use utoipa::OpenApi;
 #[openapi(
         paths(
             crate::services::depth_history_service::depth_history_api,
+            crate::services::liquidity_changes_history_service::liquidity_changes_history_api,
             crate::services::earnings_history_service::earnings_history_api,
             crate::services::swaps_history_service::swaps_history_api,
             crate::services::rune_pool_history_service::rune_pool_history_api,
+            crate::services::savers_history_service::savers_history_api,
+            crate::services::network_history_service::network_history_api,
+            crate::services::member_positions_service::member_positions_history_api,
+            crate::services::pool_stats_service::pool_stats_api,
+            crate::services::actions_service::actions_api,
+            crate::services::actions_service::action_by_tx_id_api,
+            crate::services::quarantine_service::quarantine_api,
+            crate::services::quarantine_service::replay_quarantine_record_api,
+            crate::services::quarantine_service::replay_quarantine_api,
+            crate::services::schema_drift_service::schema_drift_api,
+            crate::services::validation_service::validation_metrics_api,
+            crate::services::coverage_service::coverage_api,
+            crate::services::coverage_service::scan_coverage_api,
+            crate::services::health_service::health_api,
+            crate::services::upstream_service::upstream_disagreements_api,
+            crate::services::index_service::indexes_api,
+            crate::services::rollup_service::rebuild_rollups_api,
         ),
         components(schemas(
             crate::models::depth_history_model::DepthHistory,
             crate::models::depth_history_model::DepthHistoryResponse,
+            crate::models::liquidity_changes_history_model::LiquidityChangesHistory,
+            crate::models::liquidity_changes_history_model::LiquidityChangesHistoryResponse,
             crate::models::swaps_history_model::SwapsHistory,
             crate::models::swaps_history_model::SwapsHistoryResponse,
             crate::models::earnings_history_model::EarningsHistory,
@@ -18,12 +38,53 @@ use utoipa::OpenApi;
             crate::models::earnings_history_model::EarningsHistoryResponse,
             crate::models::rune_pool_history_model::RunePoolHistory,
             crate::models::rune_pool_history_model::RunePoolHistoryResponse,
+            crate::models::savers_history_model::SaversHistory,
+            crate::models::savers_history_model::SaversHistoryResponse,
+            crate::models::network_history_model::NetworkHistory,
+            crate::models::network_history_model::NetworkHistoryResponse,
+            crate::models::member_positions_model::MemberPosition,
+            crate::models::member_positions_model::MemberPositionsResponse,
+            crate::models::pool_stats_model::PoolStats,
+            crate::models::pool_stats_model::PoolStatsResponse,
+            crate::models::actions_model::Action,
+            crate::models::actions_model::ActionTransaction,
+            crate::models::actions_model::ActionCoin,
+            crate::models::actions_model::ActionsResponse,
+            crate::models::quarantine_model::QuarantineKind,
+            crate::models::quarantine_model::QuarantineRecord,
+            crate::models::quarantine_model::QuarantineResponse,
+            crate::models::quarantine_model::QuarantineReplayResponse,
+            crate::models::schema_drift_model::SchemaDriftField,
+            crate::models::schema_drift_model::SchemaDriftResponse,
+            crate::models::validation_model::ValidationMetric,
+            crate::models::validation_model::ValidationMetricsResponse,
+            crate::models::coverage_model::GapStatus,
+            crate::models::coverage_model::CoverageGap,
+            crate::models::coverage_model::CoverageResponse,
+            crate::models::health_model::CircuitBreakerStatus,
+            crate::models::health_model::UpstreamStatus,
+            crate::models::health_model::HealthResponse,
+            crate::models::upstream_disagreement_model::UpstreamDisagreement,
+            crate::models::upstream_disagreement_model::UpstreamDisagreementsResponse,
+            crate::models::index_model::IndexProblem,
+            crate::models::index_model::IndexUsage,
+            crate::models::index_model::IndexReportResponse,
+            crate::models::rollup_model::RollupRebuild,
+            crate::models::rollup_model::RollupRebuildResponse,
         )),
         tags(
             (name = "Depth and Price History", description = "Returns the asset and rune depths and price. The values report the state at the end of each interval."),
+            (name = "Liquidity Changes History", description = "Returns liquidity adds, withdrawals and net flow of a pool. The values are summed over each interval."),
             (name = "Earnings History", description = "Returns earnings data for the specified interval."),
             (name = "Swaps History", description = "Returns swap count, volume, fees, slip in specified interval. If pool is not specified returns for all pools"),
             (name = "RUNEPool total members and units History", description = "Returns RUNEPool members and units. The values report the state at the end of each interval."),
+            (name = "Network History", description = "Returns hourly snapshots of the network bonding APY, total bond and node counts. The values report the state at the end of each interval."),
+            (name = "Member Positions History", description = "Returns the liquidity positions of a watched address with their value from the pool depth. The values report the state at the end of each interval."),
+            (name = "Pool Stats History", description = "Returns hourly snapshots of the period stats Midgard computes for a pool (APY, swap counts, unique members, fees). The values report the state at the end of each interval."),
+            (name = "Actions", description = "Returns the swaps, liquidity adds and withdrawals ingested from Midgard, newest first."),
+            (name = "Savers History", description = "Returns savers depth, units and count of a pool. The values report the state at the end of each interval."),
+            (name = "Admin", description = "Maintenance endpoints. Lists the upstream records that failed to parse and replays them after a model update, reports the upstream fields the models don't know about yet counts the intervals that broke a validation rule, lists the gaps of the hourly datasets, the fields two Midgard endpoints disagree on and the missing or unused database indexes, and rebuilds the day to year rollups of a range."),
+            (name = "Health", description = "Returns the service status of a network and the state of every one of its Midgard endpoints: its circuit breaker, which pauses calls while it is failing, and how far it has aggregated."),
         )
     )]
 pub struct ApiDoc;
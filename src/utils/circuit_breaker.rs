@@ -0,0 +1,204 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+
+use crate::models::health_model::CircuitBreakerStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+struct BreakerInner {
+    consecutive_failures: u32,
+    opened_at: Option<(Instant, f64)>,
+    trial_in_flight: bool,
+}
+
+// Stops calls to an upstream after `failure_threshold` failures in a row. Once `cooldown` has
+// passed a single trial call is let through, it closes the breaker again if it succeeds.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold,
+            cooldown,
+            inner: Mutex::new(BreakerInner {
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    fn state_of(&self, inner: &BreakerInner) -> BreakerState {
+        match inner.opened_at {
+            None => BreakerState::Closed,
+            Some((opened_at, _)) if opened_at.elapsed() < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state_of(&self.inner.lock().unwrap())
+    }
+
+    // Whether a call may go out now, taking the trial slot when half open.
+    pub fn allow(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        match self.state_of(&inner) {
+            BreakerState::Closed => true,
+            BreakerState::Open => false,
+            BreakerState::HalfOpen if inner.trial_in_flight => false,
+            BreakerState::HalfOpen => {
+                inner.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.trial_in_flight = false;
+    }
+
+    // Gives the trial slot back when the call never reached the upstream, so it says nothing about
+    // its health.
+    pub fn release(&self) {
+        self.inner.lock().unwrap().trial_in_flight = false;
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.consecutive_failures += 1;
+
+        // A failed trial opens the breaker for another cooldown.
+        if inner.opened_at.is_some() || inner.consecutive_failures >= self.failure_threshold {
+            inner.opened_at = Some((Instant::now(), Utc::now().timestamp() as f64));
+            inner.trial_in_flight = false;
+        }
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let inner = self.inner.lock().unwrap();
+        let state = self.state_of(&inner);
+
+        let opened_at = inner.opened_at.map(|(_, opened_at)| opened_at);
+
+        CircuitBreakerStatus {
+            state: state.to_str().to_string(),
+            consecutive_failures: inner.consecutive_failures,
+            opened_at,
+            retry_at: match state {
+                BreakerState::Open => {
+                    opened_at.map(|opened_at| opened_at + self.cooldown.as_secs_f64())
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_the_failure_threshold() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert!(breaker.allow());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow());
+        assert!(breaker.status().retry_at.is_some());
+    }
+
+    #[test]
+    fn successes_reset_the_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn lets_a_single_trial_through_once_half_open() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn a_successful_trial_closes_it() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert!(breaker.allow());
+        breaker.record_success();
+
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn a_failed_trial_opens_it_again() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(20));
+
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.allow());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+    }
+
+    #[test]
+    fn release_gives_the_trial_back() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert!(breaker.allow());
+        breaker.release();
+
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.allow());
+    }
+}
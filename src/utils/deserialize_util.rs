@@ -25,3 +25,28 @@ where
         _ => Err(serde::de::Error::custom("Expected a string or number")),
     }
 }
+
+// Same as above but keeps integer precision, used for nanosecond timestamps which don't fit in f64
+pub fn deserialize_string_to_i64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value: Value = Value::deserialize(deserializer)?;
+
+    match value {
+        Value::String(s) => s.parse::<i64>().map_err(serde::de::Error::custom),
+        Value::Number(num) => num
+            .as_i64()
+            .ok_or_else(|| serde::de::Error::custom("Expected a valid integer")),
+        _ => Err(serde::de::Error::custom("Expected a string or number")),
+    }
+}
+
+pub fn default_true() -> bool {
+    true
+}
+
+// Records stored before the granularity was recorded were all fetched by the hourly scheduler.
+pub fn default_granularity() -> String {
+    String::from("hour")
+}
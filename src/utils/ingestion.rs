@@ -0,0 +1,32 @@
+use std::{env, future::Future, sync::OnceLock};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+static PERMITS: OnceLock<Semaphore> = OnceLock::new();
+
+// Ingestion jobs running at once, read from `INGESTION_CONCURRENCY`.
+pub fn concurrency() -> usize {
+    env::var("INGESTION_CONCURRENCY")
+        .ok()
+        .and_then(|concurrency| concurrency.parse::<usize>().ok())
+        .filter(|concurrency| *concurrency > 0)
+        .unwrap_or(DEFAULT_CONCURRENCY)
+}
+
+// A slot of the ingestion executor, shared by the scheduler and the backfill handlers. Jobs hold
+// one while they fetch and write, they must not take a second one or they could wait on themselves.
+pub async fn permit() -> SemaphorePermit<'static> {
+    PERMITS
+        .get_or_init(|| Semaphore::new(concurrency()))
+        .acquire()
+        .await
+        .expect("the ingestion semaphore is never closed")
+}
+
+pub async fn run<F: Future>(job: F) -> F::Output {
+    let _permit = permit().await;
+
+    job.await
+}
@@ -0,0 +1,772 @@
+use std::{
+    env,
+    sync::{atomic::Ordering, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::time::sleep;
+
+use crate::{
+    models::{
+        quarantine_model::{QuarantineKind, QuarantineRecord},
+        schema_drift_model::HasExtraFields,
+        upstream_disagreement_model::UpstreamDisagreement,
+    },
+    repository::stores::Stores,
+};
+
+use super::circuit_breaker::BreakerState;
+use super::midgard_upstreams::{json_number, preferred_upstreams, upstream_of, Upstream};
+use super::network::Network;
+use super::time_interval::TimeInterval;
+use super::validation::{IntervalValidator, Validate, ValidationMode, Violation};
+
+// Dataset names used to tag quarantined records, so they can be replayed into the right repository.
+pub const DEPTH_HISTORY: &str = "depth_history";
+pub const SWAPS_HISTORY: &str = "swaps_history";
+pub const EARNINGS_HISTORY: &str = "earnings_history";
+pub const RUNE_POOL_HISTORY: &str = "rune_pool_history";
+pub const SAVERS_HISTORY: &str = "savers_history";
+pub const LIQUIDITY_CHANGES_HISTORY: &str = "liquidity_changes_history";
+
+// Dataset names of the snapshots, only used to report their unknown fields.
+pub const NETWORK_HISTORY: &str = "network_history";
+pub const POOL_STATS: &str = "pool_stats";
+pub const MEMBER_POSITIONS: &str = "member_positions";
+
+// Requests let through per second once the burst is spent, and the burst, read from
+// `MIDGARD_REQUESTS_PER_SECOND` and `MIDGARD_BURST`. The defaults are meant for the public Nine
+// Realms endpoint, a private Midgard can take more. Each network has its own limit.
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 5.0;
+pub const DEFAULT_BURST: f64 = 10.0;
+
+// Per request timeout and retries of a failed request, from `MIDGARD_TIMEOUT_SECS` and
+// `MIDGARD_MAX_RETRIES`.
+const DEFAULT_TIMEOUT_SECS: f64 = 30.0;
+const DEFAULT_MAX_RETRIES: f64 = 3.0;
+
+// Backoff of the first retry, doubled on each of the next ones, and the longest wait between two
+// attempts, `Retry-After` included.
+const BASE_BACKOFF_SECS: f64 = 0.5;
+const MAX_BACKOFF_SECS: f64 = 60.0;
+
+// Every how many history pages one is fetched again from a second endpoint and compared, from
+// `MIDGARD_CROSS_CHECK_EVERY`. Off unless set.
+const DEFAULT_CROSS_CHECK_EVERY: f64 = 0.0;
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+pub fn env_number(key: &str, default: f64) -> f64 {
+    env::var(key)
+        .ok()
+        .and_then(|number| number.parse::<f64>().ok())
+        .filter(|number| *number >= 0.0)
+        .unwrap_or(default)
+}
+
+#[derive(Debug)]
+pub enum UpstreamError {
+    // The breakers of every endpoint are open, the request wasn't sent.
+    CircuitOpen,
+    // Midgard kept answering with this status until the retries ran out.
+    Status(u16),
+    Request(reqwest::Error),
+}
+
+impl std::fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpstreamError::CircuitOpen => write!(f, "Midgard circuit breakers are open"),
+            UpstreamError::Status(status) => write!(f, "Midgard answered with status {}", status),
+            UpstreamError::Request(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// Full jitter, a random wait up to the exponential backoff of the attempt.
+fn backoff(attempt: u32) -> Duration {
+    let jitter = f64::from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos(),
+    ) / 1e9;
+
+    let backoff = BASE_BACKOFF_SECS * 2f64.powi(attempt as i32 - 1);
+
+    Duration::from_secs_f64((backoff * jitter).min(MAX_BACKOFF_SECS))
+}
+
+// `Retry-After` as a number of seconds or an HTTP date.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    parse_retry_after(
+        response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?,
+    )
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let seconds = match value.trim().parse::<f64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+            (date.timestamp() - Utc::now().timestamp()) as f64
+        }
+    };
+
+    Some(Duration::from_secs_f64(
+        seconds.clamp(0.0, MAX_BACKOFF_SECS),
+    ))
+}
+
+// One rate limited attempt, without retries nor breaker.
+pub async fn send_once(network: &Network, url: &str) -> reqwest::Result<reqwest::Response> {
+    network.rate_limiter.acquire().await;
+
+    HTTP_CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs_f64(env_number(
+                    "MIDGARD_TIMEOUT_SECS",
+                    DEFAULT_TIMEOUT_SECS,
+                )))
+                .build()
+                .unwrap()
+        })
+        .get(url)
+        .send()
+        .await
+}
+
+// Rate limited and server errors are worth another try, other statuses are returned as is.
+fn is_retried_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// Timeouts and connection errors may not happen again, other errors would.
+fn is_retried_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect()
+}
+
+// Rate limits, server errors, timeouts and connection errors are retried, other statuses are
+// returned as is. The outcome is recorded on the breaker of the endpoint, unless the request
+// couldn't even be built.
+async fn send_with_retries(
+    network: &Network,
+    upstream: &Upstream,
+    path: &str,
+) -> Result<reqwest::Response, UpstreamError> {
+    let url = upstream.url(path);
+    let max_retries = env_number("MIDGARD_MAX_RETRIES", DEFAULT_MAX_RETRIES) as u32;
+    let mut attempt = 0;
+
+    loop {
+        let (error, wait) = match send_once(network, &url).await {
+            Ok(response) if is_retried_status(response.status()) => (
+                UpstreamError::Status(response.status().as_u16()),
+                retry_after(&response),
+            ),
+            Ok(response) => {
+                upstream.breaker.record_success();
+                return Ok(response);
+            }
+            Err(e) if is_retried_error(&e) => (UpstreamError::Request(e), None),
+            Err(e) if e.is_builder() => {
+                upstream.breaker.release();
+                return Err(UpstreamError::Request(e));
+            }
+            Err(e) => {
+                upstream.breaker.record_failure();
+                return Err(UpstreamError::Request(e));
+            }
+        };
+
+        attempt += 1;
+
+        if attempt > max_retries {
+            upstream.breaker.record_failure();
+            return Err(error);
+        }
+
+        let wait = wait.unwrap_or_else(|| backoff(attempt));
+
+        eprintln!(
+            "Retrying {} in {:.1}s ({} of {}): {}",
+            url,
+            wait.as_secs_f64(),
+            attempt,
+            max_retries,
+            error
+        );
+
+        sleep(wait).await;
+    }
+}
+
+// Every Midgard request goes through here with a path (e.g. `/v2/network`), so concurrent jobs of
+// a network share its rate limit and breakers. Endpoints are tried in order of preference, the
+// next one is used once the retries on one ran out or its breaker is open.
+pub async fn midgard_get(
+    network: &Network,
+    path: &str,
+) -> Result<reqwest::Response, UpstreamError> {
+    let mut last_error = UpstreamError::CircuitOpen;
+
+    for upstream in preferred_upstreams(network) {
+        if !upstream.breaker.allow() {
+            continue;
+        }
+
+        match send_with_retries(network, upstream, path).await {
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                eprintln!("Midgard {} failed on {}: {}", upstream.base_url, path, e);
+                last_error = e;
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    // Midgard couldn't be reached or the body couldn't be read.
+    Request(String),
+    // The page couldn't be parsed, it has been quarantined.
+    Parse(String),
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Request(e) => write!(f, "Failed to fetch data: {}", e),
+            FetchError::Parse(e) => write!(f, "Failed to deserialize response: {}", e),
+        }
+    }
+}
+
+// A Midgard history page. Intervals that failed to parse are quarantined and left out.
+pub struct HistoryPage<M, I> {
+    pub meta: M,
+    pub intervals: Vec<I>,
+}
+
+pub async fn quarantine(
+    db: &Stores,
+    dataset: &str,
+    kind: QuarantineKind,
+    pool: Option<&str>,
+    source_url: &str,
+    raw: String,
+    error: String,
+) {
+    let record = QuarantineRecord {
+        id: None,
+        dataset: dataset.to_string(),
+        kind,
+        pool: pool.map(str::to_string),
+        source_url: source_url.to_string(),
+        error,
+        raw,
+        quarantined_at: Utc::now().timestamp() as f64,
+        replayed_at: None,
+    };
+
+    if let Err(e) = db.quarantine_repo.insert_quarantine_record(&record).await {
+        eprintln!("Failed to quarantine {} record: {:?}", dataset, e);
+    }
+}
+
+// Parses a history page body interval by interval, so one bad interval doesn't drop the others,
+// then checks the parsed intervals against the validation rules.
+pub async fn parse_history_page<M, I>(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    source_url: &str,
+    body: String,
+    mut validator: IntervalValidator,
+) -> Result<HistoryPage<M, I>, FetchError>
+where
+    M: DeserializeOwned,
+    I: DeserializeOwned + HasExtraFields + Validate,
+{
+    let page = serde_json::from_str::<Value>(&body)
+        .map_err(|e| e.to_string())
+        .and_then(|value| {
+            let meta = value.get("meta").cloned().ok_or("missing field `meta`")?;
+            let meta = serde_json::from_value::<M>(meta).map_err(|e| e.to_string())?;
+
+            match value.get("intervals") {
+                Some(Value::Array(intervals)) => Ok((meta, intervals.clone())),
+                _ => Err(String::from("missing field `intervals`")),
+            }
+        });
+
+    let (meta, raw_intervals) = match page {
+        Ok(page) => page,
+        Err(e) => {
+            quarantine(
+                db,
+                dataset,
+                QuarantineKind::Page,
+                pool,
+                source_url,
+                body,
+                e.clone(),
+            )
+            .await;
+            return Err(FetchError::Parse(e));
+        }
+    };
+
+    let mut intervals = Vec::with_capacity(raw_intervals.len());
+    let fetched_at = Utc::now().timestamp() as f64;
+
+    for mut raw_interval in raw_intervals {
+        set_is_complete(&mut raw_interval, fetched_at);
+
+        if let Some(granularity) = validator.granularity() {
+            set_granularity(&mut raw_interval, granularity);
+        }
+
+        match serde_json::from_value::<I>(raw_interval.clone()) {
+            Ok(interval) => intervals.push((raw_interval, interval)),
+            Err(e) => {
+                eprintln!("Quarantining {} interval: {:?}", dataset, e);
+                quarantine(
+                    db,
+                    dataset,
+                    QuarantineKind::Interval,
+                    pool,
+                    source_url,
+                    raw_interval.to_string(),
+                    e.to_string(),
+                )
+                .await;
+            }
+        }
+    }
+
+    record_schema_drift(
+        db,
+        dataset,
+        source_url,
+        intervals.iter().map(|(_, interval)| interval),
+    )
+    .await;
+
+    let mut valid_intervals = Vec::with_capacity(intervals.len());
+
+    for (raw_interval, interval) in intervals {
+        let violations = validator.validate(&interval);
+
+        if violations.is_empty() {
+            valid_intervals.push(interval);
+            continue;
+        }
+
+        let rejected = validator.mode == ValidationMode::Reject;
+        let error = violations
+            .iter()
+            .map(Violation::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        record_violations(db, dataset, &violations, rejected).await;
+
+        if rejected {
+            eprintln!("Quarantining invalid {} interval: {}", dataset, error);
+            quarantine(
+                db,
+                dataset,
+                QuarantineKind::Invalid,
+                pool,
+                source_url,
+                raw_interval.to_string(),
+                error,
+            )
+            .await;
+        } else {
+            eprintln!("Inserting invalid {} interval: {}", dataset, error);
+            valid_intervals.push(interval);
+        }
+    }
+
+    Ok(HistoryPage {
+        meta,
+        intervals: valid_intervals,
+    })
+}
+
+async fn record_violations(db: &Stores, dataset: &str, violations: &[Violation], rejected: bool) {
+    let seen_at = Utc::now().timestamp() as f64;
+
+    for violation in violations {
+        if let Err(e) = db
+            .validation_metrics_repo
+            .record_violation(
+                dataset,
+                violation.rule,
+                rejected,
+                &violation.message,
+                seen_at,
+            )
+            .await
+        {
+            eprintln!("Failed to record {} validation metric: {:?}", dataset, e);
+        }
+    }
+}
+
+// Midgard returns the interval in progress along with the others, its `endTime` is still ahead of
+// the fetch time. It is flagged so the scheduler fetches it again once it's over.
+fn set_is_complete(raw_interval: &mut Value, fetched_at: f64) {
+    let end_time = match raw_interval.get("endTime") {
+        Some(Value::String(end_time)) => end_time.parse::<f64>().ok(),
+        Some(end_time) => end_time.as_f64(),
+        None => None,
+    };
+
+    if let (Some(end_time), Value::Object(fields)) = (end_time, raw_interval) {
+        fields.insert(
+            String::from("isComplete"),
+            Value::Bool(end_time <= fetched_at),
+        );
+    }
+}
+
+// Midgard doesn't repeat the requested interval on its intervals, it is added so rows of different
+// granularities stored side by side can be told apart.
+pub fn set_granularity(raw_interval: &mut Value, granularity: &str) {
+    if let Value::Object(fields) = raw_interval {
+        fields
+            .entry("granularity")
+            .or_insert_with(|| Value::String(granularity.to_string()));
+    }
+}
+
+// Interval a Midgard history url was requested at, from its `interval` query parameter.
+pub fn url_granularity(source_url: &str) -> Option<TimeInterval> {
+    let (_, query) = source_url.split_once('?')?;
+
+    query
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("interval="))
+        .and_then(TimeInterval::from_str)
+}
+
+// Records the unknown fields of the parsed intervals, so new Midgard metrics show up in the drift
+// report instead of only in the raw documents.
+async fn record_schema_drift<'a, I: HasExtraFields + 'a>(
+    db: &Stores,
+    dataset: &str,
+    source_url: &str,
+    intervals: impl IntoIterator<Item = &'a I>,
+) {
+    let mut fields: Vec<String> = vec![];
+
+    for interval in intervals {
+        for field in interval.extra_field_names() {
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+    }
+
+    let seen_at = Utc::now().timestamp() as f64;
+
+    for field in fields {
+        if let Err(e) = db
+            .schema_drift_repo
+            .record_field(dataset, &field, source_url, seen_at)
+            .await
+        {
+            eprintln!("Failed to record {} schema drift: {:?}", dataset, e);
+        }
+    }
+}
+
+// Parses a snapshot response, recording its unknown fields like those of the history intervals.
+pub async fn parse_snapshot<T: DeserializeOwned + HasExtraFields>(
+    db: &Stores,
+    dataset: &str,
+    response: reqwest::Response,
+) -> Result<T, reqwest::Error> {
+    let source_url = response.url().to_string();
+    let snapshot = response.json::<T>().await?;
+
+    record_schema_drift(db, dataset, &source_url, [&snapshot]).await;
+
+    Ok(snapshot)
+}
+
+pub async fn fetch_history_page<M, I>(
+    db: &Stores,
+    dataset: &str,
+    pool: Option<&str>,
+    path: &str,
+    validator: IntervalValidator,
+) -> Result<HistoryPage<M, I>, FetchError>
+where
+    M: DeserializeOwned,
+    I: DeserializeOwned + HasExtraFields + Validate,
+{
+    let (source_url, body) = match midgard_get(&db.network, path).await {
+        Ok(response) => {
+            let source_url = response.url().to_string();
+            let body = response
+                .text()
+                .await
+                .map_err(|e| FetchError::Request(format!("{:?}", e)))?;
+
+            (source_url, body)
+        }
+        Err(e) => return Err(FetchError::Request(e.to_string())),
+    };
+
+    if is_cross_check_page(&db.network) {
+        cross_check(db, dataset, path, &source_url, &body).await;
+    }
+
+    parse_history_page(db, dataset, pool, &source_url, body, validator).await
+}
+
+fn is_cross_check_page(network: &Network) -> bool {
+    let every = env_number("MIDGARD_CROSS_CHECK_EVERY", DEFAULT_CROSS_CHECK_EVERY) as u64;
+
+    every > 0
+        && network
+            .fetched_pages
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(every)
+}
+
+// Fetches a page again from another endpoint and records the fields of the complete intervals the
+// two endpoints disagree on. Intervals still in progress differ by nature and are skipped.
+async fn cross_check(db: &Stores, dataset: &str, path: &str, source_url: &str, body: &str) {
+    let primary = upstream_of(&db.network, source_url);
+
+    let secondary = match preferred_upstreams(&db.network)
+        .into_iter()
+        .find(|upstream| {
+            !primary.is_some_and(|primary| std::ptr::eq(primary, *upstream))
+                && upstream.breaker.state() == BreakerState::Closed
+        }) {
+        Some(secondary) => secondary,
+        None => return,
+    };
+
+    let secondary_url = secondary.url(path);
+
+    let secondary_body = match send_once(&db.network, &secondary_url).await {
+        Ok(response) if response.status().is_success() => response.text().await.ok(),
+        _ => None,
+    };
+
+    let (primary_page, secondary_page) = match secondary_body.and_then(|secondary_body| {
+        Some((
+            serde_json::from_str::<Value>(body).ok()?,
+            serde_json::from_str::<Value>(&secondary_body).ok()?,
+        ))
+    }) {
+        Some(pages) => pages,
+        None => {
+            eprintln!(
+                "Failed to cross check {} against {}",
+                source_url, secondary_url
+            );
+            return;
+        }
+    };
+
+    let empty = vec![];
+    let secondary_intervals = secondary_page["intervals"].as_array().unwrap_or(&empty);
+    let now = Utc::now().timestamp() as f64;
+    let mut disagreements = vec![];
+
+    for interval in primary_page["intervals"].as_array().unwrap_or(&empty) {
+        let start_time = match (
+            json_number(&interval["startTime"]),
+            json_number(&interval["endTime"]),
+        ) {
+            (Some(start_time), Some(end_time)) if end_time <= now => start_time,
+            _ => continue,
+        };
+
+        let other = match secondary_intervals
+            .iter()
+            .find(|other| json_number(&other["startTime"]) == Some(start_time))
+        {
+            Some(other) => other,
+            None => continue,
+        };
+
+        for (field, value) in interval.as_object().into_iter().flatten() {
+            let other_value = other.get(field).unwrap_or(&Value::Null);
+
+            if value != other_value {
+                disagreements.push(UpstreamDisagreement {
+                    id: None,
+                    dataset: dataset.to_string(),
+                    start_time,
+                    field: field.clone(),
+                    primary_url: source_url.to_string(),
+                    primary_value: value.to_string(),
+                    secondary_url: secondary_url.clone(),
+                    secondary_value: other_value.to_string(),
+                    detected_at: now,
+                });
+            }
+        }
+    }
+
+    if disagreements.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} {} fields disagree between {} and {}",
+        disagreements.len(),
+        dataset,
+        source_url,
+        secondary_url
+    );
+
+    if let Err(e) = db
+        .upstream_disagreement_repo
+        .insert_disagreements(&disagreements)
+        .await
+    {
+        eprintln!("Failed to record {} disagreements: {:?}", dataset, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, sync::atomic::AtomicU64};
+
+    use super::super::{
+        circuit_breaker::CircuitBreaker, midgard_upstreams::parse_upstreams,
+        rate_limiter::TokenBucket,
+    };
+    use super::*;
+
+    fn network(urls: &str) -> Network {
+        Network {
+            name: String::from("test"),
+            native_asset: String::from("RUNE"),
+            database: String::from("test"),
+            default_pool: String::from("BTC.BTC"),
+            upstreams: parse_upstreams(urls),
+            rate_limiter: TokenBucket::new(10.0, 10.0),
+            fetched_pages: AtomicU64::new(0),
+        }
+    }
+
+    #[test]
+    fn retry_after_reads_seconds() {
+        assert_eq!(parse_retry_after(" 3 "), Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn retry_after_reads_http_dates() {
+        let date = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let wait = parse_retry_after(&date).unwrap();
+
+        assert!(wait > Duration::from_secs(28) && wait <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retry_after_is_clamped() {
+        assert_eq!(
+            parse_retry_after("3600"),
+            Some(Duration::from_secs_f64(MAX_BACKOFF_SECS))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_ignores_invalid_values() {
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn backoff_stays_under_its_exponential_bound() {
+        for attempt in 1..20 {
+            let bound = (BASE_BACKOFF_SECS * 2f64.powi(attempt - 1)).min(MAX_BACKOFF_SECS);
+
+            assert!(backoff(attempt as u32).as_secs_f64() <= bound);
+        }
+    }
+
+    #[test]
+    fn rate_limits_and_server_errors_are_retried() {
+        assert!(is_retried_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retried_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retried_status(StatusCode::NOT_FOUND));
+        assert!(!is_retried_status(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn connection_errors_are_retried() {
+        // A port nothing listens on anymore.
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let e = reqwest::get(format!("http://{}", address))
+            .await
+            .unwrap_err();
+
+        assert!(is_retried_error(&e));
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_retried() {
+        // Accepts the connection but never answers.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let e = reqwest::Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap()
+            .get(format!("http://{}", listener.local_addr().unwrap()))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(is_retried_error(&e));
+    }
+
+    #[tokio::test]
+    async fn requests_that_cant_be_built_leave_the_breaker_alone() {
+        let mut network = network("http://[invalid");
+        network.upstreams[0].breaker = CircuitBreaker::new(1, Duration::ZERO);
+
+        let upstream = &network.upstreams[0];
+        upstream.breaker.record_failure();
+        assert!(upstream.breaker.allow());
+
+        let e = send_with_retries(&network, upstream, "/v2/health")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(e, UpstreamError::Request(ref e) if e.is_builder()));
+        assert_eq!(upstream.breaker.status().consecutive_failures, 1);
+        // The trial slot was given back for the next call.
+        assert!(upstream.breaker.allow());
+    }
+}
@@ -0,0 +1,271 @@
+use std::{sync::Mutex, time::Duration};
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::models::health_model::UpstreamStatus;
+
+use super::{
+    circuit_breaker::{BreakerState, CircuitBreaker},
+    midgard_client::{env_number, send_once},
+    network::Network,
+};
+
+// Failed requests in a row that open the breaker of an endpoint, and how long it stays open, from
+// `MIDGARD_BREAKER_THRESHOLD` and `MIDGARD_BREAKER_COOLDOWN_SECS`.
+const DEFAULT_BREAKER_THRESHOLD: f64 = 5.0;
+const DEFAULT_BREAKER_COOLDOWN_SECS: f64 = 60.0;
+
+// How far an endpoint may fall behind the most advanced one before it is lagging, in blocks and in
+// seconds, from `MIDGARD_MAX_LAG_BLOCKS` and `MIDGARD_MAX_LAG_SECS`.
+const DEFAULT_MAX_LAG_BLOCKS: f64 = 50.0;
+const DEFAULT_MAX_LAG_SECS: f64 = 300.0;
+
+const HEALTH_PATH: &str = "/v2/health";
+// Midgard ends a history window at its last aggregated block, so the `meta.endTime` of the
+// latest 5 minute interval of the default pool shows how far an endpoint has got.
+fn probe_path(network: &Network) -> String {
+    format!(
+        "/v2/history/depths/{}?interval=5min&count=1",
+        network.default_pool
+    )
+}
+
+#[derive(Default)]
+struct Probe {
+    last_aggregated_height: Option<i64>,
+    last_end_time: Option<f64>,
+    lagging: bool,
+    checked_at: Option<f64>,
+}
+
+pub struct Upstream {
+    pub base_url: String,
+    pub priority: u32,
+    pub breaker: CircuitBreaker,
+    probe: Mutex<Probe>,
+}
+
+impl Upstream {
+    pub fn is_lagging(&self) -> bool {
+        self.probe.lock().unwrap().lagging
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub fn status(&self) -> UpstreamStatus {
+        let probe = self.probe.lock().unwrap();
+
+        UpstreamStatus {
+            url: self.base_url.clone(),
+            priority: self.priority,
+            lagging: probe.lagging,
+            last_aggregated_height: probe.last_aggregated_height,
+            last_end_time: probe.last_end_time,
+            checked_at: probe.checked_at,
+            breaker: self.breaker.status(),
+        }
+    }
+}
+
+fn new_upstream(base_url: &str, priority: u32) -> Upstream {
+    Upstream {
+        base_url: base_url.trim().trim_end_matches('/').to_string(),
+        priority,
+        breaker: CircuitBreaker::new(
+            env_number("MIDGARD_BREAKER_THRESHOLD", DEFAULT_BREAKER_THRESHOLD).max(1.0) as u32,
+            Duration::from_secs_f64(env_number(
+                "MIDGARD_BREAKER_COOLDOWN_SECS",
+                DEFAULT_BREAKER_COOLDOWN_SECS,
+            )),
+        ),
+        probe: Mutex::new(Probe::default()),
+    }
+}
+
+// Endpoints from a comma separated list of urls, each one optionally followed by `|priority`.
+// Without a priority an endpoint ranks by its position in the list.
+pub fn parse_upstreams(urls: &str) -> Vec<Upstream> {
+    let mut upstreams: Vec<Upstream> = urls
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .enumerate()
+        .map(|(position, url)| match url.split_once('|') {
+            Some((base_url, priority)) => new_upstream(
+                base_url,
+                priority.trim().parse::<u32>().unwrap_or(position as u32),
+            ),
+            None => new_upstream(url, position as u32),
+        })
+        .collect();
+
+    upstreams.sort_by_key(|upstream| upstream.priority);
+    upstreams
+}
+
+// Endpoints in the order they are tried, the ones in sync first then by priority.
+pub fn preferred_upstreams(network: &Network) -> Vec<&Upstream> {
+    let mut upstreams: Vec<&Upstream> = network.upstreams.iter().collect();
+
+    upstreams.sort_by_key(|upstream| (upstream.is_lagging(), upstream.priority));
+    upstreams
+}
+
+// Whether every endpoint is refusing calls, ingestion has nothing to call then.
+pub fn all_open(network: &Network) -> bool {
+    network
+        .upstreams
+        .iter()
+        .all(|upstream| upstream.breaker.state() == BreakerState::Open)
+}
+
+// The endpoint a url was fetched from.
+pub fn upstream_of<'a>(network: &'a Network, url: &str) -> Option<&'a Upstream> {
+    network
+        .upstreams
+        .iter()
+        .find(|upstream| url.starts_with(&upstream.base_url))
+}
+
+pub fn json_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(number) => number.parse::<f64>().ok(),
+        number => number.as_f64(),
+    }
+}
+
+async fn fetch_json(network: &Network, url: &str) -> Option<Value> {
+    match send_once(network, url).await {
+        Ok(response) if response.status().is_success() => response.json::<Value>().await.ok(),
+        Ok(response) => {
+            eprintln!("Failed to probe {}: status {}", url, response.status());
+            None
+        }
+        Err(e) => {
+            eprintln!("Failed to probe {}: {:?}", url, e);
+            None
+        }
+    }
+}
+
+// Reads the last aggregated height and the latest `meta.endTime` of every endpoint, then flags the
+// ones too far behind the most advanced. An endpoint that can't be probed keeps its last flag, its
+// breaker takes care of it.
+pub async fn check_upstreams(network: &Network) {
+    let probe_path = probe_path(network);
+
+    for upstream in &network.upstreams {
+        let height = fetch_json(network, &upstream.url(HEALTH_PATH))
+            .await
+            .and_then(|health| json_number(&health["lastAggregated"]["height"]));
+        let end_time = fetch_json(network, &upstream.url(&probe_path))
+            .await
+            .and_then(|page| json_number(&page["meta"]["endTime"]));
+
+        let mut probe = upstream.probe.lock().unwrap();
+        probe.last_aggregated_height = height
+            .map(|height| height as i64)
+            .or(probe.last_aggregated_height);
+        probe.last_end_time = end_time.or(probe.last_end_time);
+        probe.checked_at = Some(Utc::now().timestamp() as f64);
+    }
+
+    let max_lag_blocks = env_number("MIDGARD_MAX_LAG_BLOCKS", DEFAULT_MAX_LAG_BLOCKS) as i64;
+    let max_lag_secs = env_number("MIDGARD_MAX_LAG_SECS", DEFAULT_MAX_LAG_SECS);
+
+    let statuses: Vec<UpstreamStatus> = network.upstreams.iter().map(Upstream::status).collect();
+
+    let max_height = statuses
+        .iter()
+        .filter_map(|status| status.last_aggregated_height)
+        .max();
+    let max_end_time = statuses
+        .iter()
+        .filter_map(|status| status.last_end_time)
+        .fold(None, |max: Option<f64>, end_time| {
+            Some(max.map_or(end_time, |max| max.max(end_time)))
+        });
+
+    for upstream in &network.upstreams {
+        let mut probe = upstream.probe.lock().unwrap();
+
+        let behind_height = match (probe.last_aggregated_height, max_height) {
+            (Some(height), Some(max_height)) => max_height - height > max_lag_blocks,
+            _ => false,
+        };
+        let behind_time = match (probe.last_end_time, max_end_time) {
+            (Some(end_time), Some(max_end_time)) => max_end_time - end_time > max_lag_secs,
+            _ => false,
+        };
+
+        if (behind_height || behind_time) && !probe.lagging {
+            eprintln!(
+                "{} Midgard {} is lagging, it is now tried last",
+                network.name, upstream.base_url
+            );
+        }
+
+        probe.lagging = behind_height || behind_time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(upstreams: &[Upstream]) -> Vec<(&str, u32)> {
+        upstreams
+            .iter()
+            .map(|upstream| (upstream.base_url.as_str(), upstream.priority))
+            .collect()
+    }
+
+    #[test]
+    fn ranks_endpoints_by_position_without_priorities() {
+        assert_eq!(
+            urls(&parse_upstreams("https://a.io, https://b.io/")),
+            vec![("https://a.io", 0), ("https://b.io", 1)]
+        );
+    }
+
+    #[test]
+    fn sorts_endpoints_by_priority() {
+        assert_eq!(
+            urls(&parse_upstreams(
+                "https://a.io|5,https://b.io|1,https://c.io"
+            )),
+            vec![
+                ("https://b.io", 1),
+                ("https://c.io", 2),
+                ("https://a.io", 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_priorities_fall_back_to_the_position() {
+        assert_eq!(
+            urls(&parse_upstreams("https://a.io|first,https://b.io|0")),
+            vec![("https://a.io", 0), ("https://b.io", 0)]
+        );
+    }
+
+    #[test]
+    fn skips_empty_entries() {
+        assert_eq!(
+            urls(&parse_upstreams(" ,https://a.io,, ")),
+            vec![("https://a.io", 0)]
+        );
+        assert!(parse_upstreams("").is_empty());
+    }
+
+    #[test]
+    fn reads_numbers_and_numeric_strings() {
+        assert_eq!(json_number(&Value::from(12)), Some(12.0));
+        assert_eq!(json_number(&Value::from("12.5")), Some(12.5));
+        assert_eq!(json_number(&Value::Null), None);
+    }
+}
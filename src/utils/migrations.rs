@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::{
+    models::migration_model::AppliedMigration,
+    repository::{history_store::StoreError, stores::Stores},
+};
+
+// Rows changed at a time, the progress of large collections is printed after each batch.
+const BATCH_SIZE: i64 = 1000;
+
+// A change to the stored data, applied once per network through the repositories so it works on
+// every backend.
+#[async_trait]
+pub trait Migration: Send + Sync {
+    // Migrations are applied in the order of their ids, which never change once released.
+    fn id(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    // Rows the migration still has to change.
+    async fn count(&self, db: &Stores) -> Result<u64, StoreError>;
+
+    // Changes at most `limit` rows and returns how many it changed, it is called until none are.
+    async fn apply_batch(&self, db: &Stores, limit: i64) -> Result<u64, StoreError>;
+}
+
+// Every migration, oldest first.
+pub fn migrations() -> Vec<Box<dyn Migration>> {
+    vec![Box::new(MarkHourlyGranularity), Box::new(MarkDepthPool)]
+}
+
+// Applies the migrations the network's database hasn't recorded yet, stopping at the first one that
+// fails. With `dry_run` only the rows they would change are counted. Returns the ids of the pending
+// migrations.
+pub async fn run_migrations(db: &Stores, dry_run: bool) -> Result<Vec<String>, StoreError> {
+    let applied = db.migration_repo.fetch_applied().await?;
+    let mut pending = vec![];
+
+    for migration in migrations() {
+        if applied.iter().any(|applied| applied.id == migration.id()) {
+            continue;
+        }
+
+        let total = migration.count(db).await?;
+
+        println!(
+            "[{}] Migration {} ({}): {} rows to change",
+            db.network.name,
+            migration.id(),
+            migration.description(),
+            total
+        );
+
+        pending.push(migration.id().to_string());
+
+        if dry_run {
+            continue;
+        }
+
+        let mut changed = 0;
+
+        loop {
+            let batch = migration.apply_batch(db, BATCH_SIZE).await?;
+
+            if batch == 0 {
+                break;
+            }
+
+            changed += batch;
+
+            println!(
+                "[{}] Migration {}: {}/{} rows",
+                db.network.name,
+                migration.id(),
+                changed,
+                total.max(changed)
+            );
+        }
+
+        db.migration_repo
+            .record_applied(&AppliedMigration {
+                id: migration.id().to_string(),
+                description: migration.description().to_string(),
+                applied_at: Utc::now().timestamp() as f64,
+                rows: changed as i64,
+            })
+            .await?;
+
+        println!(
+            "[{}] Applied migration {}, {} rows changed",
+            db.network.name,
+            migration.id(),
+            changed
+        );
+    }
+
+    Ok(pending)
+}
+
+// Rows stored before the granularity was recorded are matched as hours by every query, marking them
+// lets the queries match the granularity alone.
+struct MarkHourlyGranularity;
+
+#[async_trait]
+impl Migration for MarkHourlyGranularity {
+    fn id(&self) -> &'static str {
+        "0001_mark_hourly_granularity"
+    }
+
+    fn description(&self) -> &'static str {
+        "Marks the history rows stored without a granularity as hours"
+    }
+
+    async fn count(&self, db: &Stores) -> Result<u64, StoreError> {
+        Ok(db.depth_history_repo.count_missing_granularity().await?
+            + db.earnings_history_repo.count_missing_granularity().await?
+            + db.rune_pool_history_repo
+                .count_missing_granularity()
+                .await?
+            + db.swaps_history_repo.count_missing_granularity().await?
+            + db.savers_history_repo.count_missing_granularity().await?
+            + db.liquidity_changes_history_repo
+                .count_missing_granularity()
+                .await?)
+    }
+
+    // One dataset at a time, the next one once the previous has none left.
+    async fn apply_batch(&self, db: &Stores, limit: i64) -> Result<u64, StoreError> {
+        let marked = db
+            .depth_history_repo
+            .mark_missing_granularity(limit)
+            .await?;
+        if marked > 0 {
+            return Ok(marked);
+        }
+
+        let marked = db
+            .earnings_history_repo
+            .mark_missing_granularity(limit)
+            .await?;
+        if marked > 0 {
+            return Ok(marked);
+        }
+
+        let marked = db
+            .rune_pool_history_repo
+            .mark_missing_granularity(limit)
+            .await?;
+        if marked > 0 {
+            return Ok(marked);
+        }
+
+        let marked = db
+            .swaps_history_repo
+            .mark_missing_granularity(limit)
+            .await?;
+        if marked > 0 {
+            return Ok(marked);
+        }
+
+        let marked = db
+            .savers_history_repo
+            .mark_missing_granularity(limit)
+            .await?;
+        if marked > 0 {
+            return Ok(marked);
+        }
+
+        db.liquidity_changes_history_repo
+            .mark_missing_granularity(limit)
+            .await
+    }
+}
+
+// The depth history was only ingested for the default pool before it was ingested for every tracked
+// pool, the rows stored without a pool are its rows.
+struct MarkDepthPool;
+
+#[async_trait]
+impl Migration for MarkDepthPool {
+    fn id(&self) -> &'static str {
+        "0002_mark_depth_pool"
+    }
+
+    fn description(&self) -> &'static str {
+        "Marks the depth history rows stored without a pool as rows of the default pool"
+    }
+
+    async fn count(&self, db: &Stores) -> Result<u64, StoreError> {
+        db.depth_history_repo.count_missing_pool().await
+    }
+
+    async fn apply_batch(&self, db: &Stores, limit: i64) -> Result<u64, StoreError> {
+        db.depth_history_repo
+            .mark_missing_pool(&db.network.default_pool, limit)
+            .await
+    }
+}
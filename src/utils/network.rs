@@ -0,0 +1,156 @@
+use std::{env, sync::atomic::AtomicU64};
+
+use super::{
+    midgard_client::{env_number, DEFAULT_BURST, DEFAULT_REQUESTS_PER_SECOND},
+    midgard_upstreams::{parse_upstreams, Upstream},
+    rate_limiter::TokenBucket,
+};
+
+// Networks cached by default, overridden by the comma separated `NETWORKS`.
+const DEFAULT_NETWORKS: &str = "mainnet";
+
+// Pool of the networks missing from `KNOWN_NETWORKS`.
+const DEFAULT_POOL: &str = "BTC.BTC";
+
+// Midgard urls, native asset, database and pool of the networks known without configuration. Each
+// one can be changed with `<NAME>_MIDGARD_URLS`, `<NAME>_NATIVE_ASSET`, `<NAME>_DATABASE` and
+// `<NAME>_DEFAULT_POOL`.
+const KNOWN_NETWORKS: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "mainnet",
+        "https://midgard.ninerealms.com",
+        "RUNE",
+        "rustmidgardapi",
+        "BTC.BTC",
+    ),
+    (
+        "stagenet",
+        "https://stagenet-midgard.ninerealms.com",
+        "RUNE",
+        "rustmidgardapi_stagenet",
+        "BTC.BTC",
+    ),
+    (
+        "mayachain",
+        "https://midgard.mayachain.info",
+        "CACAO",
+        "rustmidgardapi_mayachain",
+        "BTC.BTC",
+    ),
+];
+
+// A Midgard deployment cached side by side with the others in its own database, its routes are
+// served under `/{name}`. Every network has its own endpoints, breakers and rate limit.
+pub struct Network {
+    pub name: String,
+    // Symbol the `rune*` amounts of the models are in (e.g. RUNE, CACAO).
+    pub native_asset: String,
+    pub database: String,
+    // Pool the depth, swaps, earnings and actions are ingested for, also the tracked pool when
+    // none is configured and the one the endpoints are probed on.
+    pub default_pool: String,
+    pub upstreams: Vec<Upstream>,
+    pub rate_limiter: TokenBucket,
+    pub fetched_pages: AtomicU64,
+}
+
+impl Network {
+    pub fn from_env(name: &str) -> Result<Self, String> {
+        let known = KNOWN_NETWORKS
+            .iter()
+            .find(|(known_name, _, _, _, _)| *known_name == name);
+
+        let mut midgard_urls = env_setting(name, "MIDGARD_URLS");
+
+        // Single network deployments configured the mainnet endpoints without a prefix.
+        if midgard_urls.is_none() && name == "mainnet" {
+            midgard_urls = env::var("MIDGARD_URLS")
+                .ok()
+                .filter(|urls| !urls.is_empty());
+        }
+
+        let midgard_urls = match (midgard_urls, known) {
+            (Some(urls), _) => urls,
+            (None, Some((_, url, _, _, _))) => url.to_string(),
+            (None, None) => {
+                return Err(format!(
+                    "Unknown network {}, set {}_MIDGARD_URLS",
+                    name,
+                    env_prefix(name)
+                ))
+            }
+        };
+
+        let native_asset = env_setting(name, "NATIVE_ASSET")
+            .or_else(|| known.map(|(_, _, native_asset, _, _)| native_asset.to_string()))
+            .unwrap_or_else(|| String::from("RUNE"));
+
+        let database = env_setting(name, "DATABASE")
+            .or_else(|| known.map(|(_, _, _, database, _)| database.to_string()))
+            .unwrap_or_else(|| format!("rustmidgardapi_{}", name));
+
+        let default_pool = env_setting(name, "DEFAULT_POOL")
+            .or_else(|| known.map(|(_, _, _, _, pool)| pool.to_string()))
+            .unwrap_or_else(|| String::from(DEFAULT_POOL));
+
+        let upstreams = parse_upstreams(&midgard_urls);
+
+        if upstreams.is_empty() {
+            return Err(format!("No Midgard url configured for network {}", name));
+        }
+
+        Ok(Network {
+            name: name.to_string(),
+            native_asset,
+            database,
+            default_pool,
+            upstreams,
+            rate_limiter: TokenBucket::new(
+                env_number("MIDGARD_BURST", DEFAULT_BURST).max(1.0),
+                env_number("MIDGARD_REQUESTS_PER_SECOND", DEFAULT_REQUESTS_PER_SECOND).max(0.1),
+            ),
+            fetched_pages: AtomicU64::new(0),
+        })
+    }
+
+    // A setting of this network, `<NAME>_<KEY>` if set, `<KEY>` otherwise.
+    pub fn setting(&self, key: &str) -> Option<String> {
+        env_setting(&self.name, key)
+            .or_else(|| env::var(key).ok().filter(|value| !value.is_empty()))
+    }
+}
+
+fn env_prefix(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+fn env_setting(name: &str, key: &str) -> Option<String> {
+    env::var(format!("{}_{}", env_prefix(name), key))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+// Networks from the comma separated `NETWORKS`, the first one is also served without a prefix.
+pub fn networks_from_env() -> Result<Vec<Network>, String> {
+    let names = env::var("NETWORKS").unwrap_or_else(|_| String::from(DEFAULT_NETWORKS));
+
+    let mut networks: Vec<Network> = vec![];
+
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        if networks.iter().any(|network| network.name == name) {
+            continue;
+        }
+
+        networks.push(Network::from_env(name)?);
+    }
+
+    if networks.is_empty() {
+        networks.push(Network::from_env(DEFAULT_NETWORKS)?);
+    }
+
+    Ok(networks)
+}
@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::{models::actions_model::Action, repository::actions_repo::ActionsFilter};
+
 use super::time_interval::TimeInterval;
 
 #[derive(Deserialize, Clone)]
@@ -11,8 +13,14 @@ pub struct QueryParameters {
     pub pool: Option<String>,
     pub page: Option<i64>,
     pub sort_by: Option<String>,
+    pub include_extra: Option<bool>,
 }
 impl QueryParameters {
+    // Unknown upstream fields are only returned on request, to keep the documented shape by default.
+    pub fn include_extra(&self) -> bool {
+        self.include_extra.unwrap_or(false)
+    }
+
     pub fn process_query_parameters(&self) -> (f64, f64, TimeInterval, f64, i64, String, String) {
         let count = match self.count {
             Some(value) if value > 0 && value <= 400 => value,
@@ -72,3 +80,76 @@ impl QueryParameters {
         )
     }
 }
+
+#[derive(Deserialize, Clone)]
+pub struct ActionsQueryParameters {
+    pub address: Option<String>,
+    pub pool: Option<String>,
+    #[serde(rename = "type")]
+    pub action_type: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub count: Option<i64>,
+    pub page: Option<i64>,
+}
+
+impl ActionsQueryParameters {
+    pub fn process_query_parameters(&self) -> (ActionsFilter, i64, i64) {
+        let count = match self.count {
+            Some(value) if value > 0 && value <= 400 => value,
+            _ => 50,
+        };
+
+        let page = self.page.unwrap_or(1);
+
+        // Unknown action types are ignored instead of returning an empty result.
+        let action_type = self
+            .action_type
+            .clone()
+            .filter(|action_type| Action::is_action_type(action_type));
+
+        let filter = ActionsFilter {
+            address: self.address.clone(),
+            pool: self.pool.clone(),
+            action_type,
+            from: self.from,
+            to: self.to,
+        };
+
+        (filter, count, page)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct QuarantineQueryParameters {
+    pub dataset: Option<String>,
+    pub include_replayed: Option<bool>,
+    pub count: Option<i64>,
+    pub page: Option<i64>,
+}
+
+impl QuarantineQueryParameters {
+    pub fn process_query_parameters(&self) -> (Option<String>, bool, i64, i64) {
+        let count = match self.count {
+            Some(value) if value > 0 && value <= 400 => value,
+            _ => 50,
+        };
+
+        let page = self.page.unwrap_or(1);
+
+        (
+            self.dataset.clone(),
+            self.include_replayed.unwrap_or(false),
+            count,
+            page,
+        )
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct RollupQueryParameters {
+    pub dataset: Option<String>,
+    pub pool: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
@@ -0,0 +1,92 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::time::sleep;
+
+// Token bucket shared by the callers of an upstream, bursts up to `capacity` requests then lets
+// `refill_per_second` through.
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: f64, refill_per_second: f64) -> Self {
+        TokenBucket {
+            capacity,
+            refill_per_second,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    // Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let (tokens, refilled_at) = &mut *state;
+
+                let now = Instant::now();
+                let refill =
+                    now.duration_since(*refilled_at).as_secs_f64() * self.refill_per_second;
+                *tokens = (*tokens + refill).min(self.capacity);
+                *refilled_at = now;
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+
+                (1.0 - *tokens) / self.refill_per_second
+            };
+
+            sleep(Duration::from_secs_f64(wait)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lets_the_burst_through_at_once() {
+        let bucket = TokenBucket::new(3.0, 1.0);
+        let started = Instant::now();
+
+        for _ in 0..3 {
+            bucket.acquire().await;
+        }
+
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_refill_once_the_burst_is_spent() {
+        let bucket = TokenBucket::new(1.0, 10.0);
+        let started = Instant::now();
+
+        bucket.acquire().await;
+        bucket.acquire().await;
+        bucket.acquire().await;
+
+        // Two tokens refilled at 10 per second.
+        assert!(started.elapsed() >= Duration::from_millis(190));
+    }
+
+    #[tokio::test]
+    async fn never_holds_more_than_the_burst() {
+        let bucket = TokenBucket::new(1.0, 100.0);
+
+        sleep(Duration::from_millis(50)).await;
+        let started = Instant::now();
+
+        bucket.acquire().await;
+        bucket.acquire().await;
+
+        assert!(started.elapsed() >= Duration::from_millis(9));
+    }
+}
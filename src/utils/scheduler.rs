@@ -1,84 +1,208 @@
 use actix_web::web::Data;
-use futures::TryStreamExt;
-use mongodb::{bson::doc, options::FindOptions};
+use futures::{future::join_all, join};
 use tokio::time::{interval, Duration};
 
 use crate::{
-    repository::mongodb_repository::MongoDB,
+    repository::stores::Stores,
     services::{
+        actions_service, coverage_service,
         depth_history_service::{self},
-        earnings_history_service, rune_pool_history_service, swaps_history_service,
+        earnings_history_service, liquidity_changes_history_service, member_positions_service,
+        network_history_service, pool_stats_service, retention_service, rollup_service,
+        rune_pool_history_service, savers_history_service, swaps_history_service,
     },
+    utils::{ingestion, midgard_upstreams, network::Network},
 };
 
-pub async fn get_last_end_time(db: &Data<MongoDB>) -> f64 {
-    let options = FindOptions::builder()
-        .sort(doc! { "endTime": -1 })
-        .limit(1)
-        .build();
+// The depth history of the default pool is the reference the sync of every dataset resumes from.
+pub async fn get_last_end_time(db: &Data<Stores>) -> f64 {
+    // Day and month rows backfilled by hand end ahead of the hourly sync, only hours move it.
+    db.depth_history_repo
+        .fetch_latest(Some(&db.network.default_pool), f64::MAX)
+        .await
+        .unwrap()
+        .map_or(0.0, |depth_history| depth_history.end_time)
+}
 
-    let mut result = db.depth_history_repo.col.find(None, options).await.unwrap();
+// Start of the oldest interval Midgard was still accumulating when it was fetched. The sync resumes
+// from there so the incomplete intervals get overwritten.
+pub async fn get_first_incomplete_start_time(db: &Data<Stores>) -> Option<f64> {
+    db.depth_history_repo
+        .fetch_first_incomplete(Some(&db.network.default_pool))
+        .await
+        .unwrap()
+        .map(|depth_history| depth_history.start_time)
+}
 
-    if let Some(doc) = result.try_next().await.unwrap() {
-        return doc.end_time;
+// Pools of the per pool datasets, the default pool of the network then the comma separated
+// `TRACKED_POOLS` (or `<NETWORK>_TRACKED_POOLS`). The default pool is always tracked, the sync
+// resumes from its depth history.
+pub fn tracked_pools(network: &Network) -> Vec<String> {
+    let mut pools = vec![network.default_pool.clone()];
+
+    for pool in network
+        .setting("TRACKED_POOLS")
+        .unwrap_or_default()
+        .split(',')
+    {
+        let pool = pool.trim().to_string();
+
+        if !pool.is_empty() && !pools.contains(&pool) {
+            pools.push(pool);
+        }
     }
 
-    0.0
+    pools
 }
 
-pub async fn run_cron_job(db: Data<MongoDB>) {
+pub async fn run_cron_job(db: Data<Stores>) {
     let mut interval = interval(Duration::from_secs(3600));
 
     loop {
         interval.tick().await; // Waiting for the next tick.
 
-        let from = get_last_end_time(&db).await;
+        midgard_upstreams::check_upstreams(&db.network).await;
+
+        // Every job would fail on the open breakers, the tick is skipped until Midgard is tried again.
+        if midgard_upstreams::all_open(&db.network) {
+            println!(
+                "{} Midgard circuit breakers are open, skipping the scheduled data fetch",
+                db.network.name
+            );
+            continue;
+        }
+
+        let from = match get_first_incomplete_start_time(&db).await {
+            Some(start_time) => start_time,
+            None => get_last_end_time(&db).await,
+        };
 
         let interval = String::from("hour");
 
-        println!("Running scheduled data fetch at {:?}", from);
-
-        let depth_history_result = depth_history_service::fetch_and_update_depth_history(
-            db.clone(),
-            from,
-            400.0,
-            interval.to_string(),
-            String::from("BTC.BTC"),
-        )
-        .await;
-
-        let swap_history_result = swaps_history_service::fetch_and_update_swaps_history(
-            &db,
-            from,
-            400.0,
-            interval.to_string(),
-            String::from("BTC.BTC"),
-        )
-        .await;
-
-        let rune_pool_history_result =
-            rune_pool_history_service::fetch_and_update_rune_pool_history(
+        println!(
+            "Running scheduled {} data fetch at {:?}",
+            db.network.name, from
+        );
+
+        let pools = tracked_pools(&db.network);
+        let member_watch_list = member_positions_service::member_watch_list(&db.network);
+
+        // Every dataset and pool is fetched at once, the ingestion executor bounds how many run
+        // together and the Midgard client how fast they send requests.
+        let (
+            depth_history_results,
+            swap_history_result,
+            rune_pool_history_result,
+            earnings_history_result,
+            savers_history_results,
+            liquidity_changes_history_results,
+            pool_stats_results,
+            actions_result,
+            network_history_result,
+            member_positions_result,
+        ) = join!(
+            join_all(pools.iter().map(|pool| {
+                ingestion::run(depth_history_service::fetch_and_update_depth_history(
+                    db.clone(),
+                    from,
+                    400.0,
+                    interval.to_string(),
+                    pool.clone(),
+                ))
+            })),
+            ingestion::run(swaps_history_service::fetch_and_update_swaps_history(
                 &db,
                 from,
                 400.0,
                 interval.to_string(),
-            )
-            .await;
+                db.network.default_pool.clone(),
+            )),
+            ingestion::run(
+                rune_pool_history_service::fetch_and_update_rune_pool_history(
+                    &db,
+                    from,
+                    400.0,
+                    interval.to_string(),
+                )
+            ),
+            ingestion::run(earnings_history_service::fetch_and_update_earnigns_history(
+                &db,
+                from,
+                400.0,
+                interval.to_string(),
+            )),
+            join_all(pools.iter().map(|pool| {
+                ingestion::run(savers_history_service::fetch_and_update_savers_history(
+                    &db,
+                    from,
+                    400.0,
+                    interval.to_string(),
+                    pool.clone(),
+                ))
+            })),
+            join_all(pools.iter().map(|pool| {
+                ingestion::run(
+                    liquidity_changes_history_service::fetch_and_update_liquidity_changes_history(
+                        &db,
+                        from,
+                        400.0,
+                        interval.to_string(),
+                        pool.clone(),
+                    ),
+                )
+            })),
+            join_all(pools.iter().map(|pool| {
+                ingestion::run(pool_stats_service::fetch_and_update_pool_stats(
+                    &db,
+                    pool.clone(),
+                ))
+            })),
+            ingestion::run(actions_service::fetch_and_update_actions(
+                &db,
+                from,
+                db.network.default_pool.clone(),
+            )),
+            ingestion::run(network_history_service::fetch_and_update_network_history(
+                &db
+            )),
+            ingestion::run(member_positions_service::fetch_and_update_member_positions(
+                &db,
+                &member_watch_list,
+            )),
+        );
+
+        let depth_history_result = depth_history_results.into_iter().all(|result| result);
+        let savers_history_result = savers_history_results.into_iter().all(|result| result);
+        let liquidity_changes_history_result = liquidity_changes_history_results
+            .into_iter()
+            .all(|result| result);
+        let pool_stats_result = pool_stats_results.into_iter().all(|result| result);
+
+        // Last, so the gaps left by the jobs above are picked up on this tick.
+        let coverage_result = coverage_service::check_coverage(&db).await;
+
+        // The day to year rollups of the hours fetched on this tick.
+        let rollups_result = rollup_service::refresh_rollups(&db, from).await;
 
-        let earnings_history_result = earnings_history_service::fetch_and_update_earnigns_history(
-            &db,
-            from,
-            400.0,
-            interval.to_string(),
-        )
-        .await;
+        // After the rollups, which the expired rows have to be covered by.
+        let retention_result = retention_service::expire_rows(&db).await;
 
         println!(
-            "Jobs done : depth history - {}, swap history - {}, rune pool history - {} earnings history {}",
+            "{} jobs done : depth history - {}, swap history - {}, rune pool history - {} earnings history {} savers history {} liquidity changes history {} actions {} network history {} member positions {} pool stats {} coverage {} rollups {} retention {}",
+            db.network.name,
             depth_history_result,
             swap_history_result,
             rune_pool_history_result,
-            earnings_history_result
+            earnings_history_result,
+            savers_history_result,
+            liquidity_changes_history_result,
+            actions_result,
+            network_history_result,
+            member_positions_result,
+            pool_stats_result,
+            coverage_result,
+            rollups_result,
+            retention_result
         );
 
         println!("Cron job running");
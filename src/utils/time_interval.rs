@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use chrono::{DateTime, Datelike, Months, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimeInterval {
     Hour,
     Day,
@@ -19,6 +21,77 @@ impl TimeInterval {
             TimeInterval::Year => 31822400,   // 1 year
         }
     }
+
+    // Shortest and longest length of an interval in seconds, months and years vary in length.
+    pub fn span_bounds(&self) -> (i64, i64) {
+        match self {
+            TimeInterval::Hour => (3600, 3600),
+            TimeInterval::Day => (86400, 86400),
+            TimeInterval::Week => (604800, 604800),
+            TimeInterval::Month => (28 * 86400, 31 * 86400),
+            TimeInterval::Quarter => (89 * 86400, 92 * 86400),
+            TimeInterval::Year => (365 * 86400, 366 * 86400),
+        }
+    }
+
+    // Intervals shorter than this one, shortest first.
+    pub fn finer(&self) -> Vec<TimeInterval> {
+        [
+            TimeInterval::Hour,
+            TimeInterval::Day,
+            TimeInterval::Week,
+            TimeInterval::Month,
+            TimeInterval::Quarter,
+            TimeInterval::Year,
+        ]
+        .into_iter()
+        .filter(|interval| interval.as_seconds() < self.as_seconds())
+        .collect()
+    }
+
+    // Start of the calendar interval containing `time`, in UTC. Weeks start on Monday like
+    // Midgard's.
+    pub fn bucket_start(&self, time: f64) -> f64 {
+        let date = DateTime::from_timestamp(time as i64, 0)
+            .unwrap_or_default()
+            .date_naive();
+
+        let start = match self {
+            TimeInterval::Hour => return (time / 3600.0).floor() * 3600.0,
+            TimeInterval::Day => date,
+            TimeInterval::Week => {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            TimeInterval::Month => date.with_day(1).unwrap_or(date),
+            TimeInterval::Quarter => {
+                NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1).unwrap_or(date)
+            }
+            TimeInterval::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+        };
+
+        start
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc()
+            .timestamp() as f64
+    }
+
+    // Start of the calendar interval following the one starting at `start`.
+    pub fn next_start(&self, start: f64) -> f64 {
+        let months = match self {
+            TimeInterval::Month => 1,
+            TimeInterval::Quarter => 3,
+            TimeInterval::Year => 12,
+            _ => return start + self.as_seconds() as f64,
+        };
+
+        DateTime::from_timestamp(start as i64, 0)
+            .and_then(|start| start.checked_add_months(Months::new(months)))
+            .map_or(start + self.as_seconds() as f64, |next| {
+                next.timestamp() as f64
+            })
+    }
+
     pub fn from_str(interval: &str) -> Option<Self> {
         match interval.to_lowercase().as_str() {
             "hour" => Some(TimeInterval::Hour),
@@ -0,0 +1,506 @@
+use std::env;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{
+    depth_history_model::DepthHistory, earnings_history_model::EarningsHistory,
+    liquidity_changes_history_model::LiquidityChangesHistory,
+    rune_pool_history_model::RunePoolHistory, savers_history_model::SaversHistory,
+    schema_drift_model::ExtraFields, swaps_history_model::SwapsHistory,
+};
+
+use super::time_interval::TimeInterval;
+
+// Rule names, used as keys of the validation metrics.
+pub const NON_NEGATIVE: &str = "non_negative";
+pub const POSITIVE_DEPTH: &str = "positive_depth";
+pub const INTERVAL_SPAN: &str = "interval_span";
+pub const MONOTONIC_TIME: &str = "monotonic_time";
+pub const SWAPS_TOTAL_COUNT: &str = "swaps_total_count";
+pub const EARNINGS_TOTAL: &str = "earnings_total";
+
+// Amounts are in 1e8 base units, parsed to f64, so sums are compared with a one unit tolerance.
+const AMOUNT_TOLERANCE: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    // Violations are logged and counted, the interval is still inserted.
+    Warn,
+    // Violating intervals are quarantined instead of inserted.
+    Reject,
+}
+
+impl ValidationMode {
+    // Read from `VALIDATION_MODE`, rejecting unless it is set to `warn`.
+    pub fn from_env() -> Self {
+        match env::var("VALIDATION_MODE") {
+            Ok(mode) if mode.eq_ignore_ascii_case("warn") => ValidationMode::Warn,
+            _ => ValidationMode::Reject,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ValidationMode::Warn => "warn",
+            ValidationMode::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Violation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+// Rules a single interval can be checked against, without the context of the page.
+pub trait Validate {
+    fn start_time(&self) -> f64;
+
+    fn end_time(&self) -> f64;
+
+    fn violations(&self) -> Vec<Violation>;
+}
+
+// Every known numeric field but the `signed` ones must be >= 0. Unknown fields aren't checked,
+// their meaning isn't known yet.
+fn negative_amounts<T: Serialize>(
+    interval: &T,
+    extra: &ExtraFields,
+    signed: &[&str],
+) -> Vec<Violation> {
+    let fields = match serde_json::to_value(interval) {
+        Ok(Value::Object(fields)) => fields,
+        _ => return vec![],
+    };
+
+    fields
+        .iter()
+        .filter(|(field, _)| !extra.contains_key(*field) && !signed.contains(&field.as_str()))
+        .filter_map(|(field, value)| match value.as_f64() {
+            Some(amount) if amount < 0.0 => Some(Violation {
+                rule: NON_NEGATIVE,
+                message: format!("`{}` is negative ({})", field, amount),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+impl Validate for DepthHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        let mut violations = negative_amounts(self, &self.extra, &[]);
+
+        if self.asset_depth == 0.0 || self.rune_depth == 0.0 {
+            violations.push(Violation {
+                rule: POSITIVE_DEPTH,
+                message: format!(
+                    "pool depth is empty (assetDepth {}, runeDepth {})",
+                    self.asset_depth, self.rune_depth
+                ),
+            });
+        }
+
+        violations
+    }
+}
+
+impl Validate for SwapsHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        let mut violations = negative_amounts(self, &self.extra, &[]);
+
+        let count = self.to_asset_count
+            + self.to_rune_count
+            + self.to_trade_count
+            + self.from_trade_count
+            + self.synth_mint_count
+            + self.synth_redeem_count;
+
+        // Counts are whole numbers, they are compared as such rather than as sums of f64.
+        if count.round() as i64 != self.total_count.round() as i64 {
+            violations.push(Violation {
+                rule: SWAPS_TOTAL_COUNT,
+                message: format!(
+                    "`totalCount` is {} but the per type counts sum to {}",
+                    self.total_count, count
+                ),
+            });
+        }
+
+        violations
+    }
+}
+
+impl Validate for EarningsHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        // Liquidity earnings are what is left of the earnings after bonding, it can go below 0.
+        let mut violations = negative_amounts(self, &self.extra, &["liquidityEarnings"]);
+
+        let earnings = self.liquidity_fees + self.block_rewards;
+
+        if (earnings - self.earnings).abs() > AMOUNT_TOLERANCE {
+            violations.push(Violation {
+                rule: EARNINGS_TOTAL,
+                message: format!(
+                    "`earnings` is {} but liquidity fees plus block rewards are {}",
+                    self.earnings, earnings
+                ),
+            });
+        }
+
+        violations
+    }
+}
+
+impl Validate for RunePoolHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        negative_amounts(self, &self.extra, &[])
+    }
+}
+
+impl Validate for SaversHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        negative_amounts(self, &self.extra, &[])
+    }
+}
+
+impl Validate for LiquidityChangesHistory {
+    fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
+    fn end_time(&self) -> f64 {
+        self.end_time
+    }
+
+    fn violations(&self) -> Vec<Violation> {
+        // The net flow is negative whenever more was withdrawn than added.
+        negative_amounts(self, &self.extra, &["net"])
+    }
+}
+
+// Checks the intervals of one page in order, on top of the per interval rules it checks their span
+// against the requested interval and that time only moves forward from the sync cursor.
+pub struct IntervalValidator {
+    pub mode: ValidationMode,
+    interval: Option<TimeInterval>,
+    span: Option<(i64, i64)>,
+    cursor: f64,
+    last_end_time: Option<f64>,
+}
+
+impl IntervalValidator {
+    pub fn new(interval: Option<&TimeInterval>, cursor: f64) -> Self {
+        IntervalValidator {
+            mode: ValidationMode::from_env(),
+            interval: interval.copied(),
+            span: interval.map(TimeInterval::span_bounds),
+            cursor,
+            last_end_time: None,
+        }
+    }
+
+    // Interval the page was requested at, stored on the intervals as their granularity.
+    pub fn granularity(&self) -> Option<&'static str> {
+        self.interval.map(|interval| interval.to_str())
+    }
+
+    pub fn validate<I: Validate>(&mut self, interval: &I) -> Vec<Violation> {
+        let mut violations = interval.violations();
+
+        let (start_time, end_time) = (interval.start_time(), interval.end_time());
+        let span = (end_time - start_time) as i64;
+
+        let (min_span, max_span) = self.span.unwrap_or((1, i64::MAX));
+
+        if span < min_span || span > max_span {
+            violations.push(Violation {
+                rule: INTERVAL_SPAN,
+                message: format!(
+                    "interval spans {}s, expected between {}s and {}s",
+                    span, min_span, max_span
+                ),
+            });
+        }
+
+        // Midgard aligns the first interval on the interval boundary so it may start before the
+        // cursor, but it must end after it. The following ones start where the last one ended.
+        let goes_back = match self.last_end_time {
+            Some(last_end_time) => start_time < last_end_time,
+            None => end_time <= self.cursor,
+        };
+
+        if goes_back {
+            violations.push(Violation {
+                rule: MONOTONIC_TIME,
+                message: format!(
+                    "interval {} - {} goes back before {}",
+                    start_time,
+                    end_time,
+                    self.last_end_time.unwrap_or(self.cursor)
+                ),
+            });
+        }
+
+        // A rejected interval isn't inserted, the next one is checked against the last kept one.
+        if violations.is_empty() || self.mode == ValidationMode::Warn {
+            self.last_end_time = Some(self.last_end_time.unwrap_or(end_time).max(end_time));
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Map, Value};
+
+    use super::*;
+
+    const DEPTH_FIELDS: &str = "startTime endTime assetDepth runeDepth assetPrice assetPriceUSD
+        liquidityUnits membersCount synthUnits synthSupply units luvi";
+
+    const SWAPS_FIELDS: &str = "startTime endTime toAssetCount toRuneCount toTradeCount
+        fromTradeCount synthMintCount synthRedeemCount totalCount toAssetVolume toRuneVolume
+        toTradeVolume fromTradeVolume synthMintVolume synthRedeemVolume totalVolume
+        toAssetVolumeUSD toRuneVolumeUSD toTradeVolumeUSD fromTradeVolumeUSD synthMintVolumeUSD
+        synthRedeemVolumeUSD totalVolumeUSD toAssetFees toRuneFees toTradeFees fromTradeFees
+        synthMintFees synthRedeemFees totalFees toAssetAverageSlip toRuneAverageSlip
+        toTradeAverageSlip fromTradeAverageSlip synthMintAverageSlip synthRedeemAverageSlip
+        averageSlip runePriceUSD";
+
+    const EARNINGS_FIELDS: &str = "startTime endTime liquidityFees blockRewards earnings
+        bondingEarnings liquidityEarnings avgNodeCount runePriceUSD";
+
+    // 2020-01-01T00:00:00Z
+    const BASE: f64 = 1577836800.0;
+    const HOUR: f64 = 3600.0;
+
+    // A model with every field in `fields` set to zero, then the `values`.
+    fn record<T: DeserializeOwned>(fields: &str, values: Value) -> T {
+        let mut row: Map<String, Value> = fields
+            .split_whitespace()
+            .map(|field| (field.to_string(), json!(0)))
+            .collect();
+
+        if let Value::Object(values) = values {
+            row.extend(values);
+        }
+
+        serde_json::from_value(Value::Object(row)).unwrap()
+    }
+
+    fn depth(start_time: f64, end_time: f64, asset_depth: f64) -> DepthHistory {
+        record(
+            DEPTH_FIELDS,
+            json!({
+                "startTime": start_time,
+                "endTime": end_time,
+                "assetDepth": asset_depth,
+                "runeDepth": 100,
+            }),
+        )
+    }
+
+    fn rules(violations: &[Violation]) -> Vec<&'static str> {
+        violations.iter().map(|violation| violation.rule).collect()
+    }
+
+    fn validator() -> IntervalValidator {
+        IntervalValidator {
+            mode: ValidationMode::Reject,
+            interval: Some(TimeInterval::Hour),
+            span: Some(TimeInterval::Hour.span_bounds()),
+            cursor: BASE,
+            last_end_time: None,
+        }
+    }
+
+    #[test]
+    fn non_negative_accepts_positive_amounts() {
+        assert!(depth(BASE, BASE + HOUR, 100.0).violations().is_empty());
+    }
+
+    #[test]
+    fn non_negative_rejects_negative_amounts() {
+        let mut interval = depth(BASE, BASE + HOUR, 100.0);
+        interval.luvi = -1.0;
+
+        assert_eq!(rules(&interval.violations()), vec![NON_NEGATIVE]);
+    }
+
+    #[test]
+    fn non_negative_accepts_negative_signed_amounts() {
+        let earnings: EarningsHistory = record(
+            EARNINGS_FIELDS,
+            json!({ "liquidityEarnings": -5, "pools": [] }),
+        );
+
+        assert!(earnings.violations().is_empty());
+    }
+
+    #[test]
+    fn non_negative_skips_unknown_fields() {
+        let mut interval = depth(BASE, BASE + HOUR, 100.0);
+        interval.extra.insert(String::from("newField"), json!(-1));
+
+        assert!(interval.violations().is_empty());
+    }
+
+    #[test]
+    fn positive_depth_rejects_empty_pools() {
+        assert_eq!(
+            rules(&depth(BASE, BASE + HOUR, 0.0).violations()),
+            vec![POSITIVE_DEPTH]
+        );
+    }
+
+    #[test]
+    fn interval_span_accepts_the_requested_interval() {
+        assert!(validator()
+            .validate(&depth(BASE, BASE + HOUR, 100.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn interval_span_rejects_other_spans() {
+        assert_eq!(
+            rules(&validator().validate(&depth(BASE, BASE + 24.0 * HOUR, 100.0))),
+            vec![INTERVAL_SPAN]
+        );
+    }
+
+    #[test]
+    fn monotonic_time_accepts_consecutive_intervals() {
+        let mut validator = validator();
+
+        // The first interval may start before the cursor as long as it ends after it.
+        assert!(validator
+            .validate(&depth(BASE - HOUR / 2.0, BASE + HOUR / 2.0, 100.0))
+            .is_empty());
+        assert!(validator
+            .validate(&depth(BASE + HOUR / 2.0, BASE + 1.5 * HOUR, 100.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn monotonic_time_rejects_intervals_going_back() {
+        let mut validator = validator();
+
+        assert_eq!(
+            rules(&validator.validate(&depth(BASE - HOUR, BASE, 100.0))),
+            vec![MONOTONIC_TIME]
+        );
+
+        assert!(validator
+            .validate(&depth(BASE + HOUR, BASE + 2.0 * HOUR, 100.0))
+            .is_empty());
+        assert_eq!(
+            rules(&validator.validate(&depth(BASE, BASE + HOUR, 100.0))),
+            vec![MONOTONIC_TIME]
+        );
+    }
+
+    #[test]
+    fn swaps_total_count_accepts_the_sum_of_the_counts() {
+        let swaps: SwapsHistory = record(
+            SWAPS_FIELDS,
+            json!({
+                "toAssetCount": "120",
+                "toRuneCount": 80,
+                "synthMintCount": 3,
+                "totalCount": "203",
+            }),
+        );
+
+        assert!(swaps.violations().is_empty());
+    }
+
+    #[test]
+    fn swaps_total_count_rejects_another_sum() {
+        let swaps: SwapsHistory = record(
+            SWAPS_FIELDS,
+            json!({ "toAssetCount": 2, "toRuneCount": 1, "totalCount": 4 }),
+        );
+
+        assert_eq!(rules(&swaps.violations()), vec![SWAPS_TOTAL_COUNT]);
+    }
+
+    #[test]
+    fn earnings_total_accepts_rounding_errors() {
+        let earnings: EarningsHistory = record(
+            EARNINGS_FIELDS,
+            json!({
+                "liquidityFees": 10.5,
+                "blockRewards": 20,
+                "earnings": 31,
+                "pools": [],
+            }),
+        );
+
+        assert!(earnings.violations().is_empty());
+    }
+
+    #[test]
+    fn earnings_total_rejects_another_sum() {
+        let earnings: EarningsHistory = record(
+            EARNINGS_FIELDS,
+            json!({
+                "liquidityFees": 10,
+                "blockRewards": 20,
+                "earnings": 35,
+                "pools": [],
+            }),
+        );
+
+        assert_eq!(rules(&earnings.violations()), vec![EARNINGS_TOTAL]);
+    }
+}
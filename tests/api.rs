@@ -0,0 +1,1084 @@
+// The routes of one network over each storage backend. The memory backend always runs, SQLite and
+// Postgres with their features. Postgres uses `DATABASE_URL` if it is a postgres url, a local
+// server otherwise, with a schema per test.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix_web::{
+    test,
+    web::{self, Data},
+    App,
+};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use rust_api::{
+    models::{
+        coverage_model::{CoverageGap, GapStatus},
+        depth_history_model::DepthHistory,
+        earnings_history_model::EarningsHistory,
+        network_history_model::{NetworkHistory, NetworkResponse},
+        rune_pool_history_model::RunePoolHistory,
+        schema_drift_model::HasExtraFields,
+        swaps_history_model::SwapsHistory,
+    },
+    network_routes,
+    repository::stores::{Backend, Stores},
+    services::{coverage_service, retention_service, rollup_service},
+    utils::{
+        midgard_client::{RUNE_POOL_HISTORY, SWAPS_HISTORY},
+        network::Network,
+        scheduler::{get_first_incomplete_start_time, get_last_end_time},
+        time_interval::TimeInterval,
+    },
+};
+
+const HOUR: f64 = 3600.0;
+const DAY: f64 = 86400.0;
+
+// 2020-01-01T00:00:00Z
+const BASE: f64 = 1577836800.0;
+
+const DEPTH_FIELDS: &str = "startTime endTime assetDepth runeDepth assetPrice assetPriceUSD
+    liquidityUnits membersCount synthUnits synthSupply units luvi";
+
+const SWAPS_FIELDS: &str = "startTime endTime toAssetCount toRuneCount toTradeCount fromTradeCount
+    synthMintCount synthRedeemCount totalCount toAssetVolume toRuneVolume toTradeVolume
+    fromTradeVolume synthMintVolume synthRedeemVolume totalVolume toAssetVolumeUSD toRuneVolumeUSD
+    toTradeVolumeUSD fromTradeVolumeUSD synthMintVolumeUSD synthRedeemVolumeUSD totalVolumeUSD
+    toAssetFees toRuneFees toTradeFees fromTradeFees synthMintFees synthRedeemFees totalFees
+    toAssetAverageSlip toRuneAverageSlip toTradeAverageSlip fromTradeAverageSlip
+    synthMintAverageSlip synthRedeemAverageSlip averageSlip runePriceUSD";
+
+const EARNINGS_FIELDS: &str = "startTime endTime liquidityFees blockRewards earnings
+    bondingEarnings liquidityEarnings avgNodeCount runePriceUSD";
+
+const RUNE_POOL_FIELDS: &str = "startTime endTime count units";
+
+const EARNINGS_POOL_FIELDS: &str = "pool assetLiquidityFees runeLiquidityFees
+    totalLiquidityFeesRune saverEarning rewards earnings";
+
+static NEXT_DATABASE: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "postgres")]
+fn postgres_url() -> String {
+    std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| url.starts_with("postgres"))
+        .unwrap_or_else(|| String::from("postgres://postgres@127.0.0.1/postgres"))
+}
+
+// A database no other test uses.
+fn database() -> String {
+    format!(
+        "api_test_{}_{}",
+        Utc::now().timestamp_millis(),
+        NEXT_DATABASE.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+async fn init(backend: &Backend, database: &str) -> Data<Stores> {
+    let mut network = Network::from_env("mainnet").unwrap();
+    network.database = database.to_string();
+
+    Data::new(Stores::init(backend, network).await.unwrap())
+}
+
+// The stores of a network of its own.
+async fn stores(url: &str) -> Data<Stores> {
+    init(&Stores::backend(url).await.unwrap(), &database()).await
+}
+
+async fn get(db: &Data<Stores>, uri: &str) -> Value {
+    let app = test::init_service(
+        App::new().service(
+            web::scope("/mainnet")
+                .app_data(db.clone())
+                .configure(network_routes),
+        ),
+    )
+    .await;
+
+    test::call_and_read_body_json(&app, test::TestRequest::get().uri(uri).to_request()).await
+}
+
+// A model with every field in `fields` set to zero, then the `values`.
+fn record<T: DeserializeOwned>(fields: &str, values: Value) -> T {
+    let mut row: Map<String, Value> = fields
+        .split_whitespace()
+        .map(|field| (field.to_string(), json!(0)))
+        .collect();
+
+    if let Value::Object(values) = values {
+        row.extend(values);
+    }
+
+    serde_json::from_value(Value::Object(row)).unwrap()
+}
+
+fn depth(start_time: f64, asset_depth: f64, is_complete: bool) -> DepthHistory {
+    record(
+        DEPTH_FIELDS,
+        json!({
+            "pool": "BTC.BTC",
+            "startTime": start_time,
+            "endTime": start_time + HOUR,
+            "assetDepth": asset_depth,
+            "isComplete": is_complete,
+        }),
+    )
+}
+
+fn swaps(start_time: f64, total_count: f64, rune_price_usd: f64) -> SwapsHistory {
+    record(
+        SWAPS_FIELDS,
+        json!({
+            "startTime": start_time,
+            "endTime": start_time + HOUR,
+            "totalCount": total_count,
+            "runePriceUSD": rune_price_usd,
+        }),
+    )
+}
+
+fn earnings(start_time: f64, pools: &[(&str, f64)]) -> EarningsHistory {
+    let pools: Vec<Value> = pools
+        .iter()
+        .map(|(pool, rewards)| {
+            let pool: Map<String, Value> = record(
+                EARNINGS_POOL_FIELDS,
+                json!({ "pool": pool, "rewards": rewards }),
+            );
+            Value::Object(pool)
+        })
+        .collect();
+
+    record(
+        EARNINGS_FIELDS,
+        json!({
+            "startTime": start_time,
+            "endTime": start_time + HOUR,
+            "pools": pools,
+        }),
+    )
+}
+
+fn rune_pool(start_time: f64, is_complete: bool) -> RunePoolHistory {
+    record(
+        RUNE_POOL_FIELDS,
+        json!({
+            "startTime": start_time,
+            "endTime": start_time + HOUR,
+            "count": 1,
+            "isComplete": is_complete,
+        }),
+    )
+}
+
+fn number(value: &Value, field: &str) -> f64 {
+    value[field].as_f64().unwrap()
+}
+
+fn intervals(response: &Value) -> &Vec<Value> {
+    response["intervals"].as_array().unwrap()
+}
+
+async fn upsert_counts_inserted_and_replaced_rows(url: &str) {
+    let db = stores(url).await;
+    let rows: Vec<DepthHistory> = (0..3)
+        .map(|i| depth(BASE + i as f64 * HOUR, 100.0, true))
+        .collect();
+
+    let inserted = db.depth_history_repo.upsert(&rows).await.unwrap();
+    assert_eq!((inserted.upserted, inserted.matched), (3, 0));
+
+    let replaced = db.depth_history_repo.upsert(&rows[1..]).await.unwrap();
+    assert_eq!((replaced.upserted, replaced.matched), (0, 2));
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/depth-history?interval=hour&count=10&from={}&to={}",
+            BASE,
+            BASE + 3.0 * HOUR
+        ),
+    )
+    .await;
+    assert_eq!(intervals(&response).len(), 3);
+}
+
+async fn incomplete_intervals_are_replaced(url: &str) {
+    let db = stores(url).await;
+
+    db.depth_history_repo
+        .upsert(&[depth(BASE, 100.0, true), depth(BASE + HOUR, 200.0, false)])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_first_incomplete_start_time(&db).await,
+        Some(BASE + HOUR)
+    );
+
+    db.depth_history_repo
+        .upsert(&[depth(BASE + HOUR, 250.0, true)])
+        .await
+        .unwrap();
+
+    assert_eq!(get_first_incomplete_start_time(&db).await, None);
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/depth-history?interval=hour&count=10&from={}&to={}",
+            BASE,
+            BASE + 2.0 * HOUR
+        ),
+    )
+    .await;
+    let latest = &intervals(&response)[0];
+
+    assert_eq!(intervals(&response).len(), 2);
+    assert_eq!(number(latest, "assetDepth"), 250.0);
+    assert_eq!(latest["isComplete"], json!(true));
+}
+
+async fn days_sum_flows_and_keep_the_last_state(url: &str) {
+    let db = stores(url).await;
+    let rows: Vec<SwapsHistory> = (0..48)
+        .map(|i| swaps(BASE + i as f64 * HOUR, 1.0 + (i / 24) as f64, i as f64))
+        .collect();
+
+    db.swaps_history_repo.upsert(&rows).await.unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/swaps-history?interval=day&count=2&from={}&to={}",
+            BASE,
+            BASE + 2.0 * DAY
+        ),
+    )
+    .await;
+    let days = intervals(&response);
+
+    // Newest first.
+    assert_eq!(days.len(), 2);
+    assert_eq!(number(&days[1], "startTime"), BASE);
+    assert_eq!(number(&days[1], "totalCount"), 24.0);
+    assert_eq!(number(&days[1], "runePriceUSD"), 23.0);
+    assert_eq!(number(&days[0], "startTime"), BASE + DAY);
+    assert_eq!(number(&days[0], "totalCount"), 48.0);
+    assert_eq!(number(&days[0], "runePriceUSD"), 47.0);
+}
+
+async fn months_sum_flows_and_keep_the_last_state(url: &str) {
+    let db = stores(url).await;
+    // February 2020, months are calendar ones.
+    let second_month = BASE + 31.0 * DAY;
+
+    db.swaps_history_repo
+        .upsert(&[
+            swaps(BASE, 2.0, 1.0),
+            swaps(BASE + DAY, 3.0, 2.0),
+            swaps(second_month + DAY, 5.0, 3.0),
+            swaps(second_month + 2.0 * DAY, 7.0, 4.0),
+        ])
+        .await
+        .unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/swaps-history?interval=month&count=2&from={}&to={}",
+            BASE,
+            second_month + 2.0 * DAY + HOUR
+        ),
+    )
+    .await;
+    let months = intervals(&response);
+
+    assert_eq!(months.len(), 2);
+    assert_eq!(number(&months[1], "totalCount"), 5.0);
+    assert_eq!(number(&months[1], "runePriceUSD"), 2.0);
+    assert_eq!(number(&months[0], "totalCount"), 12.0);
+    assert_eq!(number(&months[0], "runePriceUSD"), 4.0);
+}
+
+async fn days_weight_the_average_slip(url: &str) {
+    let db = stores(url).await;
+    let mut quiet = swaps(BASE, 1.0, 1.0);
+    quiet.average_slip = 10.0;
+    let mut busy = swaps(BASE + HOUR, 3.0, 1.0);
+    busy.average_slip = 2.0;
+
+    db.swaps_history_repo.upsert(&[quiet, busy]).await.unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/swaps-history?interval=day&count=1&from={}&to={}",
+            BASE,
+            BASE + DAY
+        ),
+    )
+    .await;
+    let days = intervals(&response);
+
+    // Weighted by the swap count like the materialized days, not the last hour's.
+    assert_eq!(days.len(), 1);
+    assert_eq!(number(&days[0], "averageSlip"), 4.0);
+}
+
+async fn latest_and_first_incomplete_are_hourly(url: &str) {
+    let db = stores(url).await;
+
+    assert_eq!(get_last_end_time(&db).await, 0.0);
+    assert_eq!(get_first_incomplete_start_time(&db).await, None);
+
+    let mut day = depth(BASE, 1.0, false);
+    day.granularity = String::from("day");
+    day.end_time = BASE + 10.0 * DAY;
+
+    db.depth_history_repo
+        .upsert(&[
+            depth(BASE, 1.0, true),
+            depth(BASE + HOUR, 2.0, false),
+            depth(BASE + 2.0 * HOUR, 3.0, false),
+            day,
+        ])
+        .await
+        .unwrap();
+
+    // The day rows ending later are skipped.
+    assert_eq!(get_last_end_time(&db).await, BASE + 3.0 * HOUR);
+    assert_eq!(
+        get_first_incomplete_start_time(&db).await,
+        Some(BASE + HOUR)
+    );
+
+    let latest = db
+        .depth_history_repo
+        .fetch_latest(None, BASE + 2.0 * HOUR)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest.start_time, BASE + HOUR);
+}
+
+async fn earnings_keep_their_pools(url: &str) {
+    let db = stores(url).await;
+
+    db.earnings_history_repo
+        .upsert(&[earnings(BASE, &[("BTC.BTC", 1.0), ("ETH.ETH", 2.0)])])
+        .await
+        .unwrap();
+    db.earnings_history_repo
+        .upsert(&[earnings(BASE, &[("BTC.BTC", 3.0)])])
+        .await
+        .unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/earnings-history?interval=hour&count=1&from={}&to={}",
+            BASE,
+            BASE + HOUR
+        ),
+    )
+    .await;
+    let pools = intervals(&response)[0]["pools"].as_array().unwrap();
+
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0]["pool"], json!("BTC.BTC"));
+    assert_eq!(number(&pools[0], "rewards"), 3.0);
+}
+
+async fn full_ranges_are_fully_covered(url: &str) {
+    let db = stores(url).await;
+    let rows: Vec<DepthHistory> = (0..24)
+        .map(|i| depth(BASE + i as f64 * HOUR, 1.0, true))
+        .collect();
+
+    db.depth_history_repo.upsert(&rows).await.unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/depth-history?interval=hour&count=24&from={}&to={}",
+            BASE,
+            BASE + DAY
+        ),
+    )
+    .await;
+
+    assert_eq!(number(&response["meta"], "coverage"), 1.0);
+}
+
+async fn gaps_lower_the_coverage(url: &str) {
+    let db = stores(url).await;
+    let rows: Vec<DepthHistory> = (0..24)
+        .filter(|i| !(8..16).contains(i))
+        .map(|i| depth(BASE + i as f64 * HOUR, 1.0, true))
+        .collect();
+
+    db.depth_history_repo.upsert(&rows).await.unwrap();
+
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/depth-history?interval=hour&count=24&from={}&to={}",
+            BASE,
+            BASE + DAY
+        ),
+    )
+    .await;
+    let coverage = number(&response["meta"], "coverage");
+
+    assert!(coverage < 1.0);
+    assert!((coverage - 16.0 / 24.0).abs() < 1e-9);
+}
+
+async fn gaps_are_updated_by_dataset_pool_and_start(url: &str) {
+    let db = stores(url).await;
+    let gap = |pool: &str| CoverageGap {
+        id: None,
+        dataset: String::from("savers_history"),
+        pool: Some(pool.to_string()),
+        start_time: BASE,
+        end_time: BASE + 2.0 * HOUR,
+        missing_intervals: 2,
+        status: GapStatus::Found,
+        attempts: 0,
+        detected_at: BASE,
+        updated_at: BASE,
+    };
+
+    db.coverage_repo.insert_gap(&gap("BTC.BTC")).await.unwrap();
+    db.coverage_repo.insert_gap(&gap("ETH.ETH")).await.unwrap();
+    db.coverage_repo
+        .update_gap(&CoverageGap {
+            status: GapStatus::Repaired,
+            attempts: 1,
+            ..gap("ETH.ETH")
+        })
+        .await
+        .unwrap();
+
+    let gaps = db.coverage_repo.fetch_gaps(None, None).await.unwrap();
+
+    assert_eq!(gaps.len(), 2);
+    assert_eq!(gaps[0].status, GapStatus::Found);
+    assert_eq!(gaps[1].status, GapStatus::Repaired);
+    assert_eq!(gaps[1].attempts, 1);
+}
+
+async fn member_values_come_from_the_stored_depth_of_their_pool(url: &str) {
+    use rust_api::models::member_positions_model::{MemberPool, MemberPosition};
+
+    let db = stores(url).await;
+    let pool_depth = |pool: &str, asset_depth: f64| -> DepthHistory {
+        record(
+            DEPTH_FIELDS,
+            json!({
+                "pool": pool,
+                "startTime": BASE,
+                "endTime": BASE + HOUR,
+                "assetDepth": asset_depth,
+                "runeDepth": 2.0 * asset_depth,
+                "units": 100,
+            }),
+        )
+    };
+
+    db.depth_history_repo
+        .upsert(&[pool_depth("BTC.BTC", 1000.0), pool_depth("ETH.ETH", 500.0)])
+        .await
+        .unwrap();
+
+    for pool in ["BTC.BTC", "ETH.ETH", "DOGE.DOGE"] {
+        let member_pool: MemberPool = record(
+            "liquidityUnits runeAdded assetAdded runeWithdrawn assetWithdrawn runePending
+                assetPending",
+            json!({ "pool": pool, "liquidityUnits": 10 }),
+        );
+
+        db.member_positions_repo
+            .upsert_member_position(&MemberPosition::from_member_pool(
+                "thor1member",
+                member_pool,
+                BASE + 60.0,
+            ))
+            .await
+            .unwrap();
+    }
+
+    let body = get(
+        &db,
+        &format!(
+            "/mainnet/members/thor1member/history?interval=hour&count=10&from={}&to={}",
+            BASE,
+            BASE + HOUR
+        ),
+    )
+    .await;
+
+    let value = |pool: &str| {
+        body["intervals"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|interval| interval["pool"] == pool)
+            .map(|interval| {
+                (
+                    interval["assetValue"].clone(),
+                    interval["runeValue"].clone(),
+                )
+            })
+            .unwrap()
+    };
+
+    assert_eq!(value("BTC.BTC"), (json!(100.0), json!(200.0)));
+    assert_eq!(value("ETH.ETH"), (json!(50.0), json!(100.0)));
+    assert_eq!(value("DOGE.DOGE"), (Value::Null, Value::Null));
+}
+
+async fn snapshots_keep_the_fields_unknown_to_the_model(url: &str) {
+    let db = stores(url).await;
+
+    let response: NetworkResponse = serde_json::from_value(json!({
+        "bondingAPY": "0.1",
+        "liquidityAPY": "0.2",
+        "activeNodeCount": "100",
+        "standbyNodeCount": "10",
+        "poolShareFactor": "0.5",
+        "totalPooledRune": "1000",
+        "totalReserve": "2000",
+        "bondMetrics": {
+            "totalActiveBond": "300",
+            "totalStandbyBond": "30",
+            "averageActiveBond": "3",
+        },
+        "nextChurnHeight": "12345",
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.extra_field_names(),
+        vec!["nextChurnHeight", "bondMetrics.averageActiveBond"]
+    );
+
+    let snapshot = NetworkHistory::from_network_response(response, BASE + 60.0);
+    db.network_history_repo
+        .upsert_network_history(&snapshot)
+        .await
+        .unwrap();
+
+    let uri = format!(
+        "/mainnet/network-history?interval=day&count=1&from={}&to={}",
+        BASE,
+        BASE + DAY
+    );
+
+    let response = get(&db, &uri).await;
+    assert!(intervals(&response)[0].get("nextChurnHeight").is_none());
+
+    let response = get(&db, &format!("{}&include_extra=true", uri)).await;
+    let interval = &intervals(&response)[0];
+    assert_eq!(number(interval, "activeNodeCount"), 100.0);
+    assert_eq!(interval["nextChurnHeight"], "12345");
+    assert_eq!(interval["bondMetrics"]["averageActiveBond"], "3");
+}
+
+async fn actions_are_found_by_their_keys(url: &str) {
+    use rust_api::{models::actions_model::Action, repository::actions_repo::ActionsFilter};
+
+    let db = stores(url).await;
+    let action = |date: i64, action_type: &str, tx_id: &str, pool: &str| -> Action {
+        serde_json::from_value(json!({
+            "pools": [pool],
+            "type": action_type,
+            "status": "success",
+            "in": [{ "address": "thor1member", "txID": tx_id, "coins": [] }],
+            "out": [{ "address": "", "txID": "", "coins": [] }],
+            "date": date.to_string(),
+            "height": "1",
+        }))
+        .unwrap()
+    };
+    let filter =
+        |address: Option<&str>, pool: Option<&str>, action_type: Option<&str>| ActionsFilter {
+            address: address.map(String::from),
+            pool: pool.map(String::from),
+            action_type: action_type.map(String::from),
+            from: None,
+            to: None,
+        };
+
+    for action in [
+        action(1_000_000_000, "swap", "A", "BTC.BTC"),
+        action(2_000_000_000, "addLiquidity", "B", "ETH.ETH"),
+        action(3_000_000_000, "swap", "C", "ETH.ETH"),
+        action(3_000_000_000, "swap", "C", "ETH.ETH"),
+    ] {
+        db.actions_repo.upsert_action(&action).await.unwrap();
+    }
+
+    let (count, actions) = db
+        .actions_repo
+        .fetch_actions(&filter(Some("thor1member"), None, None), 2, 1)
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0].date, 3_000_000_000);
+
+    let (count, actions) = db
+        .actions_repo
+        .fetch_actions(&filter(None, Some("ETH.ETH"), Some("swap")), 10, 1)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(actions[0].in_txs[0].tx_id, "C");
+
+    let (count, _) = db
+        .actions_repo
+        .fetch_actions(
+            &ActionsFilter {
+                from: Some(2),
+                ..filter(None, None, None)
+            },
+            10,
+            1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+
+    let actions = db.actions_repo.fetch_actions_by_tx_id("B").await.unwrap();
+    assert_eq!(actions.len(), 1);
+}
+
+async fn quarantine_drift_and_violations_are_updated_in_place(url: &str) {
+    use rust_api::models::quarantine_model::{QuarantineKind, QuarantineRecord};
+
+    let db = stores(url).await;
+    let record = |quarantined_at: f64| QuarantineRecord {
+        id: None,
+        dataset: String::from(SWAPS_HISTORY),
+        kind: QuarantineKind::Interval,
+        pool: None,
+        source_url: String::from("https://midgard"),
+        error: String::from("missing field"),
+        raw: String::from("{}"),
+        quarantined_at,
+        replayed_at: None,
+    };
+
+    db.quarantine_repo
+        .insert_quarantine_record(&record(BASE))
+        .await
+        .unwrap();
+    db.quarantine_repo
+        .insert_quarantine_record(&record(BASE + HOUR))
+        .await
+        .unwrap();
+
+    let pending = db
+        .quarantine_repo
+        .fetch_pending_quarantine_records(None)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].quarantined_at, BASE);
+
+    let id = pending[0].id.unwrap();
+    db.quarantine_repo
+        .update_error(pending[1].id.unwrap(), "still missing")
+        .await
+        .unwrap();
+    db.quarantine_repo
+        .mark_replayed(id, BASE + DAY)
+        .await
+        .unwrap();
+
+    let replayed = db
+        .quarantine_repo
+        .fetch_quarantine_record(id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(replayed.replayed_at, Some(BASE + DAY));
+
+    let (count, records) = db
+        .quarantine_repo
+        .fetch_quarantine_records(Some(String::from(SWAPS_HISTORY)), false, 10, 1)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(records[0].error, "still missing");
+
+    for seen_at in [BASE, BASE + HOUR] {
+        db.schema_drift_repo
+            .record_field(SWAPS_HISTORY, "newField", "https://midgard", seen_at)
+            .await
+            .unwrap();
+    }
+
+    let fields = db.schema_drift_repo.fetch_schema_drift(None).await.unwrap();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].first_seen_at, BASE);
+    assert_eq!(fields[0].last_seen_at, BASE + HOUR);
+
+    for (rejected, message) in [(false, "first"), (true, "second"), (true, "third")] {
+        db.validation_metrics_repo
+            .record_violation(SWAPS_HISTORY, "nonNegative", rejected, message, BASE)
+            .await
+            .unwrap();
+    }
+
+    let metrics = db
+        .validation_metrics_repo
+        .fetch_validation_metrics(None)
+        .await
+        .unwrap();
+    assert_eq!(metrics.len(), 1);
+    assert_eq!((metrics[0].warned, metrics[0].rejected), (1, 2));
+    assert_eq!(metrics[0].last_violation, "third");
+}
+
+async fn rollups_keep_the_hours_before_their_first_bucket(url: &str) {
+    let db = stores(url).await;
+    let rows: Vec<SwapsHistory> = (5 * 24..40 * 24)
+        .map(|i| swaps(BASE + i as f64 * HOUR, 1.0, 1.0))
+        .collect();
+
+    db.swaps_history_repo.upsert(&rows).await.unwrap();
+    rollup_service::rebuild_rollups(&*db.swaps_history_repo, None, BASE, BASE + 40.0 * DAY)
+        .await
+        .unwrap();
+
+    // The January bucket starts before `from`, only the February one is in the range.
+    let from = BASE + 10.0 * DAY;
+    let to = BASE + 40.0 * DAY;
+    let response = get(
+        &db,
+        &format!(
+            "/mainnet/swaps-history?interval=month&count=10&from={}&to={}",
+            from, to
+        ),
+    )
+    .await;
+    let total: f64 = intervals(&response)
+        .iter()
+        .map(|month| number(month, "totalCount"))
+        .sum();
+
+    assert_eq!(total, (to - from) / HOUR);
+}
+
+async fn rollups_in_progress_keep_the_expired_hours(url: &str) {
+    let db = stores(url).await;
+
+    // The last hour of January is missing, its rollups don't line up with February's.
+    let hours: Vec<f64> = (0..40 * 24)
+        .map(|i| BASE + i as f64 * HOUR)
+        .filter(|start_time| *start_time != BASE + 31.0 * DAY - HOUR)
+        .collect();
+    let (first_hours, last_hours) = hours.split_at(hours.len() - 48);
+
+    let rows: Vec<SwapsHistory> = first_hours.iter().map(|h| swaps(*h, 1.0, 1.0)).collect();
+    db.swaps_history_repo.upsert(&rows).await.unwrap();
+    rollup_service::rebuild_rollups(&*db.swaps_history_repo, None, BASE, BASE + 40.0 * DAY)
+        .await
+        .unwrap();
+
+    let expired = retention_service::expire_dataset(
+        &db,
+        SWAPS_HISTORY,
+        None,
+        TimeInterval::Hour,
+        BASE + 35.0 * DAY,
+    )
+    .await
+    .unwrap();
+    assert!(expired > 0);
+
+    // The next hours refresh the year in progress.
+    let rows: Vec<SwapsHistory> = last_hours.iter().map(|h| swaps(*h, 1.0, 1.0)).collect();
+    db.swaps_history_repo.upsert(&rows).await.unwrap();
+    rollup_service::rebuild_rollups(
+        &*db.swaps_history_repo,
+        None,
+        last_hours[0],
+        BASE + 40.0 * DAY,
+    )
+    .await
+    .unwrap();
+
+    let years = db
+        .swaps_history_repo
+        .fetch_intervals(BASE, BASE + 366.0 * DAY, None, TimeInterval::Year)
+        .await
+        .unwrap();
+
+    assert_eq!(years.len(), 1);
+    assert_eq!(years[0].total_count, hours.len() as f64);
+}
+
+async fn expired_hours_are_not_gaps(url: &str) {
+    let db = stores(url).await;
+
+    // Hours are kept until an hour into the fourth day, no other test reads the rune pool retention.
+    let days = (Utc::now().timestamp() as f64 - BASE - 3.0 * DAY - HOUR) / DAY;
+    std::env::set_var(
+        "MAINNET_RUNE_POOL_HISTORY_RETENTION",
+        format!("hour={}", days),
+    );
+
+    // The second day has an hour in progress and isn't expired. The fourth one misses an hour.
+    let rows: Vec<RunePoolHistory> = (0..4 * 24)
+        .map(|i| BASE + i as f64 * HOUR)
+        .filter(|start_time| *start_time != BASE + 3.0 * DAY + 5.0 * HOUR)
+        .map(|start_time| rune_pool(start_time, start_time != BASE + 2.0 * DAY - HOUR))
+        .collect();
+
+    db.rune_pool_history_repo.upsert(&rows).await.unwrap();
+    rollup_service::rebuild_rollups(&*db.rune_pool_history_repo, None, BASE, BASE + 4.0 * DAY)
+        .await
+        .unwrap();
+    retention_service::expire_dataset(
+        &db,
+        RUNE_POOL_HISTORY,
+        None,
+        TimeInterval::Hour,
+        BASE + 3.0 * DAY,
+    )
+    .await
+    .unwrap();
+
+    coverage_service::scan_gaps(&db, RUNE_POOL_HISTORY, None)
+        .await
+        .unwrap();
+
+    // The third day was expired between the second and the fourth, its day holds the hours.
+    let gaps = db
+        .coverage_repo
+        .fetch_gaps(Some(RUNE_POOL_HISTORY.to_string()), None)
+        .await
+        .unwrap();
+
+    assert_eq!(gaps.len(), 1);
+    assert_eq!(gaps[0].start_time, BASE + 3.0 * DAY + 5.0 * HOUR);
+    assert_eq!(gaps[0].end_time, BASE + 3.0 * DAY + 6.0 * HOUR);
+}
+
+async fn rollups_keep_midgards_own_rows(url: &str) {
+    let db = stores(url).await;
+    let mut rows: Vec<SwapsHistory> = (0..48)
+        .map(|i| swaps(BASE + i as f64 * HOUR, 1.0, 1.0))
+        .collect();
+
+    let mut day = swaps(BASE, 30.0, 1.0);
+    day.granularity = String::from("day");
+    day.end_time = BASE + DAY;
+    rows.push(day);
+
+    db.swaps_history_repo.upsert(&rows).await.unwrap();
+    rollup_service::rebuild_rollups(&*db.swaps_history_repo, None, BASE, BASE + 2.0 * DAY)
+        .await
+        .unwrap();
+
+    let days = db
+        .swaps_history_repo
+        .fetch_intervals(BASE, BASE + 2.0 * DAY, None, TimeInterval::Day)
+        .await
+        .unwrap();
+
+    // The first day came from Midgard, only the second one is rolled up from the hours.
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0].total_count, 30.0);
+    assert!(!days[0].rolled_up);
+    assert_eq!(days[1].total_count, 24.0);
+    assert!(days[1].rolled_up);
+}
+
+// Stores started again on the same database find the collections the first ones wrote.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+async fn collections_are_kept_across_restarts(url: &str) {
+    use rust_api::models::{
+        actions_model::ActionsSyncState, migration_model::AppliedMigration,
+        savers_history_model::SaversHistory,
+    };
+
+    let backend = Stores::backend(url).await.unwrap();
+    let database = database();
+    let db = init(&backend, &database).await;
+
+    let savers: SaversHistory = record(
+        "startTime endTime saversDepth saversUnits saversCount",
+        json!({ "pool": "BTC.BTC", "startTime": BASE, "endTime": BASE + HOUR }),
+    );
+    db.savers_history_repo.upsert(&[savers]).await.unwrap();
+
+    let state = ActionsSyncState {
+        id: String::from("actions"),
+        from_timestamp: 1,
+        next_page_token: Some(String::from("next")),
+        newest_date: 2,
+    };
+    db.actions_repo.save_sync_state(&state).await.unwrap();
+    db.actions_repo
+        .save_sync_state(&ActionsSyncState {
+            newest_date: 3,
+            ..state
+        })
+        .await
+        .unwrap();
+
+    db.migration_repo
+        .record_applied(&AppliedMigration {
+            id: String::from("0001_test"),
+            description: String::from("Test"),
+            applied_at: BASE,
+            rows: 4,
+        })
+        .await
+        .unwrap();
+
+    let db = init(&backend, &database).await;
+
+    let savers = db
+        .savers_history_repo
+        .fetch_intervals(BASE, BASE + HOUR, Some("BTC.BTC"), TimeInterval::Hour)
+        .await
+        .unwrap();
+    assert_eq!(savers.len(), 1);
+
+    let state = db.actions_repo.get_sync_state("actions").await.unwrap();
+    assert_eq!(state.map(|state| state.newest_date), Some(3));
+
+    let applied = db.migration_repo.fetch_applied().await.unwrap();
+    assert_eq!(applied.len(), 1);
+    assert_eq!(applied[0].rows, 4);
+}
+
+#[cfg(feature = "sqlite")]
+#[actix_web::test]
+async fn sqlite_keeps_the_collections_across_restarts() {
+    collections_are_kept_across_restarts("sqlite://:memory:").await;
+}
+
+#[cfg(feature = "postgres")]
+#[actix_web::test]
+async fn postgres_keeps_the_collections_across_restarts() {
+    collections_are_kept_across_restarts(&postgres_url()).await;
+}
+
+// Depth rows stored before the pool was recorded were all fetched for the default pool.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+async fn depth_rows_without_a_pool_go_to_the_default_pool(url: &str) {
+    use rust_api::utils::migrations::run_migrations;
+
+    let db = stores(url).await;
+    let mut row = depth(BASE, 100.0, true);
+    row.pool = String::new();
+    db.depth_history_repo.upsert(&[row]).await.unwrap();
+
+    run_migrations(&db, false).await.unwrap();
+
+    let rows = db
+        .depth_history_repo
+        .fetch_intervals(BASE, BASE + HOUR, Some("BTC.BTC"), TimeInterval::Hour)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].pool, "BTC.BTC");
+    assert_eq!(db.depth_history_repo.count_missing_pool().await.unwrap(), 0);
+}
+
+#[cfg(feature = "sqlite")]
+#[actix_web::test]
+async fn sqlite_marks_the_pool_of_old_depth_rows() {
+    depth_rows_without_a_pool_go_to_the_default_pool("sqlite://:memory:").await;
+}
+
+#[cfg(feature = "postgres")]
+#[actix_web::test]
+async fn postgres_marks_the_pool_of_old_depth_rows() {
+    depth_rows_without_a_pool_go_to_the_default_pool(&postgres_url()).await;
+}
+
+// Networks are kept in a schema named after their database, whatever its case and characters.
+#[cfg(feature = "postgres")]
+#[actix_web::test]
+async fn postgres_quotes_the_schema() {
+    let backend = Stores::backend(&postgres_url()).await.unwrap();
+    let db = init(&backend, &format!("Api Test {}", database())).await;
+
+    db.swaps_history_repo
+        .upsert(&[swaps(BASE, 1.0, 1.0)])
+        .await
+        .unwrap();
+
+    let swaps = db
+        .swaps_history_repo
+        .fetch_intervals(BASE, BASE + HOUR, None, TimeInterval::Hour)
+        .await
+        .unwrap();
+    assert_eq!(swaps.len(), 1);
+}
+
+macro_rules! api_suite {
+    ($($test:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[actix_web::test]
+                async fn $test() {
+                    super::$test("memory://").await;
+                }
+            )*
+        }
+
+        #[cfg(feature = "sqlite")]
+        mod sqlite {
+            $(
+                #[actix_web::test]
+                async fn $test() {
+                    super::$test("sqlite://:memory:").await;
+                }
+            )*
+        }
+
+        #[cfg(feature = "postgres")]
+        mod postgres {
+            $(
+                #[actix_web::test]
+                async fn $test() {
+                    super::$test(&super::postgres_url()).await;
+                }
+            )*
+        }
+    };
+}
+
+api_suite!(
+    upsert_counts_inserted_and_replaced_rows,
+    incomplete_intervals_are_replaced,
+    days_sum_flows_and_keep_the_last_state,
+    months_sum_flows_and_keep_the_last_state,
+    days_weight_the_average_slip,
+    latest_and_first_incomplete_are_hourly,
+    earnings_keep_their_pools,
+    full_ranges_are_fully_covered,
+    gaps_lower_the_coverage,
+    gaps_are_updated_by_dataset_pool_and_start,
+    member_values_come_from_the_stored_depth_of_their_pool,
+    snapshots_keep_the_fields_unknown_to_the_model,
+    actions_are_found_by_their_keys,
+    quarantine_drift_and_violations_are_updated_in_place,
+    rollups_keep_the_hours_before_their_first_bucket,
+    rollups_in_progress_keep_the_expired_hours,
+    expired_hours_are_not_gaps,
+    rollups_keep_midgards_own_rows,
+);